@@ -1,4 +1,5 @@
-use crate::mesh::half_edge::{VertexId, Mesh3D};
+use crate::mesh::half_edge::{VertexId, Mesh, Mesh3D};
+use crate::geometry::metric::Geometry;
 use crate::geometry::vector::Vector3;
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
@@ -28,31 +29,29 @@ impl PartialOrd for State {
 impl Geodesic {
     /// Compute shortest-path geodesic distances from `source` to all vertices using Dijkstra's algorithm over edge graph.
     pub fn dijkstra(mesh: &Mesh3D<(), ()>, source: VertexId) -> Vec<f64> {
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        Self::dijkstra_with(mesh, positions.as_slice(), source)
+    }
+
+    /// Dijkstra over the edge graph with edge lengths taken from `geometry`.
+    pub fn dijkstra_with<G: Geometry + ?Sized, VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, geometry: &G, source: VertexId) -> Vec<f64> {
         let n = mesh.vertices.len();
         let mut dist = vec![f64::INFINITY; n];
         let mut heap = BinaryHeap::new();
-        dist[source] = 0.0;
+        dist[source.index()] = 0.0;
         heap.push(State { dist: 0.0, v: source });
         while let Some(State { dist: d, v }) = heap.pop() {
-            if d > dist[v] { continue; }
-            // traverse outgoing edges around vertex v
-            let mut he = mesh.vertices[v].he_out;
-            loop {
-                let to = mesh.halfedges[mesh.halfedges[he].twin].from;
-                // edge length via Vector3
-                let p: Vector3 = mesh.vertices[v].attr;
-                let q: Vector3 = mesh.vertices[to].attr;
-                let w = (p - q).norm();
-                let nd = d + w;
-                if nd < dist[to] {
-                    dist[to] = nd;
+            if d > dist[v.index()] { continue; }
+            // traverse neighbors around vertex v
+            for h in mesh.vertex_outgoing_halfedges(v) {
+                let to = mesh.halfedge_to(h);
+                let nd = d + geometry.halfedge_length(mesh, h);
+                if nd < dist[to.index()] {
+                    dist[to.index()] = nd;
                     heap.push(State { dist: nd, v: to });
                 }
-                // next outgoing half-edge
-                he = mesh.halfedges[mesh.halfedges[he].twin].next;
-                if he == mesh.vertices[v].he_out { break; }
             }
         }
         dist
     }
-}
\ No newline at end of file
+}
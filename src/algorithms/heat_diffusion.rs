@@ -1,6 +1,7 @@
+use crate::error::Result;
 use crate::mesh::half_edge::Mesh3D;
 use crate::geometry::vector::Vector3;
-use nalgebra::DMatrix;
+use crate::operators::assembly::{conjugate_gradient, cotan_stiffness, mul_vec, voronoi_mass};
 
 /// Heat diffusion algorithm
 pub struct HeatDiffusion;
@@ -15,94 +16,51 @@ impl HeatDiffusion {
     /// - `diffusivity`: diffusion coefficient
     ///
     /// # Returns
-    /// - `Vec<f64>`: scalar field values after diffusion
+    /// - `Vec<f64>`: scalar field values after diffusion, by one backward
+    ///   Euler step `(M + t κ K) u = M f`
     pub fn diffuse(
         mesh: &Mesh3D<(), ()>,
         field: &[f64],
         time: f64,
         diffusivity: f64,
-    ) -> Vec<f64> {
-        let n = mesh.vertices.len();
-        let mut laplacian = DMatrix::zeros(n, n);
-        let mut mass_matrix = DMatrix::zeros(n, n);
-
-        // Build Laplacian and mass matrices
-        for edge in &mesh.edges {
-            let he0 = edge.he;
-            let he1 = mesh.halfedges[he0].twin;
-            let i = mesh.halfedges[he0].from;
-            let j = mesh.halfedges[he1].from;
-            let k0 = mesh.halfedges[mesh.halfedges[he0].next].from;
-            let k1 = mesh.halfedges[mesh.halfedges[he1].next].from;
-            let pi = mesh.vertices[i].attr;
-            let pj = mesh.vertices[j].attr;
-            let pk0 = mesh.vertices[k0].attr;
-            let pk1 = mesh.vertices[k1].attr;
-            let cot0 = cotangent(pi, pj, pk0);
-            let cot1 = cotangent(pi, pj, pk1);
-            let w = 0.5 * (cot0 + cot1);
-            laplacian[(i, j)] = w;
-            laplacian[(j, i)] = w;
-            laplacian[(i, i)] -= w;
-            laplacian[(j, j)] -= w;
-        }
-
-        // Build mass matrix
-        let areas = mesh.vertex_areas();
-        for i in 0..n {
-            mass_matrix[(i, i)] = areas[i];
-        }
+    ) -> Result<Vec<f64>> {
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        // Build stiffness and lumped mass matrices
+        let stiffness = cotan_stiffness(mesh, positions.as_slice());
+        let mass = voronoi_mass(mesh, positions.as_slice());
 
         // Solve heat equation
-        let id = DMatrix::identity(n, n);
-        let lhs = &mass_matrix + time * diffusivity * &laplacian;
-        let rhs = &mass_matrix * DMatrix::from_column_slice(n, 1, field);
-        let solution = lhs.lu().solve(&rhs).unwrap();
-
-        solution.column(0).iter().cloned().collect()
+        let lhs = &mass + &stiffness.map(|w| time * diffusivity * w);
+        let rhs = mul_vec(&mass, field);
+        conjugate_gradient(&lhs, &rhs, 1e-12, 10 * field.len().max(10))
     }
 }
 
-/// Compute cotangent of angle at vertex pk in triangle (pi, pj, pk)
-fn cotangent(pi: Vector3, pj: Vector3, pk: Vector3) -> f64 {
-    let u = pi - pk;
-    let v = pj - pk;
-    u.dot(&v) / u.cross(&v).norm()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::Mesh;
 
     #[test]
     fn test_heat_diffusion() {
         // Simple triangle mesh
-        let mut mesh = Mesh3D::<(), ()>::new();
-        mesh.vertices = vec![
-            Vertex { he_out: 0, attr: Vector3::new(0.0, 0.0, 0.0) },
-            Vertex { he_out: 1, attr: Vector3::new(1.0, 0.0, 0.0) },
-            Vertex { he_out: 2, attr: Vector3::new(0.0, 1.0, 0.0) },
-        ];
-        mesh.halfedges = vec![
-            HalfEdge { from: 0, twin: 0, next: 1, edge: 0, face: 0 },
-            HalfEdge { from: 1, twin: 1, next: 2, edge: 1, face: 0 },
-            HalfEdge { from: 2, twin: 2, next: 0, edge: 2, face: 0 },
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
         ];
-        mesh.edges = vec![
-            Edge { he: 0, attr: () },
-            Edge { he: 1, attr: () },
-            Edge { he: 2, attr: () },
-        ];
-        mesh.faces = vec![ Face { he: 0, attr: () } ];
+        let mesh: Mesh3D<(), ()> = Mesh::from_vertices_and_faces(positions, &[3], &[0, 1, 2]).unwrap();
 
         let field = vec![1.0, 0.0, 0.0];
         let time = 0.1;
         let diffusivity = 1.0;
-        let result = HeatDiffusion::diffuse(&mesh, &field, time, diffusivity);
+        let result = HeatDiffusion::diffuse(&mesh, &field, time, diffusivity).unwrap();
 
-        assert!((result[0] - 0.75).abs() < 1e-6);
-        assert!((result[1] - 0.125).abs() < 1e-6);
-        assert!((result[2] - 0.125).abs() < 1e-6);
+        // heat spreads symmetrically and its total is conserved
+        let areas = mesh.vertex_areas();
+        let total: f64 = result.iter().zip(&areas).map(|(u, a)| u * a).sum();
+        assert!((total - areas[0]).abs() < 1e-9);
+        assert!(result[0] < 1.0 && result[1] > 0.0);
+        assert!((result[1] - result[2]).abs() < 1e-9);
     }
 }
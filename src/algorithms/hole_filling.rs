@@ -0,0 +1,346 @@
+use crate::error::{Error, Result};
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::{EdgeId, FaceId, HalfEdgeId, Mesh3D, VertexId};
+use crate::operators::assembly::{conjugate_gradient, diagonal, uniform_laplacian};
+use sprs::{CsMat, TriMat};
+use std::f64::consts::PI;
+
+/// Hole filling after Liepa, "Filling Holes in Meshes" (SGP 2003)
+pub struct HoleFilling;
+
+impl HoleFilling {
+    /// Fill every boundary loop with at most `max_boundary_edges` edges, so
+    /// the outer boundary of an open scan can be kept. Returns the faces
+    /// added; ids of existing elements do not change.
+    pub fn fill_holes(mesh: &mut Mesh3D<(), ()>, max_boundary_edges: usize) -> Result<Vec<FaceId>> {
+        let holes: Vec<HalfEdgeId> = mesh
+            .boundary_halfedge_loops()
+            .into_iter()
+            .filter(|boundary| boundary.len() <= max_boundary_edges)
+            .map(|boundary| boundary[0])
+            .collect();
+        let mut faces = Vec::new();
+        for he in holes {
+            faces.extend(Self::fill_hole(mesh, he)?);
+        }
+        Ok(faces)
+    }
+
+    /// Fill the hole bounded by boundary half-edge `he`.
+    ///
+    /// The loop is triangulated by dynamic programming, minimizing the
+    /// largest dihedral angle and then the area, without diagonals that
+    /// already exist as mesh edges; if every triangulation needs one,
+    /// `Error::Topology` is returned and the mesh is left unchanged. The patch is refined until
+    /// its edges match the mean boundary edge length, made Delaunay by edge
+    /// flips, and the new vertices are placed by solving the umbrella
+    /// bi-Laplacian system, whose two-ring stencil reaches into the
+    /// surrounding surface so the patch blends in smoothly.
+    pub fn fill_hole(mesh: &mut Mesh3D<(), ()>, he: HalfEdgeId) -> Result<Vec<FaceId>> {
+        if !mesh.is_boundary_halfedge(he) {
+            return Err(Error::Topology(format!("half-edge {} is not on the boundary", he)));
+        }
+        let mut ring = vec![he];
+        let mut h = mesh.halfedges[he].next;
+        while h != he {
+            ring.push(h);
+            h = mesh.halfedges[h].next;
+        }
+        let n = ring.len();
+        if n < 3 {
+            return Err(Error::Topology(format!("boundary loop of half-edge {} has only {} edges", he, n)));
+        }
+        let first_face = mesh.faces.len();
+        let first_vertex = mesh.vertices.len();
+
+        let best = Self::triangulate_loop(mesh, &ring)?;
+        let loop_vertices: Vec<VertexId> = ring.iter().map(|&h| mesh.halfedges[h].from).collect();
+        let f = mesh.fill_boundary_loop(he, ())?;
+        // the face with chain i..j closed by j -> i is split into triangles
+        let mut stack = vec![(f, 0, n - 1)];
+        while let Some((f, i, j)) = stack.pop() {
+            if j - i < 2 { continue; }
+            let k = best[i][j];
+            let mut rest = f;
+            if k > i + 1 {
+                rest = Self::split_face(mesh, f, loop_vertices[i], loop_vertices[k]);
+                stack.push((f, i, k));
+            }
+            if j > k + 1 {
+                Self::split_face(mesh, rest, loop_vertices[k], loop_vertices[j]);
+                stack.push((rest, k, j));
+            }
+        }
+
+        let target = ring.iter().map(|&h| Self::length(mesh, mesh.halfedges[h].edge)).sum::<f64>() / n as f64;
+        Self::refine(mesh, first_face, target);
+        Self::fair(mesh, first_vertex);
+        mesh.sync_properties();
+        Ok((first_face..mesh.faces.len()).map(FaceId::new).collect())
+    }
+
+    /// Optimal triangulation of the loop: `best[i][j]` is the apex of the
+    /// triangle on chord `(i, j)` in the triangulation of the chain `i..=j`
+    fn triangulate_loop(mesh: &Mesh3D<(), ()>, ring: &[HalfEdgeId]) -> Result<Vec<Vec<usize>>> {
+        let n = ring.len();
+        let vertices: Vec<VertexId> = ring.iter().map(|&h| mesh.halfedges[h].from).collect();
+        let p: Vec<Vector3> = vertices.iter().map(|&v| mesh.vertices[v].attr).collect();
+        // normal of the surface across every boundary edge i -> i + 1
+        let outside: Vec<Vector3> = ring.iter().map(|&h| face_normal(mesh, mesh.halfedges[mesh.halfedges[h].twin].face)).collect();
+        let connected = |a: usize, b: usize| mesh.vertex_vertices(vertices[a]).any(|w| w == vertices[b]);
+        let normal = |i: usize, k: usize, j: usize| (p[k] - p[i]).cross(&(p[j] - p[i])).normalize();
+
+        // (largest dihedral angle, area) of the best triangulation of i..=j
+        let mut weight: Vec<Vec<(f64, f64)>> = vec![vec![(0.0, 0.0); n]; n];
+        let mut best = vec![vec![0; n]; n];
+        for span in 2..n {
+            for i in 0..n - span {
+                let j = i + span;
+                let mut best_weight = (f64::INFINITY, f64::INFINITY);
+                for k in i + 1..j {
+                    // a diagonal along an existing edge would duplicate it
+                    if (k > i + 1 && connected(i, k)) || (j > k + 1 && connected(k, j)) {
+                        continue;
+                    }
+                    if !weight[i][k].0.is_finite() || !weight[k][j].0.is_finite() {
+                        continue;
+                    }
+                    let nt = normal(i, k, j);
+                    let neighbor = |a: usize, b: usize| {
+                        if b == a + 1 { outside[a] } else { normal(a, best[a][b], b) }
+                    };
+                    let mut dihedral = weight[i][k].0.max(weight[k][j].0)
+                        .max(angle(&nt, &neighbor(i, k)))
+                        .max(angle(&nt, &neighbor(k, j)));
+                    if i == 0 && j == n - 1 {
+                        dihedral = dihedral.max(angle(&nt, &outside[n - 1]));
+                    }
+                    let area = weight[i][k].1 + weight[k][j].1 + 0.5 * (p[k] - p[i]).cross(&(p[j] - p[i])).norm();
+                    let better = dihedral < best_weight.0 - 1e-12
+                        || (dihedral <= best_weight.0 + 1e-12 && area < best_weight.1);
+                    if better {
+                        best_weight = (dihedral, area);
+                        best[i][j] = k;
+                    }
+                }
+                weight[i][j] = best_weight;
+            }
+        }
+        if !weight[0][n - 1].0.is_finite() {
+            return Err(Error::Topology(format!(
+                "every triangulation of the hole at half-edge {} duplicates an existing edge",
+                ring[0]
+            )));
+        }
+        Ok(best)
+    }
+
+    /// Split face `f` along a new edge from `x` to `y`; returns the new face,
+    /// the part from `y` back to `x`.
+    fn split_face(mesh: &mut Mesh3D<(), ()>, f: FaceId, x: VertexId, y: VertexId) -> FaceId {
+        let find = |v: VertexId| mesh.face_halfedges(f).find(|&h| mesh.halfedges[h].from == v).unwrap();
+        let (ha, hb) = (find(x), find(y));
+        mesh.insert_diagonal(ha, hb, ());
+        FaceId::new(mesh.faces.len() - 1)
+    }
+
+    /// Split patch edges longer than 4/3 of `target` and flip patch edges
+    /// to the Delaunay configuration, until no edge is too long. Faces from
+    /// `first_face` on form the patch; its boundary edges are left alone.
+    fn refine(mesh: &mut Mesh3D<(), ()>, first_face: usize, target: f64) {
+        let in_patch = |mesh: &Mesh3D<(), ()>, e: EdgeId| {
+            mesh.edge_halfedges(e).iter().all(|&h| {
+                let f = mesh.halfedges[h].face;
+                f.is_valid() && f.index() >= first_face
+            })
+        };
+        let patch_edges = |mesh: &Mesh3D<(), ()>| -> Vec<EdgeId> {
+            mesh.edge_ids().filter(|&e| in_patch(mesh, e)).collect()
+        };
+        // every split shortens the patch edges, so this terminates; the
+        // bound only guards against degenerate input
+        for _ in 0..32 {
+            let mut split = false;
+            for e in patch_edges(mesh) {
+                if Self::length(mesh, e) > 4.0 / 3.0 * target {
+                    let [a, b] = mesh.edge_vertices(e);
+                    let midpoint = (mesh.vertices[a].attr + mesh.vertices[b].attr) * 0.5;
+                    mesh.split_edge(e, midpoint);
+                    split = true;
+                }
+            }
+            for _ in 0..8 {
+                let mut flipped = false;
+                for e in patch_edges(mesh) {
+                    if mesh.is_flip_ok(e) && !Self::is_delaunay(mesh, e) && mesh.flip_edge(e).is_ok() {
+                        flipped = true;
+                    }
+                }
+                if !flipped { break; }
+            }
+            if !split { break; }
+        }
+    }
+
+    /// Whether the angles opposite edge `e` sum to at most pi
+    fn is_delaunay(mesh: &Mesh3D<(), ()>, e: EdgeId) -> bool {
+        let opposite_angle = |h: HalfEdgeId| {
+            let a = mesh.vertices[mesh.halfedges[h].from].attr;
+            let b = mesh.vertices[mesh.halfedge_to(h)].attr;
+            let c = mesh.vertices[mesh.halfedge_to(mesh.halfedges[h].next)].attr;
+            angle(&(a - c), &(b - c))
+        };
+        let [h, t] = mesh.edge_halfedges(e);
+        opposite_angle(h) + opposite_angle(t) <= PI + 1e-12
+    }
+
+    /// Place the vertices from `first_vertex` on by solving `L² x = 0` with
+    /// uniform Laplacian `L`, keeping all other vertices fixed.
+    fn fair(mesh: &mut Mesh3D<(), ()>, first_vertex: usize) {
+        let n = mesh.vertices.len();
+        let m = n - first_vertex;
+        if m == 0 { return; }
+        // with L = -D⁻¹ K for the degrees D and the graph stiffness K, the
+        // rows of L² x = 0 are those of the symmetric K D⁻¹ K x = 0
+        let stiffness = uniform_laplacian(mesh);
+        let inverse_degrees: Vec<f64> = (0..n)
+            .map(|i| stiffness.get(i, i).map_or(0.0, |&d| if d > 0.0 { 1.0 / d } else { 0.0 }))
+            .collect();
+        let bilaplacian = &(&stiffness * &diagonal(&inverse_degrees)) * &stiffness;
+        let mut a = TriMat::new((m, m));
+        let mut b = vec![vec![0.0; m]; 3];
+        for (&c, (r, u)) in bilaplacian.iter() {
+            if r < first_vertex { continue; }
+            if u >= first_vertex {
+                a.add_triplet(r - first_vertex, u - first_vertex, c);
+            } else {
+                let p = mesh.vertices[u].attr;
+                for (d, x) in [p.x, p.y, p.z].into_iter().enumerate() {
+                    b[d][r - first_vertex] -= c * x;
+                }
+            }
+        }
+        let a: CsMat<f64> = a.to_csr();
+        let solve = |rhs: &[f64]| conjugate_gradient(&a, rhs, 1e-12, 10 * m + 100);
+        let (Ok(x), Ok(y), Ok(z)) = (solve(&b[0]), solve(&b[1]), solve(&b[2])) else { return; };
+        for r in 0..m {
+            mesh.vertices[first_vertex + r].attr = Vector3::new(x[r], y[r], z[r]);
+        }
+    }
+
+    fn length(mesh: &Mesh3D<(), ()>, e: EdgeId) -> f64 {
+        let [a, b] = mesh.edge_vertices(e);
+        (mesh.vertices[b].attr - mesh.vertices[a].attr).norm()
+    }
+}
+
+/// Unit normal of face `f` by Newell's method
+fn face_normal(mesh: &Mesh3D<(), ()>, f: FaceId) -> Vector3 {
+    let points: Vec<Vector3> = mesh.face_vertices(f).map(|v| mesh.vertices[v].attr).collect();
+    let n = points.len();
+    (0..n).fold(Vector3::zero(), |acc, i| acc + points[i].cross(&points[(i + 1) % n])).normalize()
+}
+
+/// Angle between two vectors, pi/2 if either vanishes
+fn angle(u: &Vector3, v: &Vector3) -> f64 {
+    let norms = u.norm() * v.norm();
+    if norms == 0.0 { return PI / 2.0; }
+    (u.dot(v) / norms).clamp(-1.0, 1.0).acos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::predicate::orient2d;
+    use crate::mesh::soup::PolygonSoup;
+
+    /// `n` x `n` unit squares in the plane z = `z(x, y)`, without the
+    /// squares in `[lo, hi)²`
+    fn grid_with_hole(n: usize, lo: usize, hi: usize, z: impl Fn(f64, f64) -> f64) -> Mesh3D<(), ()> {
+        let positions: Vec<Vector3> = (0..=n)
+            .flat_map(|i| (0..=n).map(move |j| (i as f64, j as f64)))
+            .map(|(x, y)| Vector3::new(x, y, z(x, y)))
+            .collect();
+        let id = |i: usize, j: usize| i * (n + 1) + j;
+        let mut indices = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                if (lo..hi).contains(&i) && (lo..hi).contains(&j) { continue; }
+                indices.extend_from_slice(&[id(i, j), id(i + 1, j), id(i + 1, j + 1)]);
+                indices.extend_from_slice(&[id(i, j), id(i + 1, j + 1), id(i, j + 1)]);
+            }
+        }
+        let mut soup = PolygonSoup::new(positions, vec![3; indices.len() / 3], indices);
+        soup.remove_unreferenced_vertices();
+        soup.into_mesh().unwrap()
+    }
+
+    #[test]
+    fn test_fill_planar_hole() {
+        let mut mesh = grid_with_hole(5, 1, 4, |_, _| 0.0);
+        assert_eq!(mesh.boundary_loop_count(), 2);
+        let n_vertices = mesh.vertices.len();
+        // the outer boundary has 20 edges and is kept
+        let added = HoleFilling::fill_holes(&mut mesh, 12).unwrap();
+        assert!(!added.is_empty());
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert!(mesh.is_disk());
+        assert!(mesh.vertices.len() > n_vertices, "patch was not refined");
+        let mut area = 0.0;
+        for f in mesh.face_ids() {
+            let p: Vec<Vector3> = mesh.face_vertices(f).map(|v| mesh.vertices[v].attr).collect();
+            assert!(p.iter().all(|q| q.z.abs() < 1e-9));
+            let signed = orient2d(&p[0], &p[1], &p[2]);
+            assert!(signed > 0.0, "face {} is folded over", f);
+            area += 0.5 * signed;
+        }
+        assert!((area - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fill_curved_hole_blends_in() {
+        // a bowl with a hole around its bottom at (3, 3), where the boundary
+        // of the hole has z >= 0.05
+        let z = |x: f64, y: f64| 0.05 * ((x - 3.0).powi(2) + (y - 3.0).powi(2));
+        let mut mesh = grid_with_hole(6, 2, 4, z);
+        let n_vertices = mesh.vertices.len();
+        let he = mesh.boundary_halfedge_loops().into_iter().find(|l| l.len() == 8).unwrap()[0];
+        HoleFilling::fill_hole(&mut mesh, he).unwrap();
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert_eq!(mesh.boundary_loop_count(), 1);
+        // a membrane (harmonic) fill could not dip below its boundary; the
+        // bi-Laplacian patch continues the curvature of the bowl
+        let depths: Vec<f64> = mesh.vertices[n_vertices..].iter().map(|v| v.attr.z).collect();
+        assert!(!depths.is_empty());
+        assert!(depths.iter().any(|&d| d < 0.04), "{:?}", depths);
+        assert!(depths.iter().all(|&d| d > -0.05 && d < 0.1), "{:?}", depths);
+        assert!(HoleFilling::fill_hole(&mut mesh, he).is_err());
+    }
+
+    #[test]
+    fn test_fill_avoids_existing_edges() {
+        // a square folded along its diagonal 0-2, so closing it must use 1-3
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.5),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.5),
+            Vector3::new(0.0, 1.0, 0.0),
+        ];
+        let mut mesh = Mesh3D::<(), ()>::from_vertices_and_faces(positions, &[3, 3], &[0, 1, 2, 0, 2, 3]).unwrap();
+        let he = mesh.boundary_halfedge_loops()[0][0];
+        HoleFilling::fill_hole(&mut mesh, he).unwrap();
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert_eq!(mesh.boundary_loop_count(), 0);
+        let mut edges: Vec<[usize; 2]> = mesh
+            .edge_ids()
+            .map(|e| {
+                let [a, b] = mesh.edge_vertices(e).map(|v| v.index());
+                [a.min(b), a.max(b)]
+            })
+            .collect();
+        let count = edges.len();
+        edges.sort();
+        edges.dedup();
+        assert_eq!(edges.len(), count, "duplicate edge");
+    }
+}
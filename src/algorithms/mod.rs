@@ -1,3 +1,8 @@
 pub mod geodesic;
 pub mod smoothing;
-pub mod parameterization;
\ No newline at end of file
+pub mod parameterization;
+pub mod simplification;
+pub mod hole_filling;
+pub mod heat_diffusion;
+pub mod harmonic_parameterization;
+pub mod spectral_clustering;
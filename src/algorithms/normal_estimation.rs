@@ -10,11 +10,7 @@ impl NormalEstimation {
     pub fn estimate_normals(mesh: &Mesh3D<(), ()>) -> Vec<Vector3> {
         let mut normals = vec![Vector3::zero(); mesh.vertices.len()];
         for (i, vertex) in mesh.vertices.iter().enumerate() {
-            let neighbors = mesh.vertex_neighbors(i);
-            let mut positions = Vec::with_capacity(neighbors.len());
-            for &nbr in &neighbors {
-                positions.push(mesh.vertices[nbr].attr);
-            }
+            let positions: Vec<Vector3> = mesh.vertex_neighbors(i).map(|nbr| mesh.vertices[nbr].attr).collect();
             let cov = Self::covariance_matrix(&positions);
             let eig = cov.symmetric_eigen();
             let normal = Vector3::new(eig.eigenvectors[(0, 0)], eig.eigenvectors[(1, 0)], eig.eigenvectors[(2, 0)]);
@@ -1,5 +1,4 @@
-use crate::mesh::half_edge::{Mesh3D, VertexId, EdgeId};
-use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::{Mesh3D, EdgeId};
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
 
@@ -27,75 +26,58 @@ impl PartialOrd for EdgeCollapse {
 }
 
 impl Simplification {
-    /// Simplify the mesh by collapsing edges until the target number of vertices is reached.
+    /// Simplify the mesh by collapsing the shortest edges until the target
+    /// number of vertices is reached or no edge can be collapsed without
+    /// breaking the manifold. Surviving vertices move to the edge midpoint
+    /// and the mesh is compacted afterwards.
     pub fn edge_collapse(mesh: &mut Mesh3D<(), ()>, target_vertex_count: usize) {
-        let mut heap = BinaryHeap::new();
-        let mut edge_costs = vec![f64::INFINITY; mesh.edges.len()];
-
         // Compute initial edge collapse costs
-        for (e_id, edge) in mesh.edges.iter().enumerate() {
-            let cost = Self::compute_edge_cost(mesh, e_id);
-            edge_costs[e_id] = cost;
-            heap.push(EdgeCollapse { cost, edge: e_id });
-        }
-
-        while mesh.vertices.len() > target_vertex_count {
-            if let Some(EdgeCollapse { cost: _, edge: e_id }) = heap.pop() {
-                if edge_costs[e_id] == f64::INFINITY {
-                    continue;
-                }
-                Self::collapse_edge(mesh, e_id);
-                edge_costs[e_id] = f64::INFINITY;
-
-                // Update costs for affected edges
-                for &he in &mesh.edges[e_id].he {
-                    let twin = mesh.halfedges[he].twin;
-                    let e_twin = mesh.halfedges[twin].edge;
-                    let cost = Self::compute_edge_cost(mesh, e_twin);
-                    edge_costs[e_twin] = cost;
-                    heap.push(EdgeCollapse { cost, edge: e_twin });
-                }
+        let mut edge_costs: Vec<f64> = mesh.edge_ids()
+            .map(|e_id| Self::compute_edge_cost(mesh, e_id))
+            .collect();
+        let mut heap: BinaryHeap<EdgeCollapse> = mesh.edge_ids()
+            .map(|edge| EdgeCollapse { cost: edge_costs[edge.index()], edge })
+            .collect();
+
+        let mut vertex_count = mesh.vertices.len();
+        while vertex_count > target_vertex_count {
+            let Some(EdgeCollapse { cost, edge: e_id }) = heap.pop() else {
+                break;
+            };
+            // skip removed edges and entries superseded by a cost update
+            if mesh.is_edge_deleted(e_id) || cost != edge_costs[e_id.index()] {
+                continue;
+            }
+            let [he, twin] = mesh.edge_halfedges(e_id);
+            let Some(he) = [he, twin].into_iter().find(|&h| mesh.is_collapse_ok(h)) else {
+                continue;
+            };
+            let [v0, v1] = [mesh.halfedges[he].from, mesh.halfedge_to(he)];
+            let new_pos = (mesh.vertices[v0].attr + mesh.vertices[v1].attr) * 0.5;
+            let Ok(v) = mesh.collapse_edge(he) else {
+                continue;
+            };
+            mesh.vertices[v].attr = new_pos;
+            vertex_count -= 1;
+
+            // Update costs for affected edges
+            let ring: Vec<EdgeId> = mesh.vertex_edges(v).collect();
+            for e in ring {
+                let cost = Self::compute_edge_cost(mesh, e);
+                edge_costs[e.index()] = cost;
+                heap.push(EdgeCollapse { cost, edge: e });
             }
         }
+        mesh.garbage_collect();
     }
 
     /// Compute the cost of collapsing an edge
     fn compute_edge_cost(mesh: &Mesh3D<(), ()>, e_id: EdgeId) -> f64 {
-        let he = mesh.edges[e_id].he;
-        let v0 = mesh.halfedges[he].from;
-        let v1 = mesh.halfedges[mesh.halfedges[he].twin].from;
+        let [v0, v1] = mesh.edge_vertices(e_id);
         let p0 = mesh.vertices[v0].attr;
         let p1 = mesh.vertices[v1].attr;
         (p0 - p1).norm()
     }
-
-    /// Collapse an edge by removing one of its vertices and updating the mesh topology
-    fn collapse_edge(mesh: &mut Mesh3D<(), ()>, e_id: EdgeId) {
-        let he = mesh.edges[e_id].he;
-        let v0 = mesh.halfedges[he].from;
-        let v1 = mesh.halfedges[mesh.halfedges[he].twin].from;
-
-        // Update vertex positions
-        let p0 = mesh.vertices[v0].attr;
-        let p1 = mesh.vertices[v1].attr;
-        let new_pos = (p0 + p1) * 0.5;
-        mesh.vertices[v0].attr = new_pos;
-
-        // Remove vertex v1 and update half-edges
-        let mut he = mesh.vertices[v1].he_out;
-        loop {
-            let next_he = mesh.halfedges[he].next;
-            let twin = mesh.halfedges[he].twin;
-            let from = mesh.halfedges[twin].from;
-            mesh.halfedges[twin].from = v0;
-            mesh.halfedges[he].from = v0;
-            he = next_he;
-            if he == mesh.vertices[v1].he_out {
-                break;
-            }
-        }
-        mesh.vertices.remove(v1);
-    }
 }
 
 #[cfg(test)]
@@ -106,35 +88,46 @@ mod tests {
     #[test]
     fn test_edge_collapse() {
         // Create a simple mesh with 4 vertices and 2 faces
-        let mut mesh = Mesh3D::new();
-        mesh.vertices = vec![
-            Vertex { he_out: 0, attr: Vector3::new(0.0, 0.0, 0.0) },
-            Vertex { he_out: 1, attr: Vector3::new(1.0, 0.0, 0.0) },
-            Vertex { he_out: 2, attr: Vector3::new(0.0, 1.0, 0.0) },
-            Vertex { he_out: 3, attr: Vector3::new(1.0, 1.0, 0.0) },
-        ];
-        mesh.halfedges = vec![
-            HalfEdge { from: 0, twin: 1, next: 2, edge: 0, face: 0 },
-            HalfEdge { from: 1, twin: 0, next: 3, edge: 0, face: 0 },
-            HalfEdge { from: 2, twin: 3, next: 0, edge: 1, face: 0 },
-            HalfEdge { from: 1, twin: 2, next: 4, edge: 1, face: 0 },
-            HalfEdge { from: 3, twin: 5, next: 1, edge: 2, face: 1 },
-            HalfEdge { from: 2, twin: 4, next: 3, edge: 2, face: 1 },
-        ];
-        mesh.edges = vec![
-            Edge { he: 0, attr: () },
-            Edge { he: 2, attr: () },
-            Edge { he: 4, attr: () },
-        ];
-        mesh.faces = vec![
-            Face { he: 0, attr: () },
-            Face { he: 4, attr: () },
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
         ];
+        let mut mesh = Mesh3D::<(), ()>::from_vertices_and_faces(positions, &[3, 3], &[0, 1, 2, 0, 2, 3]).unwrap();
 
         // Simplify the mesh to 3 vertices
         Simplification::edge_collapse(&mut mesh, 3);
 
         // Check the number of vertices
         assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.faces.len(), 1);
+        assert!(mesh.is_valid());
+    }
+
+    #[test]
+    fn test_edge_collapse_grid() {
+        // 4x4 grid of vertices, two triangles per cell
+        let n = 4;
+        let positions: Vec<Vector3> = (0..n * n)
+            .map(|i| Vector3::new((i % n) as f64, (i / n) as f64, 0.0))
+            .collect();
+        let mut indices = Vec::new();
+        for y in 0..n - 1 {
+            for x in 0..n - 1 {
+                let v = y * n + x;
+                indices.extend_from_slice(&[v, v + 1, v + n + 1, v, v + n + 1, v + n]);
+            }
+        }
+        let counts = vec![3; indices.len() / 3];
+        let mut mesh = Mesh3D::<(), ()>::from_vertices_and_faces(positions, &counts, &indices).unwrap();
+
+        Simplification::edge_collapse(&mut mesh, 8);
+
+        assert_eq!(mesh.vertices.len(), 8);
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        // still a disk: V - E + F = 1
+        let euler = mesh.vertices.len() as i64 - mesh.edges.len() as i64 + mesh.faces.len() as i64;
+        assert_eq!(euler, 1);
     }
 }
@@ -25,17 +25,11 @@ impl Smoothing {
 
         for _ in 0..iterations {
             // compute Laplacian displacement
-            for i in 0..n {
-                let mut sum = Vector3::zero();
-                let mut cnt = 0.0;
-                let mut he = mesh.vertices[i].he_out;
-                loop {
-                    let nbr = mesh.halfedges[mesh.halfedges[he].twin].from;
-                    sum = sum + positions[nbr];
-                    cnt += 1.0;
-                    he = mesh.halfedges[mesh.halfedges[he].twin].next;
-                    if he == mesh.vertices[i].he_out { break; }
-                }
+            for v in mesh.vertex_ids() {
+                let i = v.index();
+                let (sum, cnt) = mesh
+                    .vertex_neighbors(v)
+                    .fold((Vector3::zero(), 0.0), |(sum, cnt), nbr| (sum + positions[nbr.index()], cnt + 1.0));
                 if cnt > 0.0 && area[i] != 0.0 {
                     // uniform neighbor average minus pos, normalized by area
                     let avg = sum / cnt;
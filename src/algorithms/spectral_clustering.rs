@@ -1,13 +1,25 @@
-use nalgebra::{DMatrix, DVector};
-use sprs::{CsMat, CsVec};
-use std::collections::HashMap;
+//! Spectral clustering of mesh vertices
+//!
+//! Vertices are embedded by the eigenvectors of the `k` smallest eigenvalues
+//! of the uniform graph Laplacian and grouped by k-means in that embedding.
+//! The eigenvectors come from a dense eigendecomposition, so this is meant
+//! for meshes of up to a few thousand vertices.
+
+use crate::mesh::half_edge::Mesh3D;
+use crate::operators::assembly::uniform_laplacian;
+use nalgebra::{DMatrix, SymmetricEigen};
+use sprs::CsMat;
 
 /// Spectral clustering algorithm
 pub struct SpectralClustering;
 
 impl SpectralClustering {
-    /// Perform spectral clustering on a mesh
+    /// Cluster label in `0..k` of every vertex slot
     pub fn cluster(mesh: &Mesh3D<(), ()>, k: usize) -> Vec<usize> {
+        let n = mesh.vertices.len();
+        if k == 0 || n == 0 {
+            return vec![0; n];
+        }
         let laplacian = Self::compute_laplacian(mesh);
         let eigenvectors = Self::compute_eigenvectors(&laplacian, k);
         Self::kmeans(&eigenvectors, k)
@@ -15,107 +27,85 @@ impl SpectralClustering {
 
     /// Compute the Laplacian matrix of the mesh
     fn compute_laplacian(mesh: &Mesh3D<(), ()>) -> CsMat<f64> {
-        let n = mesh.vertices.len();
-        let mut laplacian = CsMat::zero((n, n));
-        let mut degree = vec![0.0; n];
-
-        for edge in &mesh.edges {
-            let he0 = edge.he;
-            let he1 = mesh.halfedges[he0].twin;
-            let i = mesh.halfedges[he0].from;
-            let j = mesh.halfedges[he1].from;
-            let weight = 1.0; // Uniform weight for simplicity
-
-            laplacian.insert(i, j, -weight);
-            laplacian.insert(j, i, -weight);
-            degree[i] += weight;
-            degree[j] += weight;
-        }
-
-        for i in 0..n {
-            laplacian.insert(i, i, degree[i]);
-        }
-
-        laplacian
+        uniform_laplacian(mesh)
     }
 
-    /// Compute the eigenvectors of the Laplacian matrix
+    /// Eigenvectors of the `k` smallest eigenvalues, one per column
     fn compute_eigenvectors(laplacian: &CsMat<f64>, k: usize) -> DMatrix<f64> {
-        let (n, _) = laplacian.shape();
-        let mut eigenvectors = DMatrix::zeros(n, k);
-
-        // Placeholder for actual eigenvalue decomposition
-        // Replace with a proper eigenvalue solver
-        for i in 0..k {
-            for j in 0..n {
-                eigenvectors[(j, i)] = laplacian.get(j, i).unwrap_or(&0.0).clone();
-            }
+        let n = laplacian.rows();
+        let mut dense = DMatrix::zeros(n, n);
+        for (&value, (i, j)) in laplacian.iter() {
+            dense[(i, j)] += value;
         }
-
-        eigenvectors
+        let eigen: SymmetricEigen<f64, _> = SymmetricEigen::new(dense);
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| eigen.eigenvalues[a].total_cmp(&eigen.eigenvalues[b]));
+        DMatrix::from_fn(n, k.min(n), |i, c| eigen.eigenvectors[(i, order[c])])
     }
 
-    /// Perform k-means clustering on the eigenvectors
-    fn kmeans(eigenvectors: &DMatrix<f64>, k: usize) -> Vec<usize> {
-        let n = eigenvectors.nrows();
-        let mut clusters = vec![0; n];
-        let mut centroids = DMatrix::zeros(k, eigenvectors.ncols());
-
-        // Initialize centroids randomly
-        for i in 0..k {
-            for j in 0..eigenvectors.ncols() {
-                centroids[(i, j)] = eigenvectors[(i, j)];
+    /// Lloyd's k-means on the rows of `points`, seeded by farthest-point
+    /// sampling from the first row
+    fn kmeans(points: &DMatrix<f64>, k: usize) -> Vec<usize> {
+        let n = points.nrows();
+        let k = k.min(n);
+        let distance = |i: usize, centroids: &DMatrix<f64>, c: usize| (points.row(i) - centroids.row(c)).norm_squared();
+
+        let mut centroids = DMatrix::zeros(k, points.ncols());
+        centroids.set_row(0, &points.row(0));
+        let mut nearest = vec![f64::INFINITY; n];
+        for c in 1..k {
+            for (i, d) in nearest.iter_mut().enumerate() {
+                *d = d.min(distance(i, &centroids, c - 1));
             }
+            let farthest = (0..n).max_by(|&a, &b| nearest[a].total_cmp(&nearest[b])).unwrap_or(0);
+            centroids.set_row(c, &points.row(farthest));
         }
 
-        // Placeholder for actual k-means algorithm
-        // Replace with a proper k-means implementation
-        for i in 0..n {
-            clusters[i] = i % k;
+        let mut labels = vec![0; n];
+        for iteration in 0..100 {
+            let mut changed = false;
+            for (i, label) in labels.iter_mut().enumerate() {
+                let best = (0..k).min_by(|&a, &b| distance(i, &centroids, a).total_cmp(&distance(i, &centroids, b)));
+                let best = best.unwrap_or(0);
+                changed |= best != *label;
+                *label = best;
+            }
+            if !changed && iteration > 0 {
+                break;
+            }
+            let mut sums = DMatrix::zeros(k, points.ncols());
+            let mut counts = vec![0usize; k];
+            for (i, &label) in labels.iter().enumerate() {
+                let sum = sums.row(label) + points.row(i);
+                sums.set_row(label, &sum);
+                counts[label] += 1;
+            }
+            for (c, &count) in counts.iter().enumerate() {
+                if count > 0 {
+                    centroids.set_row(c, &(sums.row(c) / count as f64));
+                }
+            }
         }
-
-        clusters
+        labels
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mesh::half_edge::{Mesh3D, Vertex, HalfEdge, Edge, Face};
     use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::Mesh;
 
     #[test]
     fn test_spectral_clustering() {
-        // Create a simple mesh with 4 vertices and 2 faces
-        let mut mesh = Mesh3D::<(), ()>::new();
-        mesh.vertices = vec![
-            Vertex { he_out: 0, attr: Vector3::new(0.0, 0.0, 0.0) },
-            Vertex { he_out: 1, attr: Vector3::new(1.0, 0.0, 0.0) },
-            Vertex { he_out: 2, attr: Vector3::new(0.0, 1.0, 0.0) },
-            Vertex { he_out: 3, attr: Vector3::new(1.0, 1.0, 0.0) },
-        ];
-        mesh.halfedges = vec![
-            HalfEdge { from: 0, twin: 1, next: 2, edge: 0, face: 0 },
-            HalfEdge { from: 1, twin: 0, next: 3, edge: 0, face: 0 },
-            HalfEdge { from: 2, twin: 3, next: 0, edge: 1, face: 0 },
-            HalfEdge { from: 3, twin: 2, next: 1, edge: 1, face: 0 },
-            HalfEdge { from: 1, twin: 5, next: 6, edge: 2, face: 1 },
-            HalfEdge { from: 3, twin: 4, next: 7, edge: 2, face: 1 },
-            HalfEdge { from: 2, twin: 7, next: 4, edge: 3, face: 1 },
-            HalfEdge { from: 3, twin: 6, next: 5, edge: 3, face: 1 },
-        ];
-        mesh.edges = vec![
-            Edge { he: 0, attr: () },
-            Edge { he: 2, attr: () },
-            Edge { he: 4, attr: () },
-            Edge { he: 6, attr: () },
-        ];
-        mesh.faces = vec![
-            Face { he: 0, attr: () },
-            Face { he: 4, attr: () },
-        ];
-
+        // two separate quads, each split into two triangles
+        let positions = (0..8).map(|i| Vector3::new((i % 2) as f64 + 3.0 * (i / 4) as f64, ((i / 2) % 2) as f64, 0.0)).collect();
+        let mesh: Mesh3D<(), ()> =
+            Mesh::from_vertices_and_faces(positions, &[3; 4], &[0, 1, 3, 0, 3, 2, 4, 5, 7, 4, 7, 6]).unwrap();
         let clusters = SpectralClustering::cluster(&mesh, 2);
-        assert_eq!(clusters.len(), 4);
+        assert_eq!(clusters.len(), 8);
+        assert!(clusters[..4].iter().all(|&c| c == clusters[0]));
+        assert!(clusters[4..].iter().all(|&c| c == clusters[4]));
+        assert_ne!(clusters[0], clusters[4]);
     }
 }
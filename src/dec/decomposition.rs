@@ -0,0 +1,224 @@
+//! Hodge–Helmholtz decomposition
+//!
+//! Every primal 1-form splits as `ω = dα + δβ + γ` into an exact part (the
+//! gradient of a scalar potential `α`), a coexact part (the rotated gradient
+//! of a vector potential `β`, a 2-form) and a harmonic part, closed and
+//! coclosed. The three parts are orthogonal in the inner product
+//! `⟨a, b⟩ = aᵀ *1 b`. Per-face tangent fields are converted by edge
+//! integration; [`Form1::whitney`] maps the parts back to face vectors.
+//!
+//! The potentials are solved with `d0`, `d1` and `*1` rather than the
+//! per-element [`Gradient`], [`Divergence`] and [`Curl`]: the parts are only
+//! orthogonal when divergence and curl are the adjoints of the derivatives
+//! in the inner products given by the Hodge stars, which `d0ᵀ *1` and
+//! `*1⁻¹ d1ᵀ *2` are by construction. `Divergence` and `Curl` project onto unweighted edge
+//! vectors and divide by Voronoi areas, so they are not adjoint to
+//! `Gradient`, which in turn returns the Whitney gradient scaled by twice the
+//! face area.
+//!
+//! [`Gradient`]: crate::operators::gradient::Gradient
+//! [`Divergence`]: crate::operators::divergence::Divergence
+//! [`Curl`]: crate::operators::curl::Curl
+
+use crate::dec::exterior::{d0, d1};
+use crate::dec::forms::{Form0, Form1, Form2};
+use crate::dec::hodge::{hodge1, hodge2, DualKind};
+use crate::error::Result;
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::Mesh3D;
+use crate::operators::assembly::{conjugate_gradient, mul_vec};
+use sprs::{CsMat, TriMat};
+
+/// Hodge–Helmholtz decomposition of a 1-form
+pub struct HodgeDecomposition<'a> {
+    /// Curl-free part `dα`
+    pub exact: Form1<'a>,
+    /// Divergence-free part `δβ`
+    pub coexact: Form1<'a>,
+    /// Harmonic part, curl- and divergence-free
+    pub harmonic: Form1<'a>,
+    /// Scalar potential `α`, zero at vertex 0
+    pub scalar_potential: Form0<'a>,
+    /// Vector potential `β`, with `*2 β` summing to zero on closed meshes
+    pub vector_potential: Form2<'a>,
+}
+
+impl<'a> HodgeDecomposition<'a> {
+    /// Decompose a per-face tangent vector field on `mesh`
+    pub fn of_field(mesh: &'a Mesh3D<(), ()>, field: &[Vector3], dual: DualKind) -> Result<Self> {
+        Self::of_form(&Form1::from_face_vectors(mesh, field), dual)
+    }
+
+    /// Decompose the 1-form `form`, with Hodge stars of the given dual mesh
+    pub fn of_form(form: &Form1<'a>, dual: DualKind) -> Result<Self> {
+        let mesh = form.mesh;
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        let (d0, d1) = (d0(mesh), d1(mesh));
+        let star1 = hodge1(mesh, positions.as_slice(), dual);
+        let star1_inv = star1.map(|&s| if s != 0.0 { 1.0 / s } else { 0.0 });
+        let star2 = hodge2(mesh, positions.as_slice());
+        let iterations = 10 * (mesh.vertices.len() + mesh.faces.len()).max(10);
+
+        // d0ᵀ *1 d0 α = d0ᵀ *1 ω, singular on constants
+        let d0t = d0.transpose_view().to_csr();
+        let laplacian = &(&d0t * &star1) * &d0;
+        let rhs = mul_vec(&d0t, &mul_vec(&star1, &form.values));
+        let alpha = conjugate_gradient(&pinned(&laplacian), &rhs, 1e-12, iterations)?;
+        let exact = mul_vec(&d0, &alpha);
+
+        // d1 *1⁻¹ d1ᵀ β̃ = d1 ω with β̃ = *2 β, singular on constants
+        // without boundary
+        let d1t = d1.transpose_view().to_csr();
+        let operator = &(&d1 * &star1_inv) * &d1t;
+        let closed = !mesh.edge_ids().any(|e| mesh.is_boundary_edge(e));
+        let operator = if closed { pinned(&operator) } else { operator };
+        let rhs = mul_vec(&d1, &form.values);
+        let mut beta_star = conjugate_gradient(&operator, &rhs, 1e-12, iterations)?;
+        if closed {
+            let mean = beta_star.iter().sum::<f64>() / beta_star.len().max(1) as f64;
+            beta_star.iter_mut().for_each(|b| *b -= mean);
+        }
+        let coexact = mul_vec(&star1_inv, &mul_vec(&d1t, &beta_star));
+        let star2_inv = star2.map(|&s| if s != 0.0 { 1.0 / s } else { 0.0 });
+        let beta = mul_vec(&star2_inv, &beta_star);
+
+        let harmonic = form.values.iter().zip(&exact).zip(&coexact).map(|((w, a), b)| w - a - b).collect();
+        Ok(HodgeDecomposition {
+            exact: Form1::new(mesh, exact)?,
+            coexact: Form1::new(mesh, coexact)?,
+            harmonic: Form1::new(mesh, harmonic)?,
+            scalar_potential: Form0::new(mesh, alpha)?,
+            vector_potential: Form2::new(mesh, beta)?,
+        })
+    }
+}
+
+/// Inner product `aᵀ *1 b` of two primal 1-forms
+pub fn inner_product(a: &Form1, b: &Form1, dual: DualKind) -> f64 {
+    let positions: Vec<Vector3> = a.mesh.vertices.iter().map(|v| v.attr).collect();
+    let star1 = hodge1(a.mesh, positions.as_slice(), dual);
+    a.values.iter().zip(mul_vec(&star1, &b.values)).map(|(x, y)| x * y).sum()
+}
+
+/// `matrix` with one added to its first diagonal entry. For a positive
+/// semidefinite matrix whose kernel is the constants, this is positive
+/// definite and has the same solutions, pinned to zero at index 0, for
+/// right-hand sides orthogonal to the constants.
+fn pinned(matrix: &CsMat<f64>) -> CsMat<f64> {
+    let n = matrix.rows();
+    let mut pin = TriMat::new((n, n));
+    if n > 0 {
+        pin.add_triplet(0, 0, 1.0);
+    }
+    let pin: CsMat<f64> = pin.to_csr();
+    matrix + &pin
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dec::fixtures::torus;
+    use crate::geometry::metric::Geometry;
+    use crate::mesh::half_edge::Mesh;
+    use crate::operators::gradient::Gradient;
+
+    /// Disk of `n × n` points on a sheared triangular lattice, bent into
+    /// a saddle, with acute triangles
+    fn disk(n: usize) -> Mesh3D<(), ()> {
+        let h = 0.75f64.sqrt();
+        let positions = (0..n * n)
+            .map(|k| {
+                let (i, j) = (k % n, k / n);
+                let (x, y) = ((i as f64 + 0.5 * (j % 2) as f64) / n as f64, j as f64 * h / n as f64);
+                Vector3::new(x, y, 0.2 * (x - 0.5) * (y - 0.4))
+            })
+            .collect();
+        let id = |i: usize, j: usize| j * n + i;
+        let mut indices = Vec::new();
+        for j in 0..n - 1 {
+            for i in 0..n - 1 {
+                if j % 2 == 0 {
+                    indices.extend([id(i, j), id(i + 1, j), id(i, j + 1)]);
+                    indices.extend([id(i + 1, j), id(i + 1, j + 1), id(i, j + 1)]);
+                } else {
+                    indices.extend([id(i, j), id(i + 1, j), id(i + 1, j + 1)]);
+                    indices.extend([id(i, j), id(i + 1, j + 1), id(i, j + 1)]);
+                }
+            }
+        }
+        Mesh::from_vertices_and_faces(positions, &vec![3; 2 * (n - 1) * (n - 1)], &indices).unwrap()
+    }
+
+    /// Smooth tangent field sampled at the face barycenters
+    fn field(mesh: &Mesh3D<(), ()>) -> Vec<Vector3> {
+        mesh.face_ids()
+            .map(|f| {
+                let c = mesh.face_vertices(f).fold(Vector3::zero(), |acc, v| acc + mesh.vertices[v].attr) / 3.0;
+                Vector3::new(c.y.sin() + c.z, c.x * c.z - 1.0, c.x + (2.0 * c.y).cos())
+            })
+            .collect()
+    }
+
+    /// Check that the parts are pairwise orthogonal and the harmonic part is
+    /// closed and coclosed; returns the squared norms of the exact plus
+    /// coexact and of the harmonic part
+    fn assert_orthogonal(parts: &HodgeDecomposition, dual: DualKind) -> (f64, f64) {
+        let (exact, coexact, harmonic) = (&parts.exact, &parts.coexact, &parts.harmonic);
+        let scale = inner_product(exact, exact, dual) + inner_product(coexact, coexact, dual);
+        assert!(inner_product(exact, coexact, dual).abs() < 1e-8 * scale);
+        assert!(inner_product(exact, harmonic, dual).abs() < 1e-8 * scale);
+        assert!(inner_product(coexact, harmonic, dual).abs() < 1e-8 * scale);
+        assert!(harmonic.d().values.iter().all(|v| v.abs() < 1e-8));
+        let codifferential = harmonic.star(dual).d();
+        assert!(codifferential.values.iter().all(|v| v.abs() < 1e-8));
+        // the exact part is the derivative of the scalar potential
+        let d_alpha = parts.scalar_potential.d();
+        assert!(d_alpha.values.iter().zip(&exact.values).all(|(a, b)| (a - b).abs() < 1e-12));
+        (scale, inner_product(harmonic, harmonic, dual))
+    }
+
+    #[test]
+    fn test_components_are_orthogonal() {
+        let mesh = torus(12, 8);
+        let dual = DualKind::Barycentric;
+        let parts = HodgeDecomposition::of_field(&mesh, &field(&mesh), dual).unwrap();
+        // the harmonic part is nonzero on a torus
+        let (scale, harmonic) = assert_orthogonal(&parts, dual);
+        assert!(harmonic > 1e-6 * scale);
+        // the pointwise gradient operator agrees with the exact part up to
+        // its scaling by twice the face area
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        let gradient = Gradient::compute(&mesh, &parts.scalar_potential.values);
+        for (f, (g, w)) in mesh.face_ids().zip(gradient.iter().zip(parts.exact.whitney())) {
+            let area = positions.as_slice().face_area(&mesh, f);
+            assert!((*g - w * (2.0 * area)).norm() < 1e-9 * (1.0 + g.norm()));
+        }
+    }
+
+    #[test]
+    fn test_disk_has_no_harmonic_part() {
+        let mesh = disk(8);
+        assert!(mesh.is_disk());
+        let dual = DualKind::Barycentric;
+        let parts = HodgeDecomposition::of_field(&mesh, &field(&mesh), dual).unwrap();
+        let (scale, harmonic) = assert_orthogonal(&parts, dual);
+        assert!(harmonic < 1e-16 * scale);
+        // nothing pins the vector potential on a disk
+        assert!(parts.vector_potential.values.iter().sum::<f64>().abs() > 1e-6);
+    }
+
+    #[test]
+    fn test_circumcentric_decomposition() {
+        // circumcentric stars need positive dual edge lengths, which the
+        // cyclic quads of the torus do not give across their diagonals
+        let mesh = disk(8);
+        let dual = DualKind::Circumcentric;
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        assert!(hodge1(&mesh, positions.as_slice(), dual).diag().data().iter().all(|&s| s > 0.0));
+        let parts = HodgeDecomposition::of_field(&mesh, &field(&mesh), dual).unwrap();
+        let (scale, harmonic) = assert_orthogonal(&parts, dual);
+        assert!(harmonic < 1e-16 * scale);
+        let barycentric = HodgeDecomposition::of_field(&mesh, &field(&mesh), DualKind::Barycentric).unwrap();
+        assert!(parts.exact.values.iter().zip(&barycentric.exact.values).any(|(a, b)| (a - b).abs() > 1e-9));
+    }
+}
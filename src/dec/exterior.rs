@@ -0,0 +1,58 @@
+use crate::mesh::half_edge::Mesh;
+use sprs::{CsMat, TriMat};
+
+/// Exterior derivative on 0-forms: `(d0 f)_e = f(head) - f(tail)`, an
+/// `edges × vertices` matrix
+pub fn d0<VA, EA, FA>(mesh: &Mesh<VA, EA, FA>) -> CsMat<f64> {
+    let mut triplets = TriMat::new((mesh.edges.len(), mesh.vertices.len()));
+    for e in mesh.edge_ids() {
+        let [tail, head] = mesh.edge_vertices(e);
+        triplets.add_triplet(e.index(), tail.index(), -1.0);
+        triplets.add_triplet(e.index(), head.index(), 1.0);
+    }
+    triplets.to_csr()
+}
+
+/// Exterior derivative on 1-forms: `(d1 ω)_f` is the sum of `ω` around the
+/// boundary of face `f`, signed by edge orientation; a `faces × edges`
+/// matrix. Since every edge appears once with each sign in a closed cycle,
+/// `d1 * d0 = 0` exactly.
+pub fn d1<VA, EA, FA>(mesh: &Mesh<VA, EA, FA>) -> CsMat<f64> {
+    let mut triplets = TriMat::new((mesh.faces.len(), mesh.edges.len()));
+    for f in mesh.face_ids() {
+        for h in mesh.face_halfedges(f) {
+            let e = mesh.halfedges[h].edge;
+            let sign = if mesh.edges[e].he == h { 1.0 } else { -1.0 };
+            triplets.add_triplet(f.index(), e.index(), sign);
+        }
+    }
+    triplets.to_csr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::Mesh3D;
+
+    #[test]
+    fn test_d1_d0_vanishes() {
+        // a quad and a triangle, with boundary
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(2.0, 0.5, 0.0),
+        ];
+        let mesh: Mesh3D<(), ()> = Mesh::from_vertices_and_faces(positions, &[4, 3], &[0, 1, 2, 3, 1, 4, 2]).unwrap();
+        let (d0, d1) = (d0(&mesh), d1(&mesh));
+        assert_eq!(d0.shape(), (6, 5));
+        assert_eq!(d1.shape(), (2, 6));
+        let product = &d1 * &d0;
+        assert!(product.iter().all(|(&v, _)| v == 0.0));
+        // the shared edge has opposite signs in its two faces
+        let shared = mesh.edge_ids().find(|&e| !mesh.is_boundary_edge(e)).unwrap();
+        assert_eq!(d1.get(0, shared.index()).unwrap() + d1.get(1, shared.index()).unwrap(), 0.0);
+    }
+}
@@ -0,0 +1,26 @@
+//! Meshes shared by the DEC tests
+
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::{Mesh, Mesh3D};
+use std::f64::consts::PI;
+
+/// Torus of `n × m` quads, each split into two triangles
+pub(crate) fn torus(n: usize, m: usize) -> Mesh3D<(), ()> {
+    let mut positions = Vec::new();
+    for i in 0..n {
+        for j in 0..m {
+            let (u, v) = (2.0 * PI * i as f64 / n as f64, 2.0 * PI * j as f64 / m as f64);
+            let r = 2.0 + 0.7 * v.cos();
+            positions.push(Vector3::new(r * u.cos(), r * u.sin(), 0.7 * v.sin()));
+        }
+    }
+    let id = |i: usize, j: usize| (i % n) * m + j % m;
+    let mut indices = Vec::new();
+    for i in 0..n {
+        for j in 0..m {
+            indices.extend([id(i, j), id(i + 1, j), id(i + 1, j + 1)]);
+            indices.extend([id(i, j), id(i + 1, j + 1), id(i, j + 1)]);
+        }
+    }
+    Mesh::from_vertices_and_faces(positions, &vec![3; 2 * n * m], &indices).unwrap()
+}
@@ -0,0 +1,397 @@
+//! Typed discrete differential forms
+//!
+//! A [`Form`] keeps its values together with the mesh they live on, its
+//! degree `K` and whether it is a primal form (on vertices, edges, faces) or
+//! a dual one (on dual cells, i.e. on faces, edges, vertices). Values are
+//! integrals over the cells, oriented as described in [`crate::dec`].
+
+use crate::dec::exterior::{d0, d1};
+use crate::dec::hodge::{hodge0, hodge1, hodge2, DualKind};
+use crate::error::{Error, Result};
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::{FaceId, HalfEdgeId, Mesh3D, VertexId};
+use crate::operators::assembly::mul_vec;
+use sprs::CsMat;
+use std::marker::PhantomData;
+
+/// Cell complex a form is discretized on
+pub trait Complex {
+    /// Whether forms live on the dual cells
+    const DUAL: bool;
+}
+
+/// Forms on the primal mesh
+#[derive(Debug, Clone, Copy)]
+pub struct Primal;
+/// Forms on the dual mesh
+#[derive(Debug, Clone, Copy)]
+pub struct Dual;
+
+impl Complex for Primal {
+    const DUAL: bool = false;
+}
+
+impl Complex for Dual {
+    const DUAL: bool = true;
+}
+
+/// Discrete `K`-form on `mesh`
+#[derive(Debug, Clone)]
+pub struct Form<'a, const K: usize, C = Primal> {
+    /// The mesh the form lives on
+    pub mesh: &'a Mesh3D<(), ()>,
+    /// One value per cell, indexed by raw element index
+    pub values: Vec<f64>,
+    complex: PhantomData<C>,
+}
+
+/// Discrete 0-form: primal on vertices, dual on faces
+pub type Form0<'a, C = Primal> = Form<'a, 0, C>;
+/// Discrete 1-form: primal or dual on edges
+pub type Form1<'a, C = Primal> = Form<'a, 1, C>;
+/// Discrete 2-form: primal on faces, dual on vertices
+pub type Form2<'a, C = Primal> = Form<'a, 2, C>;
+
+impl<'a, const K: usize> Form<'a, K, Primal> {
+    /// Primal form with the given cell values. Returns `Error::Topology` if
+    /// their number does not match the cells of `mesh`.
+    pub fn new(mesh: &'a Mesh3D<(), ()>, values: Vec<f64>) -> Result<Self> {
+        Self::with_values(mesh, values)
+    }
+
+    /// Zero primal form on `mesh`
+    pub fn zeros(mesh: &'a Mesh3D<(), ()>) -> Self {
+        Form { mesh, values: vec![0.0; Self::cell_count(mesh)], complex: PhantomData }
+    }
+}
+
+impl<'a, const K: usize> Form<'a, K, Dual> {
+    /// Dual form with the given cell values. Returns `Error::Topology` if
+    /// their number does not match the dual cells of `mesh`.
+    pub fn new_dual(mesh: &'a Mesh3D<(), ()>, values: Vec<f64>) -> Result<Self> {
+        Self::with_values(mesh, values)
+    }
+
+    /// Zero dual form on `mesh`
+    pub fn zeros_dual(mesh: &'a Mesh3D<(), ()>) -> Self {
+        Form { mesh, values: vec![0.0; Self::cell_count(mesh)], complex: PhantomData }
+    }
+}
+
+impl<'a, const K: usize, C: Complex> Form<'a, K, C> {
+    fn with_values(mesh: &'a Mesh3D<(), ()>, values: Vec<f64>) -> Result<Self> {
+        let cells = Self::cell_count(mesh);
+        if values.len() != cells {
+            return Err(Error::Topology(format!("{}-form has {} values for {} cells", K, values.len(), cells)));
+        }
+        Ok(Form { mesh, values, complex: PhantomData })
+    }
+
+    /// Number of cells carrying a value
+    fn cell_count(mesh: &Mesh3D<(), ()>) -> usize {
+        match if C::DUAL { 2 - K } else { K } {
+            0 => mesh.vertices.len(),
+            1 => mesh.edges.len(),
+            _ => mesh.faces.len(),
+        }
+    }
+
+    /// Form on the same mesh from `matrix` applied to the values
+    fn mapped<const L: usize, D: Complex>(&self, matrix: &CsMat<f64>) -> Form<'a, L, D> {
+        Form { mesh: self.mesh, values: mul_vec(matrix, &self.values), complex: PhantomData }
+    }
+}
+
+impl<'a, const K: usize, C: Complex> std::ops::Add for Form<'a, K, C> {
+    type Output = Self;
+    /// # Panics
+    /// If the forms live on different meshes.
+    fn add(mut self, other: Self) -> Self {
+        assert!(std::ptr::eq(self.mesh, other.mesh), "cannot add forms on different meshes");
+        for (a, b) in self.values.iter_mut().zip(other.values) {
+            *a += b;
+        }
+        self
+    }
+}
+
+impl<'a, const K: usize, C: Complex> std::ops::Mul<f64> for Form<'a, K, C> {
+    type Output = Self;
+    fn mul(mut self, s: f64) -> Self {
+        for a in &mut self.values {
+            *a *= s;
+        }
+        self
+    }
+}
+
+impl<'a> Form0<'a> {
+    /// Exterior derivative `d0`
+    pub fn d(&self) -> Form1<'a> {
+        self.mapped(&d0(self.mesh))
+    }
+}
+
+impl<'a> Form1<'a> {
+    /// Exterior derivative `d1`
+    pub fn d(&self) -> Form2<'a> {
+        self.mapped(&d1(self.mesh))
+    }
+}
+
+impl<'a> Form0<'a, Dual> {
+    /// Dual exterior derivative `d1ᵀ`
+    pub fn d(&self) -> Form1<'a, Dual> {
+        self.mapped(&d1(self.mesh).transpose_into().to_csr())
+    }
+}
+
+impl<'a> Form1<'a, Dual> {
+    /// Dual exterior derivative `d0ᵀ`
+    pub fn d(&self) -> Form2<'a, Dual> {
+        self.mapped(&d0(self.mesh).transpose_into().to_csr())
+    }
+}
+
+/// Hodge star between a primal `K`-form and a dual `(2 - K)`-form, both ways.
+/// The star of 2-forms takes no [`DualKind`], see [`hodge2`].
+macro_rules! star {
+    ($k:literal, $dual_k:literal, $hodge:ident($($dual:ident)?)) => {
+        impl<'a> Form<'a, $k, Primal> {
+            /// Hodge star onto the dual mesh
+            pub fn star(&self $(, $dual: DualKind)?) -> Form<'a, $dual_k, Dual> {
+                self.mapped(&$hodge(self.mesh, positions(self.mesh).as_slice() $(, $dual)?))
+            }
+        }
+
+        impl<'a> Form<'a, $dual_k, Dual> {
+            /// Inverse Hodge star back onto the primal mesh
+            pub fn star(&self $(, $dual: DualKind)?) -> Form<'a, $k, Primal> {
+                let star = $hodge(self.mesh, positions(self.mesh).as_slice() $(, $dual)?);
+                self.mapped(&star.map(|&s| if s != 0.0 { 1.0 / s } else { 0.0 }))
+            }
+        }
+    };
+}
+
+star!(0, 2, hodge0(dual));
+star!(1, 1, hodge1(dual));
+star!(2, 0, hodge2());
+
+/// Wedge product of primal forms
+pub trait Wedge<Rhs> {
+    type Output;
+    /// `self ∧ rhs`
+    fn wedge(&self, rhs: &Rhs) -> Self::Output;
+}
+
+impl<'a> Wedge<Form0<'a>> for Form0<'a> {
+    type Output = Form0<'a>;
+    fn wedge(&self, rhs: &Form0<'a>) -> Form0<'a> {
+        let values = self.values.iter().zip(&rhs.values).map(|(a, b)| a * b).collect();
+        Form { mesh: self.mesh, values, complex: PhantomData }
+    }
+}
+
+impl<'a> Wedge<Form1<'a>> for Form0<'a> {
+    type Output = Form1<'a>;
+    /// Edge value times the mean of the 0-form at its end points
+    fn wedge(&self, rhs: &Form1<'a>) -> Form1<'a> {
+        let mut result = Form1::zeros(self.mesh);
+        for e in self.mesh.edge_ids() {
+            let [i, j] = self.mesh.edge_vertices(e);
+            result.values[e.index()] = 0.5 * (self.values[i.index()] + self.values[j.index()]) * rhs.values[e.index()];
+        }
+        result
+    }
+}
+
+impl<'a> Wedge<Form2<'a>> for Form0<'a> {
+    type Output = Form2<'a>;
+    /// Face value times the mean of the 0-form at its corners
+    fn wedge(&self, rhs: &Form2<'a>) -> Form2<'a> {
+        let mut result = Form2::zeros(self.mesh);
+        for f in self.mesh.face_ids() {
+            let vs: Vec<VertexId> = self.mesh.face_vertices(f).collect();
+            let mean = vs.iter().map(|v| self.values[v.index()]).sum::<f64>() / vs.len() as f64;
+            result.values[f.index()] = mean * rhs.values[f.index()];
+        }
+        result
+    }
+}
+
+impl<'a> Wedge<Form1<'a>> for Form1<'a> {
+    type Output = Form2<'a>;
+    /// Cup product on triangles, antisymmetrized over the corner orderings:
+    /// `(α ∧ β)(abc) = (α_ab (β_bc - β_ca) + α_bc (β_ca - β_ab) + α_ca (β_ab - β_bc)) / 6`
+    fn wedge(&self, rhs: &Form1<'a>) -> Form2<'a> {
+        let mut result = Form2::zeros(self.mesh);
+        for f in self.mesh.face_ids() {
+            let hs: Vec<HalfEdgeId> = self.mesh.face_halfedges(f).collect();
+            let a: Vec<f64> = hs.iter().map(|&h| self.along(h)).collect();
+            let b: Vec<f64> = hs.iter().map(|&h| rhs.along(h)).collect();
+            result.values[f.index()] =
+                (a[0] * (b[1] - b[2]) + a[1] * (b[2] - b[0]) + a[2] * (b[0] - b[1])) / 6.0;
+        }
+        result
+    }
+}
+
+impl<'a> Form1<'a> {
+    /// Integral of the form along half-edge `h`
+    fn along(&self, h: HalfEdgeId) -> f64 {
+        let e = self.mesh.halfedges[h].edge;
+        let value = self.values[e.index()];
+        if self.mesh.edges[e].he == h { value } else { -value }
+    }
+
+    /// Integral of the form along a path of head-to-tail half-edges
+    pub fn integrate(&self, path: &[HalfEdgeId]) -> f64 {
+        path.iter().map(|&h| self.along(h)).sum()
+    }
+
+    /// 1-form of a vector field given per face, integrated along every edge
+    /// and averaged over its faces
+    pub fn from_face_vectors(mesh: &'a Mesh3D<(), ()>, field: &[Vector3]) -> Self {
+        let mut form = Form1::zeros(mesh);
+        for e in mesh.edge_ids() {
+            let [i, j] = mesh.edge_vertices(e);
+            let edge = mesh.vertices[j].attr - mesh.vertices[i].attr;
+            let faces: Vec<FaceId> = mesh.edge_faces(e).collect();
+            form.values[e.index()] = faces.iter().map(|f| field[f.index()].dot(&edge)).sum::<f64>() / faces.len().max(1) as f64;
+        }
+        form
+    }
+
+    /// 1-form of a vector field given per vertex, integrated along every
+    /// edge by the trapezoidal rule
+    pub fn from_vertex_vectors(mesh: &'a Mesh3D<(), ()>, field: &[Vector3]) -> Self {
+        let mut form = Form1::zeros(mesh);
+        for e in mesh.edge_ids() {
+            let [i, j] = mesh.edge_vertices(e);
+            let edge = mesh.vertices[j].attr - mesh.vertices[i].attr;
+            form.values[e.index()] = (field[i.index()] + field[j.index()]).dot(&edge) * 0.5;
+        }
+        form
+    }
+
+    /// Vector field of the Whitney interpolant of the form in triangle `f`,
+    /// at barycentric coordinates `bary` of its corners
+    pub fn whitney_at(&self, f: FaceId, bary: [f64; 3]) -> Vector3 {
+        let hs: Vec<HalfEdgeId> = self.mesh.face_halfedges(f).collect();
+        let grads = hat_gradients(self.mesh, f);
+        // W_ij = φ_i ∇φ_j - φ_j ∇φ_i on the edge from corner i to corner j
+        (0..3).fold(Vector3::zero(), |acc, i| {
+            let j = (i + 1) % 3;
+            acc + (grads[j] * bary[i] - grads[i] * bary[j]) * self.along(hs[i])
+        })
+    }
+
+    /// Whitney reconstruction of a face vector field, at every barycenter
+    pub fn whitney(&self) -> Vec<Vector3> {
+        let mut field = vec![Vector3::zero(); self.mesh.faces.len()];
+        for f in self.mesh.face_ids() {
+            field[f.index()] = self.whitney_at(f, [1.0 / 3.0; 3]);
+        }
+        field
+    }
+
+    /// Interior product with a face vector field: the Whitney vector field
+    /// dotted with `field`, a dual 0-form
+    pub fn interior(&self, field: &[Vector3]) -> Form0<'a, Dual> {
+        let mut result = Form0::zeros_dual(self.mesh);
+        for (f, v) in self.whitney().into_iter().enumerate() {
+            result.values[f] = v.dot(&field[f]);
+        }
+        result
+    }
+}
+
+impl<'a> Form2<'a> {
+    /// Interior product with a face vector field `X`: the density `ω / A`
+    /// times the 1-form of `n × X`, averaged over the faces of every edge
+    pub fn interior(&self, field: &[Vector3]) -> Form1<'a> {
+        let mesh = self.mesh;
+        let mut rotated = vec![Vector3::zero(); mesh.faces.len()];
+        for f in mesh.face_ids() {
+            let vs: Vec<Vector3> = mesh.face_vertices(f).map(|v| mesh.vertices[v].attr).collect();
+            let normal = (vs[1] - vs[0]).cross(&(vs[2] - vs[0]));
+            // |normal| is twice the area
+            rotated[f.index()] = normal.cross(&field[f.index()]) * (2.0 * self.values[f.index()] / normal.dot(&normal));
+        }
+        Form1::from_face_vectors(mesh, &rotated)
+    }
+}
+
+/// Vertex positions of `mesh`
+fn positions(mesh: &Mesh3D<(), ()>) -> Vec<Vector3> {
+    mesh.vertices.iter().map(|v| v.attr).collect()
+}
+
+/// Gradients of the hat functions of the corners of triangle `f`, in the
+/// order of its half-edges
+fn hat_gradients(mesh: &Mesh3D<(), ()>, f: FaceId) -> [Vector3; 3] {
+    let p: Vec<Vector3> = mesh.face_vertices(f).map(|v| mesh.vertices[v].attr).collect();
+    let normal = (p[1] - p[0]).cross(&(p[2] - p[0]));
+    let scale = 1.0 / normal.dot(&normal);
+    // ∇φ_i = N × e_i / |N|², with e_i the opposite edge
+    std::array::from_fn(|i| normal.cross(&(p[(i + 2) % 3] - p[(i + 1) % 3])) * scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::half_edge::Mesh;
+
+    /// Flat triangulated 4x4 square with an off-centre middle vertex
+    fn grid() -> Mesh3D<(), ()> {
+        let positions = (0..9)
+            .map(|i| if i == 4 { Vector3::new(2.4, 1.8, 0.0) } else { Vector3::new(2.0 * (i % 3) as f64, 2.0 * (i / 3) as f64, 0.0) })
+            .collect();
+        let faces = [0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4, 3, 4, 7, 3, 7, 6, 4, 5, 8, 4, 8, 7];
+        Mesh::from_vertices_and_faces(positions, &[3; 8], &faces).unwrap()
+    }
+
+    #[test]
+    fn test_whitney_of_exact_form() {
+        let mesh = grid();
+        let f = Form0::new(&mesh, mesh.vertices.iter().map(|v| 3.0 * v.attr.x - v.attr.y).collect()).unwrap();
+        let df = f.d();
+        assert!(df.d().values.iter().all(|v| v.abs() < 1e-12));
+        // Whitney interpolation of an exact form is the gradient of the
+        // linear interpolant, and integrating it recovers the form
+        let gradient = df.whitney();
+        assert!(gradient.iter().all(|g| (*g - Vector3::new(3.0, -1.0, 0.0)).norm() < 1e-12));
+        let reconstructed = Form1::from_face_vectors(&mesh, &gradient);
+        assert!(reconstructed.values.iter().zip(&df.values).all(|(a, b)| (a - b).abs() < 1e-12));
+        assert!(df.interior(&[Vector3::new(0.0, 1.0, 0.0); 8]).values.iter().all(|v| (v + 1.0).abs() < 1e-12));
+        assert!(Form0::new(&mesh, vec![0.0; 3]).is_err());
+    }
+
+    #[test]
+    fn test_wedge_and_stars() {
+        let mesh = grid();
+        let x = Form0::new(&mesh, mesh.vertices.iter().map(|v| v.attr.x).collect()).unwrap();
+        let y = Form0::new(&mesh, mesh.vertices.iter().map(|v| v.attr.y).collect()).unwrap();
+        // dx ∧ dy is the area form, antisymmetric, and contracts with the
+        // x axis to dy
+        let area = x.d().wedge(&y.d());
+        assert!((area.values.iter().sum::<f64>() - 16.0).abs() < 1e-12);
+        assert!(y.d().wedge(&x.d()).values.iter().zip(&area.values).all(|(a, b)| (a + b).abs() < 1e-12));
+        let dy = area.interior(&[Vector3::new(1.0, 0.0, 0.0); 8]);
+        assert!(dy.values.iter().zip(&y.d().values).all(|(a, b)| (a - b).abs() < 1e-12));
+        // the dual star round trip is the identity
+        let back = x.d().star(DualKind::Barycentric).star(DualKind::Barycentric);
+        assert!(back.values.iter().zip(&x.d().values).all(|(a, b)| (a - b).abs() < 1e-12));
+        // dual exterior derivatives compose to zero
+        let faces = Form0::new_dual(&mesh, (0..8).map(|f| f as f64).collect()).unwrap();
+        assert!(faces.d().d().values.iter().all(|v| v.abs() < 1e-12));
+    }
+
+    #[test]
+    #[should_panic(expected = "different meshes")]
+    fn test_add_forms_on_different_meshes() {
+        let (a, b) = (grid(), grid());
+        let _ = Form0::zeros(&a) + Form0::zeros(&b);
+    }
+}
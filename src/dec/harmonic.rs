@@ -0,0 +1,59 @@
+//! Harmonic 1-forms on closed surfaces
+//!
+//! Every dual homology generator of the tree–cotree decomposition is
+//! crossed by a closed but not exact 1-form, `±1` on the edges it crosses.
+//! Removing the exact part of each leaves a basis of the `2g` dimensional
+//! space of harmonic 1-forms.
+
+use crate::dec::decomposition::HodgeDecomposition;
+use crate::dec::forms::Form1;
+use crate::dec::hodge::DualKind;
+use crate::error::Result;
+use crate::mesh::half_edge::Mesh3D;
+use crate::mesh::tree_cotree::TreeCotree;
+
+/// Basis of harmonic 1-forms from the dual generators of `tree_cotree`,
+/// harmonic with respect to the Hodge stars of the given dual mesh
+pub fn harmonic_basis<'a>(mesh: &'a Mesh3D<(), ()>, tree_cotree: &TreeCotree, dual: DualKind) -> Result<Vec<Form1<'a>>> {
+    tree_cotree
+        .dual_generators
+        .iter()
+        .map(|generator| {
+            let mut closed = Form1::zeros(mesh);
+            for &h in generator {
+                let e = mesh.halfedges[h].edge;
+                closed.values[e.index()] += if mesh.edges[e].he == h { 1.0 } else { -1.0 };
+            }
+            Ok(HodgeDecomposition::of_form(&closed, dual)?.harmonic)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dec::decomposition::inner_product;
+    use crate::dec::fixtures::torus;
+
+    #[test]
+    fn test_torus_harmonic_basis() {
+        let mesh = torus(12, 8);
+        let tree_cotree = mesh.tree_cotree().unwrap();
+        let basis = harmonic_basis(&mesh, &tree_cotree, DualKind::Barycentric).unwrap();
+        assert_eq!(basis.len(), 2);
+        for form in &basis {
+            assert!(form.d().values.iter().all(|v| v.abs() < 1e-8));
+            assert!(form.star(DualKind::Barycentric).d().values.iter().all(|v| v.abs() < 1e-8));
+        }
+        // the periods over the primal generators are non-degenerate
+        let periods: Vec<Vec<f64>> = basis
+            .iter()
+            .map(|form| tree_cotree.primal_generators.iter().map(|cycle| form.integrate(cycle)).collect())
+            .collect();
+        let det = periods[0][0] * periods[1][1] - periods[0][1] * periods[1][0];
+        assert!(det.abs() > 1e-6);
+        let gram = inner_product(&basis[0], &basis[0], DualKind::Barycentric) * inner_product(&basis[1], &basis[1], DualKind::Barycentric)
+            - inner_product(&basis[0], &basis[1], DualKind::Barycentric).powi(2);
+        assert!(gram > 1e-9);
+    }
+}
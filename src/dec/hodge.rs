@@ -0,0 +1,134 @@
+use crate::geometry::area::{cotan_from_lengths, triangle_from_lengths};
+use crate::geometry::metric::Geometry;
+use crate::mesh::half_edge::Mesh;
+use crate::operators::assembly::diagonal;
+use sprs::CsMat;
+
+/// Construction of the dual mesh the Hodge stars measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DualKind {
+    /// Dual vertices at triangle barycenters; dual edges bend through edge
+    /// midpoints. Positive for every non-degenerate triangle.
+    Barycentric,
+    /// Dual vertices at triangle circumcenters, orthogonal to primal edges.
+    /// Dual volumes turn negative across obtuse angles.
+    Circumcentric,
+}
+
+/// Hodge star on 0-forms: dual cell area of every vertex
+pub fn hodge0<G: Geometry + ?Sized, VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, geometry: &G, dual: DualKind) -> CsMat<f64> {
+    let mut areas = vec![0.0; mesh.vertices.len()];
+    for f in mesh.face_ids() {
+        let [ab, bc, ca] = geometry.face_lengths(mesh, f);
+        let corners = match dual {
+            DualKind::Barycentric => [triangle_from_lengths(ab, bc, ca) / 3.0; 3],
+            DualKind::Circumcentric => {
+                let cot_a = cotan_from_lengths(ab, ca, bc);
+                let cot_b = cotan_from_lengths(ab, bc, ca);
+                let cot_c = cotan_from_lengths(bc, ca, ab);
+                [
+                    (ab * ab * cot_c + ca * ca * cot_b) / 8.0,
+                    (bc * bc * cot_a + ab * ab * cot_c) / 8.0,
+                    (ca * ca * cot_b + bc * bc * cot_a) / 8.0,
+                ]
+            }
+        };
+        for (h, area) in mesh.face_halfedges(f).zip(corners) {
+            areas[mesh.halfedges[h].from.index()] += area;
+        }
+    }
+    diagonal(&areas)
+}
+
+/// Hodge star on 1-forms: ratio of dual to primal length of every edge. The
+/// circumcentric ratio is the cotangent weight `(cot α + cot β) / 2`.
+pub fn hodge1<G: Geometry + ?Sized, VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, geometry: &G, dual: DualKind) -> CsMat<f64> {
+    let mut ratios = vec![0.0; mesh.edges.len()];
+    for e in mesh.edge_ids() {
+        ratios[e.index()] = match dual {
+            DualKind::Circumcentric => geometry.cotan_weight(mesh, e),
+            DualKind::Barycentric => {
+                // a third of the median to the edge, from every adjacent face
+                let l = geometry.edge_length(mesh, e);
+                let dual_length: f64 = mesh
+                    .edge_halfedges(e)
+                    .into_iter()
+                    .filter(|&h| !mesh.is_boundary_halfedge(h))
+                    .map(|h| {
+                        let n = mesh.halfedges[h].next;
+                        let (a, b) = (geometry.halfedge_length(mesh, n), geometry.halfedge_length(mesh, mesh.halfedges[n].next));
+                        (2.0 * a * a + 2.0 * b * b - l * l).max(0.0).sqrt() / 6.0
+                    })
+                    .sum();
+                dual_length / l
+            }
+        };
+    }
+    diagonal(&ratios)
+}
+
+/// Hodge star on 2-forms: inverse area of every face. Dual vertices are
+/// points, so it is the same for every [`DualKind`].
+pub fn hodge2<G: Geometry + ?Sized, VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, geometry: &G) -> CsMat<f64> {
+    let mut inverse = vec![0.0; mesh.faces.len()];
+    for f in mesh.face_ids() {
+        inverse[f.index()] = 1.0 / geometry.face_area(mesh, f);
+    }
+    diagonal(&inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dec::exterior::d0;
+    use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::Mesh3D;
+    use crate::operators::assembly::cotan_stiffness;
+
+    /// Triangulated 3x3 grid with a raised, off-centre middle vertex
+    fn grid() -> Mesh3D<(), ()> {
+        let positions = (0..9)
+            .map(|i| if i == 4 { Vector3::new(1.2, 0.9, 0.3) } else { Vector3::new((i % 3) as f64, (i / 3) as f64, 0.0) })
+            .collect();
+        let faces = [0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4, 3, 4, 7, 3, 7, 6, 4, 5, 8, 4, 8, 7];
+        Mesh::from_vertices_and_faces(positions, &[3; 8], &faces).unwrap()
+    }
+
+    #[test]
+    fn test_laplacian_from_stars() {
+        let mesh = grid();
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        let geometry = positions.as_slice();
+        let d0 = d0(&mesh);
+        let laplacian = &(&d0.transpose_view() * &hodge1(&mesh, geometry, DualKind::Circumcentric)) * &d0;
+        let stiffness = cotan_stiffness(&mesh, geometry);
+        for (&value, (i, j)) in stiffness.iter() {
+            assert!((laplacian.get(i, j).unwrap() - value).abs() < 1e-12);
+        }
+
+        let total: f64 = mesh.face_ids().map(|f| geometry.face_area(&mesh, f)).sum();
+        for dual in [DualKind::Barycentric, DualKind::Circumcentric] {
+            let star0 = hodge0(&mesh, geometry, dual);
+            assert!((star0.diag().data().iter().sum::<f64>() - total).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_duals_agree_on_equilateral_triangles() {
+        // the barycenter and circumcenter coincide
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.5, 0.75f64.sqrt(), 0.0),
+            Vector3::new(0.5, -(0.75f64.sqrt()), 0.0),
+        ];
+        let mesh: Mesh3D<(), ()> = Mesh::from_vertices_and_faces(positions.clone(), &[3, 3], &[0, 1, 2, 1, 0, 3]).unwrap();
+        let geometry = positions.as_slice();
+        for star in [hodge0, hodge1] {
+            let (bary, circ) = (star(&mesh, geometry, DualKind::Barycentric), star(&mesh, geometry, DualKind::Circumcentric));
+            for (a, b) in bary.diag().data().iter().zip(circ.diag().data()) {
+                assert!((a - b).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! Discrete exterior calculus
+//!
+//! Discrete k-forms are values on the k-cells of a mesh (vertices, edges,
+//! faces), indexed by raw element index. Every edge is oriented from the tail
+//! to the head of its half-edge `Edge::he`, every face by its half-edge
+//! cycle. The exterior derivatives are the signed incidence matrices between
+//! these cells and the Hodge stars map primal k-forms to dual (2-k)-forms.
+pub mod exterior;
+pub mod hodge;
+pub mod forms;
+pub mod decomposition;
+pub mod harmonic;
+#[cfg(test)]
+pub(crate) mod fixtures;
@@ -1,28 +1,29 @@
+use crate::geometry::real::Real;
 use crate::geometry::vector::Vector3;
 
 /// Compute area of triangle ABC
-pub fn triangle(a: &Vector3, b: &Vector3, c: &Vector3) -> f64 {
+pub fn triangle<T: Real>(a: &Vector3<T>, b: &Vector3<T>, c: &Vector3<T>) -> T {
     let u = Vector3::new(b.x - a.x, b.y - a.y, b.z - a.z);
     let v = Vector3::new(c.x - a.x, c.y - a.y, c.z - a.z);
-    0.5 * u.cross(&v).norm()
+    T::from_f64(0.5) * u.cross(&v).norm()
 }
 
 /// Per-vertex barycentric area: one-third of triangle area for each vertex
-pub fn barycentric_area(a: &Vector3, b: &Vector3, c: &Vector3) -> [f64;3] {
-    let area = triangle(a, b, c) / 3.0;
+pub fn barycentric_area<T: Real>(a: &Vector3<T>, b: &Vector3<T>, c: &Vector3<T>) -> [T;3] {
+    let area = triangle(a, b, c) / T::from_f64(3.0);
     [area, area, area]
 }
 
 /// Angle between two vectors
-fn angle(u: &Vector3, v: &Vector3) -> f64 {
+fn angle<T: Real>(u: &Vector3<T>, v: &Vector3<T>) -> T {
     let dot = u.dot(v);
     let nu = u.norm();
     let nv = v.norm();
-    (dot/(nu*nv)).clamp(-1.0,1.0).acos()
+    (dot/(nu*nv)).clamp(-T::ONE, T::ONE).acos()
 }
 
 /// Per-vertex mixed (Voronoi) area of triangle ABC
-pub fn mixed_area(a: &Vector3, b: &Vector3, c: &Vector3) -> [f64;3] {
+pub fn mixed_area<T: Real>(a: &Vector3<T>, b: &Vector3<T>, c: &Vector3<T>) -> [T;3] {
     let area_total = triangle(a, b, c);
     // edge vectors
     let u = *b - *a;
@@ -36,19 +37,66 @@ pub fn mixed_area(a: &Vector3, b: &Vector3, c: &Vector3) -> [f64;3] {
     let beta = angle(&w, &x);
     let gamma = angle(&y, &z);
     // obtuse triangle case
-    if alpha >= std::f64::consts::FRAC_PI_2 {
-        return [area_total/2.0, area_total/4.0, area_total/4.0];
-    } else if beta >= std::f64::consts::FRAC_PI_2 {
-        return [area_total/4.0, area_total/2.0, area_total/4.0];
-    } else if gamma >= std::f64::consts::FRAC_PI_2 {
-        return [area_total/4.0, area_total/4.0, area_total/2.0];
+    let (half, quarter) = (area_total / T::from_f64(2.0), area_total / T::from_f64(4.0));
+    if alpha >= T::FRAC_PI_2 {
+        return [half, quarter, quarter];
+    } else if beta >= T::FRAC_PI_2 {
+        return [quarter, half, quarter];
+    } else if gamma >= T::FRAC_PI_2 {
+        return [quarter, quarter, half];
     }
     // acute triangle: Voronoi region
     let cot_beta = w.dot(&x) / w.cross(&x).norm();
     let cot_gamma = y.dot(&z) / y.cross(&z).norm();
     let cot_alpha = u.dot(&v) / u.cross(&v).norm();
-    let area0 = (u.dot(&u)*cot_gamma + v.dot(&v)*cot_beta) / 8.0;
-    let area1 = (x.dot(&x)*cot_alpha + w.dot(&w)*cot_gamma) / 8.0;
-    let area2 = (y.dot(&y)*cot_beta + z.dot(&z)*cot_alpha) / 8.0;
+    let eight = T::from_f64(8.0);
+    let area0 = (u.dot(&u)*cot_gamma + v.dot(&v)*cot_beta) / eight;
+    let area1 = (x.dot(&x)*cot_alpha + w.dot(&w)*cot_gamma) / eight;
+    let area2 = (y.dot(&y)*cot_beta + z.dot(&z)*cot_alpha) / eight;
     [area0, area1, area2]
 }
+
+/// Area of a triangle with side lengths `a`, `b`, `c` (Heron's formula in
+/// its numerically stable form)
+pub fn triangle_from_lengths<T: Real>(a: T, b: T, c: T) -> T {
+    let mut s = [a, b, c];
+    s.sort_by(|x, y| y.partial_cmp(x).unwrap_or(std::cmp::Ordering::Equal));
+    let [a, b, c] = s;
+    let product = (a + (b + c)) * (c - (a - b)) * (c + (a - b)) * (a + (b - c));
+    T::from_f64(0.25) * product.max(T::ZERO).sqrt()
+}
+
+/// Angle between the sides of lengths `a` and `b` of a triangle whose third
+/// side has length `c`
+pub fn angle_from_lengths<T: Real>(a: T, b: T, c: T) -> T {
+    ((a * a + b * b - c * c) / (T::from_f64(2.0) * a * b)).clamp(-T::ONE, T::ONE).acos()
+}
+
+/// Cotangent of the angle opposite to side `c` of a triangle with side
+/// lengths `a`, `b`, `c`
+pub fn cotan_from_lengths<T: Real>(a: T, b: T, c: T) -> T {
+    (a * a + b * b - c * c) / (T::from_f64(4.0) * triangle_from_lengths(a, b, c))
+}
+
+/// Per-vertex mixed (Voronoi) area of triangle ABC from its side lengths
+/// `ab`, `bc` and `ca`, as [`mixed_area`] computes it from positions
+pub fn mixed_area_from_lengths<T: Real>(ab: T, bc: T, ca: T) -> [T;3] {
+    let area_total = triangle_from_lengths(ab, bc, ca);
+    let (half, quarter) = (area_total / T::from_f64(2.0), area_total / T::from_f64(4.0));
+    if angle_from_lengths(ab, ca, bc) >= T::FRAC_PI_2 {
+        return [half, quarter, quarter];
+    } else if angle_from_lengths(ab, bc, ca) >= T::FRAC_PI_2 {
+        return [quarter, half, quarter];
+    } else if angle_from_lengths(bc, ca, ab) >= T::FRAC_PI_2 {
+        return [quarter, quarter, half];
+    }
+    let cot_alpha = cotan_from_lengths(ab, ca, bc);
+    let cot_beta = cotan_from_lengths(ab, bc, ca);
+    let cot_gamma = cotan_from_lengths(bc, ca, ab);
+    let eight = T::from_f64(8.0);
+    [
+        (ab * ab * cot_gamma + ca * ca * cot_beta) / eight,
+        (bc * bc * cot_alpha + ab * ab * cot_gamma) / eight,
+        (ca * ca * cot_beta + bc * bc * cot_alpha) / eight,
+    ]
+}
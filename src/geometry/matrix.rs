@@ -1,25 +1,27 @@
+use crate::geometry::real::Real;
 use crate::geometry::vector::Vector3;
 
 /// 2x2 matrix
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Matrix2 {
-    pub m: [[f64;2];2],
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Matrix2<T = f64> {
+    pub m: [[T;2];2],
 }
 
-impl Matrix2 {
+impl<T: Real> Matrix2<T> {
     /// Construct Matrix2 from elements
-    pub fn new(a: f64, b: f64, c: f64, d: f64) -> Self {
+    pub fn new(a: T, b: T, c: T, d: T) -> Self {
         Self { m: [[a, b], [c, d]] }
     }
     /// Identity matrix
-    pub fn identity() -> Self { Self::new(1.0, 0.0, 0.0, 1.0) }
+    pub fn identity() -> Self { Self::new(T::ONE, T::ZERO, T::ZERO, T::ONE) }
     /// Determinant
-    pub fn det(&self) -> f64 { self.m[0][0]*self.m[1][1] - self.m[0][1]*self.m[1][0] }
+    pub fn det(&self) -> T { self.m[0][0]*self.m[1][1] - self.m[0][1]*self.m[1][0] }
     /// Inverse
     pub fn inverse(&self) -> Option<Self> {
         let d = self.det();
-        if d.abs() < 1e-12 { return None; }
-        let inv = 1.0 / d;
+        if d.abs() < T::from_f64(1e-12) { return None; }
+        let inv = T::ONE / d;
         Some(Self::new(
             self.m[1][1] * inv,
             -self.m[0][1] * inv,
@@ -31,29 +33,30 @@ impl Matrix2 {
 
 /// 3x3 matrix
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Matrix3 {
-    pub m: [[f64;3];3],
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Matrix3<T = f64> {
+    pub m: [[T;3];3],
 }
 
-impl Matrix3 {
+impl<T: Real> Matrix3<T> {
     /// Construct Matrix3 from rows
-    pub fn new(m: [[f64;3];3]) -> Self { Self { m } }
+    pub fn new(m: [[T;3];3]) -> Self { Self { m } }
+    /// Zero matrix
+    pub fn zero() -> Self { Self::new([[T::ZERO;3];3]) }
     /// Identity
     pub fn identity() -> Self {
-        Self::new([
-            [1.0, 0.0, 0.0],
-            [0.0, 1.0, 0.0],
-            [0.0, 0.0, 1.0],
-        ])
+        let mut m = [[T::ZERO;3];3];
+        for (i, row) in m.iter_mut().enumerate() { row[i] = T::ONE; }
+        Self::new(m)
     }
     /// Transpose
     pub fn transpose(&self) -> Self {
-        let mut t = [[0.0;3];3];
+        let mut t = [[T::ZERO;3];3];
         for i in 0..3 { for j in 0..3 { t[i][j] = self.m[j][i]; }}
         Self::new(t)
     }
     /// Determinant
-    pub fn det(&self) -> f64 {
+    pub fn det(&self) -> T {
         let m = &self.m;
         m[0][0]*(m[1][1]*m[2][2] - m[1][2]*m[2][1])
         - m[0][1]*(m[1][0]*m[2][2] - m[1][2]*m[2][0])
@@ -61,23 +64,23 @@ impl Matrix3 {
     }
     /// Inverse
     pub fn inverse(&self) -> Option<Self> {
-        let d = self.det(); if d.abs() < 1e-12 { return None; }
+        let d = self.det(); if d.abs() < T::from_f64(1e-12) { return None; }
         let m = &self.m;
         let cofactor = |i, j| {
-            let mut sub = [[0.0;2];2];
+            let mut sub = [[T::ZERO;2];2];
             let (si, sj) = ( (0..3).filter(|&x| x!=i).collect::<Vec<_>>(), (0..3).filter(|&y| y!=j).collect::<Vec<_>>() );
             for ii in 0..2 { for jj in 0..2 {
                 sub[ii][jj] = m[si[ii]][sj[jj]];
             }}
             let mat2 = Matrix2::new(sub[0][0], sub[0][1], sub[1][0], sub[1][1]);
-            ((i+j)%2==0) as i32 as f64 * mat2.det() - (((i+j)%2)!=0) as i32 as f64 * mat2.det()
+            if (i+j)%2 == 0 { mat2.det() } else { -mat2.det() }
         };
-        let mut inv = [[0.0;3];3];
+        let mut inv = [[T::ZERO;3];3];
         for i in 0..3 { for j in 0..3 { inv[j][i] = cofactor(i,j) / d; }}
         Some(Self::new(inv))
     }
     /// Multiply by Vector3
-    pub fn mul_vec(&self, v: Vector3) -> Vector3 {
+    pub fn mul_vec(&self, v: Vector3<T>) -> Vector3<T> {
         Vector3::new(
             self.m[0][0]*v.x + self.m[0][1]*v.y + self.m[0][2]*v.z,
             self.m[1][0]*v.x + self.m[1][1]*v.y + self.m[1][2]*v.z,
@@ -86,10 +89,10 @@ impl Matrix3 {
     }
 }
 
-impl std::ops::Mul for Matrix3 {
-    type Output = Matrix3;
-    fn mul(self, rhs: Matrix3) -> Matrix3 {
-        let mut r = [[0.0;3];3];
+impl<T: Real> std::ops::Mul for Matrix3<T> {
+    type Output = Matrix3<T>;
+    fn mul(self, rhs: Matrix3<T>) -> Matrix3<T> {
+        let mut r = [[T::ZERO;3];3];
         for i in 0..3 { for j in 0..3 { for k in 0..3 {
             r[i][j] += self.m[i][k] * rhs.m[k][j];
         }}}
@@ -97,10 +100,10 @@ impl std::ops::Mul for Matrix3 {
     }
 }
 
-impl std::ops::Mul<f64> for Matrix3 {
-    type Output = Matrix3;
-    fn mul(self, rhs: f64) -> Matrix3 {
-        let mut r = [[0.0;3];3];
+impl<T: Real> std::ops::Mul<T> for Matrix3<T> {
+    type Output = Matrix3<T>;
+    fn mul(self, rhs: T) -> Matrix3<T> {
+        let mut r = [[T::ZERO;3];3];
         for i in 0..3 { for j in 0..3 {
             r[i][j] = self.m[i][j] * rhs;
         }}
@@ -108,17 +111,23 @@ impl std::ops::Mul<f64> for Matrix3 {
     }
 }
 
-impl std::ops::Mul<Matrix3> for f64 {
-    type Output = Matrix3;
-    fn mul(self, rhs: Matrix3) -> Matrix3 {
-        rhs * self
-    }
+macro_rules! scalar_mul {
+    ($t:ty) => {
+        impl std::ops::Mul<Matrix3<$t>> for $t {
+            type Output = Matrix3<$t>;
+            fn mul(self, rhs: Matrix3<$t>) -> Matrix3<$t> {
+                rhs * self
+            }
+        }
+    };
 }
+scalar_mul!(f32);
+scalar_mul!(f64);
 
-impl std::ops::Add for Matrix3 {
-    type Output = Matrix3;
-    fn add(self, rhs: Matrix3) -> Matrix3 {
-        let mut r = [[0.0;3];3];
+impl<T: Real> std::ops::Add for Matrix3<T> {
+    type Output = Matrix3<T>;
+    fn add(self, rhs: Matrix3<T>) -> Matrix3<T> {
+        let mut r = [[T::ZERO;3];3];
         for i in 0..3 { for j in 0..3 {
             r[i][j] = self.m[i][j] + rhs.m[i][j];
         }}
@@ -0,0 +1,184 @@
+//! Intrinsic geometry of a triangle mesh
+//!
+//! Cotangent weights, areas and angles only depend on edge lengths. A
+//! [`Geometry`] supplies those lengths, either from an embedding (a slice of
+//! vertex positions) or directly as [`EdgeLengths`], so operators can be
+//! evaluated on metrics without an embedding. All faces must be triangles
+//! (see `Mesh::triangulate`).
+
+use crate::geometry::area;
+use crate::geometry::real::Real;
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::{EdgeId, FaceId, HalfEdgeId, Mesh, Mesh3D};
+use std::ops::{Index, IndexMut};
+
+/// Source of edge lengths for a triangle mesh
+pub trait Geometry<T: Real = f64> {
+    /// Length of edge `e` of `mesh`
+    fn edge_length<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, e: EdgeId) -> T;
+
+    /// Length of the edge of half-edge `h`
+    fn halfedge_length<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, h: HalfEdgeId) -> T {
+        self.edge_length(mesh, mesh.halfedges[h].edge)
+    }
+
+    /// Interior angle at the tail of half-edge `h` in its face
+    fn corner_angle<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, h: HalfEdgeId) -> T {
+        let n = mesh.halfedges[h].next;
+        let p = mesh.halfedges[n].next;
+        area::angle_from_lengths(self.halfedge_length(mesh, h), self.halfedge_length(mesh, p), self.halfedge_length(mesh, n))
+    }
+
+    /// Cotangent of the angle opposite to half-edge `h` in its face; zero for
+    /// boundary half-edges
+    fn opposite_cotan<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, h: HalfEdgeId) -> T {
+        if mesh.is_boundary_halfedge(h) {
+            return T::ZERO;
+        }
+        let n = mesh.halfedges[h].next;
+        let p = mesh.halfedges[n].next;
+        area::cotan_from_lengths(self.halfedge_length(mesh, n), self.halfedge_length(mesh, p), self.halfedge_length(mesh, h))
+    }
+
+    /// Cotangent weight `(cot α + cot β) / 2` of edge `e`
+    fn cotan_weight<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, e: EdgeId) -> T {
+        let [h, t] = mesh.edge_halfedges(e);
+        T::from_f64(0.5) * (self.opposite_cotan(mesh, h) + self.opposite_cotan(mesh, t))
+    }
+
+    /// Side lengths of triangle `f`, starting at the half-edge `f` points to
+    fn face_lengths<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, f: FaceId) -> [T; 3] {
+        let h0 = mesh.faces[f].he;
+        let h1 = mesh.halfedges[h0].next;
+        let h2 = mesh.halfedges[h1].next;
+        [self.halfedge_length(mesh, h0), self.halfedge_length(mesh, h1), self.halfedge_length(mesh, h2)]
+    }
+
+    /// Area of triangle `f`
+    fn face_area<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, f: FaceId) -> T {
+        let [a, b, c] = self.face_lengths(mesh, f);
+        area::triangle_from_lengths(a, b, c)
+    }
+
+    /// Mixed (Voronoi) areas of the corners of triangle `f`, in the order of
+    /// [`Geometry::face_lengths`]
+    fn corner_areas<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, f: FaceId) -> [T; 3] {
+        let [ab, bc, ca] = self.face_lengths(mesh, f);
+        area::mixed_area_from_lengths(ab, bc, ca)
+    }
+
+    /// Per-vertex mixed (Voronoi) areas
+    fn vertex_areas<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>) -> Vec<T> {
+        let mut areas = vec![T::ZERO; mesh.vertices.len()];
+        for f in mesh.face_ids() {
+            let corners = self.corner_areas(mesh, f);
+            for (h, a) in mesh.face_halfedges(f).zip(corners) {
+                areas[mesh.halfedges[h].from.index()] += a;
+            }
+        }
+        areas
+    }
+}
+
+/// Embedded geometry: positions indexed by vertex
+impl<T: Real> Geometry<T> for [Vector3<T>] {
+    fn edge_length<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, e: EdgeId) -> T {
+        let [u, v] = mesh.edge_vertices(e);
+        (self[v.index()] - self[u.index()]).norm()
+    }
+}
+
+/// Intrinsic geometry: one length per edge, indexed by edge
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeLengths<T = f64>(pub Vec<T>);
+
+impl<T: Real> EdgeLengths<T> {
+    /// Edge lengths of the embedding of `mesh`
+    pub fn from_mesh<EA, FA>(mesh: &Mesh3D<EA, FA, T>) -> Self {
+        let positions: Vec<Vector3<T>> = mesh.vertices.iter().map(|v| v.attr).collect();
+        Self::from_positions(mesh, &positions)
+    }
+
+    /// Edge lengths of `mesh` embedded at `positions`
+    pub fn from_positions<VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, positions: &[Vector3<T>]) -> Self {
+        let mut lengths = vec![T::ZERO; mesh.edges.len()];
+        for e in mesh.edge_ids() {
+            lengths[e.index()] = positions.edge_length(mesh, e);
+        }
+        EdgeLengths(lengths)
+    }
+
+    /// Conformally scaled metric `l_ij exp((u_i + u_j) / 2)` for a
+    /// log-scale factor `u` per vertex
+    pub fn conformal<VA, EA, FA>(&self, mesh: &Mesh<VA, EA, FA>, u: &[T]) -> Self {
+        let mut lengths = self.0.clone();
+        for e in mesh.edge_ids() {
+            let [i, j] = mesh.edge_vertices(e).map(|v| u[v.index()]);
+            lengths[e.index()] *= (T::from_f64(0.5) * (i + j)).exp();
+        }
+        EdgeLengths(lengths)
+    }
+}
+
+impl<T: Real> Geometry<T> for EdgeLengths<T> {
+    fn edge_length<VA, EA, FA>(&self, _mesh: &Mesh<VA, EA, FA>, e: EdgeId) -> T {
+        self.0[e.index()]
+    }
+}
+
+impl<T> Index<EdgeId> for EdgeLengths<T> {
+    type Output = T;
+    fn index(&self, e: EdgeId) -> &T {
+        &self.0[e.index()]
+    }
+}
+
+impl<T> IndexMut<EdgeId> for EdgeLengths<T> {
+    fn index_mut(&mut self, e: EdgeId) -> &mut T {
+        &mut self.0[e.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3x3 grid of squares with a raised center vertex
+    fn grid() -> Mesh3D<(), ()> {
+        let positions = (0..9).map(|i| Vector3::new((i % 3) as f64, (i / 3) as f64, if i == 4 { 0.3 } else { 0.0 })).collect();
+        let faces = [0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4, 3, 4, 7, 3, 7, 6, 4, 5, 8, 4, 8, 7];
+        Mesh::from_vertices_and_faces(positions, &[3; 8], &faces).unwrap()
+    }
+
+    #[test]
+    fn test_edge_lengths_match_embedding() {
+        let mesh = grid();
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        let lengths = EdgeLengths::from_mesh(&mesh);
+        let embedded = positions.as_slice().vertex_areas(&mesh);
+        let intrinsic = lengths.vertex_areas(&mesh);
+        for (a, b) in embedded.iter().zip(&intrinsic) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        for (a, b) in embedded.iter().zip(mesh.vertex_areas()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        for e in mesh.edge_ids() {
+            assert!((positions.as_slice().cotan_weight(&mesh, e) - lengths.cotan_weight(&mesh, e)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_constant_conformal_scaling() {
+        let mesh = grid();
+        let lengths = EdgeLengths::from_mesh(&mesh);
+        let scaled = lengths.conformal(&mesh, &vec![2.0f64.ln(); mesh.vertices.len()]);
+        for f in mesh.face_ids() {
+            assert!((scaled.face_area(&mesh, f) - 4.0 * lengths.face_area(&mesh, f)).abs() < 1e-12);
+        }
+        // cotangent weights are scale invariant
+        for e in mesh.edge_ids() {
+            assert!((scaled.cotan_weight(&mesh, e) - lengths.cotan_weight(&mesh, e)).abs() < 1e-12);
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod vector;
 pub mod predicate;
 pub mod matrix;
-pub mod area;
\ No newline at end of file
+pub mod area;
+pub mod real;
+pub mod metric;
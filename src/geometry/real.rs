@@ -0,0 +1,77 @@
+//! Scalar type of geometry and meshes
+
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::num::ParseFloatError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Floating-point scalar: `f32` or `f64`
+pub trait Real:
+    Copy
+    + Debug
+    + Display
+    + Default
+    + PartialOrd
+    + FromStr<Err = ParseFloatError>
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Sum
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const PI: Self;
+    const FRAC_PI_2: Self;
+    const EPSILON: Self;
+    const INFINITY: Self;
+
+    /// Nearest value of this type
+    fn from_f64(x: f64) -> Self;
+    /// Widen to `f64`
+    fn to_f64(self) -> f64;
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn acos(self) -> Self;
+    fn exp(self) -> Self;
+    fn floor(self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn clamp(self, min: Self, max: Self) -> Self;
+}
+
+macro_rules! real {
+    ($t:ident) => {
+        impl Real for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const PI: Self = std::$t::consts::PI;
+            const FRAC_PI_2: Self = std::$t::consts::FRAC_PI_2;
+            const EPSILON: Self = $t::EPSILON;
+            const INFINITY: Self = $t::INFINITY;
+
+            fn from_f64(x: f64) -> Self { x as $t }
+            fn to_f64(self) -> f64 { self as f64 }
+            fn sqrt(self) -> Self { $t::sqrt(self) }
+            fn abs(self) -> Self { $t::abs(self) }
+            fn acos(self) -> Self { $t::acos(self) }
+            fn exp(self) -> Self { $t::exp(self) }
+            fn floor(self) -> Self { $t::floor(self) }
+            fn max(self, other: Self) -> Self { $t::max(self, other) }
+            fn min(self, other: Self) -> Self { $t::min(self, other) }
+            fn clamp(self, min: Self, max: Self) -> Self { $t::clamp(self, min, max) }
+        }
+    };
+}
+
+real!(f32);
+real!(f64);
@@ -1,24 +1,27 @@
-/// 3D vector type and utilities
+use crate::geometry::real::Real;
+
+/// 3D vector type and utilities, in `f64` unless another [`Real`] is given
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Vector3 {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vector3<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl Default for Vector3 {
+impl<T: Real> Default for Vector3<T> {
     fn default() -> Self {
-        Self::new(0.0, 0.0, 0.0)
+        Self::zero()
     }
 }
 
-impl Vector3 {
+impl<T: Real> Vector3<T> {
     /// Construct new Vector3
-    pub fn new(x: f64, y: f64, z: f64) -> Self { Self { x, y, z } }
+    pub fn new(x: T, y: T, z: T) -> Self { Self { x, y, z } }
     /// Zero vector
-    pub fn zero() -> Self { Self::new(0.0, 0.0, 0.0) }
+    pub fn zero() -> Self { Self::new(T::ZERO, T::ZERO, T::ZERO) }
     /// Dot product
-    pub fn dot(&self, other: &Self) -> f64 { self.x*other.x + self.y*other.y + self.z*other.z }
+    pub fn dot(&self, other: &Self) -> T { self.x*other.x + self.y*other.y + self.z*other.z }
     /// Cross product
     pub fn cross(&self, other: &Self) -> Self {
         Self {
@@ -28,51 +31,92 @@ impl Vector3 {
         }
     }
     /// Norm (length)
-    pub fn norm(&self) -> f64 { self.dot(self).sqrt() }
+    pub fn norm(&self) -> T { self.dot(self).sqrt() }
     /// Normalize to unit length
     pub fn normalize(&self) -> Self {
-        let n = self.norm(); if n == 0.0 { *self } else { *self / n }
+        let n = self.norm(); if n == T::ZERO { *self } else { *self / n }
     }
     /// Create from array [x, y, z]
-    pub fn from_array(a: [f64;3]) -> Self { Self::new(a[0], a[1], a[2]) }
+    pub fn from_array(a: [T;3]) -> Self { Self::new(a[0], a[1], a[2]) }
     /// Convert to array [x, y, z]
-    pub fn to_array(&self) -> [f64;3] { [self.x, self.y, self.z] }
+    pub fn to_array(&self) -> [T;3] { [self.x, self.y, self.z] }
+    /// Convert to another scalar type
+    pub fn cast<U: Real>(&self) -> Vector3<U> {
+        Vector3::new(U::from_f64(self.x.to_f64()), U::from_f64(self.y.to_f64()), U::from_f64(self.z.to_f64()))
+    }
 }
 
 use std::ops::{Add, Sub, Mul, Div, Neg};
 
-impl Add for Vector3 {
+impl<T: Real> Add for Vector3<T> {
     type Output = Self;
     fn add(self, rhs: Self) -> Self::Output { Self::new(self.x+rhs.x, self.y+rhs.y, self.z+rhs.z) }
 }
-impl Sub for Vector3 {
+impl<T: Real> Sub for Vector3<T> {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self::Output { Self::new(self.x-rhs.x, self.y-rhs.y, self.z-rhs.z) }
 }
-impl Neg for Vector3 {
+impl<T: Real> Neg for Vector3<T> {
     type Output = Self;
     fn neg(self) -> Self::Output { Self::new(-self.x, -self.y, -self.z) }
 }
-impl Mul<f64> for Vector3 {
+impl<T: Real> Mul<T> for Vector3<T> {
     type Output = Self;
-    fn mul(self, s: f64) -> Self::Output { Self::new(self.x*s, self.y*s, self.z*s) }
+    fn mul(self, s: T) -> Self::Output { Self::new(self.x*s, self.y*s, self.z*s) }
 }
-impl Div<f64> for Vector3 {
+impl<T: Real> Div<T> for Vector3<T> {
     type Output = Self;
-    fn div(self, s: f64) -> Self::Output { Self::new(self.x/s, self.y/s, self.z/s) }
+    fn div(self, s: T) -> Self::Output { Self::new(self.x/s, self.y/s, self.z/s) }
 }
 
 // Allow scalar * Vector3
-impl Mul<Vector3> for f64 {
-    type Output = Vector3;
-    fn mul(self, v: Vector3) -> Self::Output { v * self }
+macro_rules! scalar_mul {
+    ($t:ty) => {
+        impl Mul<Vector3<$t>> for $t {
+            type Output = Vector3<$t>;
+            fn mul(self, v: Vector3<$t>) -> Self::Output { v * self }
+        }
+    };
+}
+scalar_mul!(f32);
+scalar_mul!(f64);
+
+impl<T: Real> From<[T;3]> for Vector3<T> {
+    fn from(a: [T;3]) -> Self { Vector3::from_array(a) }
+}
+impl<T: Real> From<Vector3<T>> for [T;3] {
+    fn from(v: Vector3<T>) -> Self { v.to_array() }
+}
+
+// Single-precision positions widen losslessly, so code working in f64 can
+// take them wherever it accepts `Into<Vector3>`
+impl From<Vector3<f32>> for Vector3<f64> {
+    fn from(v: Vector3<f32>) -> Self { v.cast() }
+}
+impl From<[f32;3]> for Vector3<f64> {
+    fn from(a: [f32;3]) -> Self { Vector3::from_array(a).cast() }
 }
 
-impl From<[f64;3]> for Vector3 {
-    fn from(a: [f64;3]) -> Self { Vector3::from_array(a) }
+/// Vertex attribute holding a 3D position, as stored by the mesh readers
+/// (`[T; 3]`) or expected by the operators (`Vector3<T>`)
+pub trait Position: Copy {
+    type Scalar: Real;
+    /// Coordinates `[x, y, z]`
+    fn coords(&self) -> [Self::Scalar; 3];
+    /// Position with coordinates `[x, y, z]`
+    fn from_coords(coords: [Self::Scalar; 3]) -> Self;
 }
-impl From<Vector3> for [f64;3] {
-    fn from(v: Vector3) -> Self { v.to_array() }
+
+impl<T: Real> Position for [T;3] {
+    type Scalar = T;
+    fn coords(&self) -> [T;3] { *self }
+    fn from_coords(coords: [T;3]) -> Self { coords }
+}
+
+impl<T: Real> Position for Vector3<T> {
+    type Scalar = T;
+    fn coords(&self) -> [T;3] { self.to_array() }
+    fn from_coords(coords: [T;3]) -> Self { Vector3::from_array(coords) }
 }
 
 #[cfg(test)]
@@ -92,4 +136,13 @@ mod tests {
         let v: Vector3 = a.into();
         assert_eq!(v.to_array(), a);
     }
+    #[test]
+    fn test_single_precision() {
+        let u = Vector3::<f32>::new(3.0, 0.0, 4.0);
+        assert_eq!(u.norm(), 5.0f32);
+        assert_eq!(2.0f32 * u, Vector3::new(6.0, 0.0, 8.0));
+        let wide: Vector3 = u.into();
+        assert_eq!(wide, Vector3::new(3.0, 0.0, 4.0));
+        assert_eq!(std::mem::size_of::<Vector3<f32>>(), 12);
+    }
 }
\ No newline at end of file
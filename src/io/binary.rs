@@ -0,0 +1,392 @@
+//! Native binary mesh snapshots
+//!
+//! Unlike the interchange formats, a snapshot stores the half-edge
+//! connectivity itself, deleted elements and properties included, so large
+//! meshes reload without rebuilding topology from a polygon soup. All values
+//! are little endian:
+//!
+//! - magic `DDMESH`, format version `u16`, scalar size `u8` (4 or 8) and the
+//!   mesh generation `u32`
+//! - element counts `u32` of vertices, half-edges, edges and faces
+//! - per vertex its outgoing half-edge and `x`, `y`, `z`; per half-edge its
+//!   vertex, twin, next, edge and face; per edge and face one half-edge.
+//!   Handles are `u32`, with `u32::MAX` for `INVALID`
+//! - per element kind a `u8` flag followed, if set, by one deletion flag
+//!   byte per element
+//! - per element kind the number of properties `u32`, then for each its
+//!   name (`u32` length and UTF-8 bytes), value type tag `u8` and values
+//!
+//! Properties of other value types than those implementing [`BinaryValue`]
+//! are not stored.
+
+use crate::error::{Error, Result};
+use crate::geometry::real::Real;
+use crate::geometry::vector::{Position, Vector3};
+use crate::io::traits::{LoadMesh, SaveMesh};
+use crate::mesh::editing::Deleted;
+use crate::mesh::half_edge::{Edge, EdgeId, Face, FaceId, HalfEdge, HalfEdgeId, Mesh, Vertex, VertexId, INVALID};
+use crate::mesh::handles::Handle;
+use crate::mesh::properties::PropertyKind;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 6] = b"DDMESH";
+/// Current snapshot format version
+pub const VERSION: u16 = 1;
+
+/// Property value type that snapshots can store
+pub trait BinaryValue: Clone + Default + fmt::Debug + 'static {
+    /// Type tag in the snapshot
+    const TAG: u8;
+    fn write(&self, out: &mut impl Write) -> Result<()>;
+    fn read(input: &mut impl Read) -> Result<Self>;
+}
+
+macro_rules! binary_value {
+    ($t:ty, $tag:expr) => {
+        impl BinaryValue for $t {
+            const TAG: u8 = $tag;
+
+            fn write(&self, out: &mut impl Write) -> Result<()> {
+                Ok(out.write_all(&self.to_le_bytes())?)
+            }
+
+            fn read(input: &mut impl Read) -> Result<Self> {
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                input.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    };
+}
+
+binary_value!(f64, 0);
+binary_value!(f32, 1);
+binary_value!(i32, 2);
+binary_value!(u32, 3);
+binary_value!(i64, 4);
+binary_value!(u64, 5);
+
+impl BinaryValue for usize {
+    const TAG: u8 = 6;
+
+    fn write(&self, out: &mut impl Write) -> Result<()> {
+        (*self as u64).write(out)
+    }
+
+    fn read(input: &mut impl Read) -> Result<Self> {
+        usize::try_from(u64::read(input)?).map_err(|e| Error::Parse(e.to_string()))
+    }
+}
+
+impl BinaryValue for bool {
+    const TAG: u8 = 7;
+
+    fn write(&self, out: &mut impl Write) -> Result<()> {
+        Ok(out.write_all(&[u8::from(*self)])?)
+    }
+
+    fn read(input: &mut impl Read) -> Result<Self> {
+        Ok(read_u8(input)? != 0)
+    }
+}
+
+impl BinaryValue for Vector3 {
+    const TAG: u8 = 8;
+
+    fn write(&self, out: &mut impl Write) -> Result<()> {
+        self.to_array().iter().try_for_each(|x| x.write(out))
+    }
+
+    fn read(input: &mut impl Read) -> Result<Self> {
+        Ok(Vector3::new(f64::read(input)?, f64::read(input)?, f64::read(input)?))
+    }
+}
+
+/// Encoding and decoding of the properties of all [`BinaryValue`] types
+macro_rules! property_columns {
+    ($($t:ty),*) => {
+        /// Encode property `name` if it stores a supported type; returns its tag
+        fn write_column<H: PropertyKind, P>(mesh: &Mesh<P, (), ()>, name: &str, column: &mut Vec<u8>) -> Result<Option<u8>> {
+            $(if let Some(prop) = mesh.find_property::<H, $t>(name) {
+                for value in mesh.property(prop).values() {
+                    value.write(column)?;
+                }
+                return Ok(Some(<$t>::TAG));
+            })*
+            Ok(None)
+        }
+
+        /// Register property `name` with the type of `tag` and decode its values
+        fn read_column<H: PropertyKind, P>(mesh: &mut Mesh<P, (), ()>, name: &str, tag: u8, input: &mut impl Read) -> Result<()> {
+            $(if tag == <$t>::TAG {
+                let prop = mesh.add_property::<H, $t>(name)?;
+                for value in mesh.property_mut(prop).values_mut() {
+                    *value = <$t>::read(input)?;
+                }
+                return Ok(());
+            })*
+            Err(Error::Parse(format!("unknown property value type {}", tag)))
+        }
+    };
+}
+
+property_columns!(f64, f32, i32, u32, i64, u64, usize, bool, Vector3);
+
+/// Write a snapshot of `mesh`, positions in its own precision
+pub fn write_binary<P: Position>(mesh: &Mesh<P, (), ()>, path: &Path) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&[std::mem::size_of::<P::Scalar>() as u8])?;
+    mesh.generation.write(&mut out)?;
+    for count in [mesh.vertices.len(), mesh.halfedges.len(), mesh.edges.len(), mesh.faces.len()] {
+        let count = u32::try_from(count)
+            .ok()
+            .filter(|&c| c != u32::MAX)
+            .ok_or_else(|| Error::Unsupported(format!("snapshot of a mesh with {} elements", count)))?;
+        count.write(&mut out)?;
+    }
+
+    for v in &mesh.vertices {
+        write_handle(&mut out, v.he_out)?;
+        for x in v.attr.coords() {
+            write_scalar(&mut out, x)?;
+        }
+    }
+    for he in &mesh.halfedges {
+        write_handle(&mut out, he.from)?;
+        write_handle(&mut out, he.twin)?;
+        write_handle(&mut out, he.next)?;
+        write_handle(&mut out, he.edge)?;
+        write_handle(&mut out, he.face)?;
+    }
+    for e in &mesh.edges {
+        write_handle(&mut out, e.he)?;
+    }
+    for f in &mesh.faces {
+        write_handle(&mut out, f.he)?;
+    }
+
+    let deleted = &mesh.deleted;
+    let counts = [mesh.vertices.len(), mesh.halfedges.len(), mesh.edges.len(), mesh.faces.len()];
+    for (flags, count) in [&deleted.vertices, &deleted.halfedges, &deleted.edges, &deleted.faces].into_iter().zip(counts) {
+        let any = flags.contains(&true);
+        any.write(&mut out)?;
+        if any {
+            for i in 0..count {
+                flags.get(i).copied().unwrap_or(false).write(&mut out)?;
+            }
+        }
+    }
+
+    write_properties::<VertexId, _>(mesh, &mut out)?;
+    write_properties::<HalfEdgeId, _>(mesh, &mut out)?;
+    write_properties::<EdgeId, _>(mesh, &mut out)?;
+    write_properties::<FaceId, _>(mesh, &mut out)?;
+    Ok(out.flush()?)
+}
+
+/// Read a snapshot written by [`write_binary`], converting positions to the
+/// precision of `P`. Returns `Error::Parse` for other files, newer format
+/// versions and inconsistent connectivity.
+pub fn read_binary<P: Position>(path: &Path) -> Result<Mesh<P, (), ()>> {
+    let mut input = BufReader::new(File::open(path)?);
+    let mut magic = [0; 6];
+    if input.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        return Err(Error::Parse("not a mesh snapshot".into()));
+    }
+    let mut version = [0; 2];
+    input.read_exact(&mut version)?;
+    let version = u16::from_le_bytes(version);
+    if version > VERSION {
+        return Err(Error::Parse(format!("snapshot format version {} is newer than {}", version, VERSION)));
+    }
+    let scalar_size = read_u8(&mut input)?;
+    if scalar_size != 4 && scalar_size != 8 {
+        return Err(Error::Parse(format!("invalid scalar size {}", scalar_size)));
+    }
+    let generation = u32::read(&mut input)?;
+    let mut counts = [0; 4];
+    for count in counts.iter_mut() {
+        *count = u32::read(&mut input)? as usize;
+    }
+    let [nv, nh, ne, nf] = counts;
+
+    let mut mesh = Mesh {
+        vertices: Vec::new(),
+        halfedges: Vec::new(),
+        edges: Vec::new(),
+        faces: Vec::new(),
+        deleted: Deleted::default(),
+        generation,
+        properties: Default::default(),
+    };
+    for _ in 0..nv {
+        let he_out = read_handle(&mut input)?;
+        let mut coords = [P::Scalar::ZERO; 3];
+        for x in coords.iter_mut() {
+            *x = read_scalar(&mut input, scalar_size)?;
+        }
+        mesh.vertices.push(Vertex { he_out, attr: P::from_coords(coords) });
+    }
+    for _ in 0..nh {
+        mesh.halfedges.push(HalfEdge {
+            from: read_handle(&mut input)?,
+            twin: read_handle(&mut input)?,
+            next: read_handle(&mut input)?,
+            edge: read_handle(&mut input)?,
+            face: read_handle(&mut input)?,
+        });
+    }
+    for _ in 0..ne {
+        mesh.edges.push(Edge { he: read_handle(&mut input)?, attr: () });
+    }
+    for _ in 0..nf {
+        mesh.faces.push(Face { he: read_handle(&mut input)?, attr: () });
+    }
+
+    let deleted = &mut mesh.deleted;
+    for (flags, count) in [&mut deleted.vertices, &mut deleted.halfedges, &mut deleted.edges, &mut deleted.faces].into_iter().zip(counts) {
+        if bool::read(&mut input)? {
+            for _ in 0..count {
+                flags.push(bool::read(&mut input)?);
+            }
+        }
+    }
+
+    read_properties::<VertexId, _>(&mut mesh, &mut input)?;
+    read_properties::<HalfEdgeId, _>(&mut mesh, &mut input)?;
+    read_properties::<EdgeId, _>(&mut mesh, &mut input)?;
+    read_properties::<FaceId, _>(&mut mesh, &mut input)?;
+
+    match mesh.validate().into_iter().next() {
+        Some(error) => Err(Error::Parse(format!("corrupt snapshot: {}", error))),
+        None => Ok(mesh),
+    }
+}
+
+fn write_properties<H: PropertyKind, P>(mesh: &Mesh<P, (), ()>, out: &mut impl Write) -> Result<()> {
+    let mut stored = Vec::new();
+    for name in H::store(mesh.properties()).names() {
+        let mut column = Vec::new();
+        if let Some(tag) = write_column::<H, P>(mesh, name, &mut column)? {
+            stored.push((name, tag, column));
+        }
+    }
+    (stored.len() as u32).write(out)?;
+    for (name, tag, column) in stored {
+        let len = u32::try_from(name.len())
+            .map_err(|_| Error::Unsupported(format!("property name of {} bytes", name.len())))?;
+        len.write(out)?;
+        out.write_all(name.as_bytes())?;
+        out.write_all(&[tag])?;
+        out.write_all(&column)?;
+    }
+    Ok(())
+}
+
+fn read_properties<H: PropertyKind, P>(mesh: &mut Mesh<P, (), ()>, input: &mut impl Read) -> Result<()> {
+    for _ in 0..u32::read(input)? {
+        let len = u32::read(input)?;
+        let mut name = vec![0; len as usize];
+        input.read_exact(&mut name)?;
+        let name = String::from_utf8(name).map_err(|e| Error::Parse(e.to_string()))?;
+        let tag = read_u8(input)?;
+        read_column::<H, P>(mesh, &name, tag, input)?;
+    }
+    Ok(())
+}
+
+fn write_handle(out: &mut impl Write, h: impl Handle) -> Result<()> {
+    let raw = if h.index() == INVALID { u32::MAX } else { h.index() as u32 };
+    raw.write(out)
+}
+
+fn read_handle<H: Handle>(input: &mut impl Read) -> Result<H> {
+    let raw = u32::read(input)?;
+    Ok(H::new(if raw == u32::MAX { INVALID } else { raw as usize }))
+}
+
+fn write_scalar<T: Real>(out: &mut impl Write, x: T) -> Result<()> {
+    match std::mem::size_of::<T>() {
+        4 => (x.to_f64() as f32).write(out),
+        _ => x.to_f64().write(out),
+    }
+}
+
+fn read_scalar<T: Real>(input: &mut impl Read, size: u8) -> Result<T> {
+    let x = match size {
+        4 => f32::read(input)? as f64,
+        _ => f64::read(input)?,
+    };
+    Ok(T::from_f64(x))
+}
+
+fn read_u8(input: &mut impl Read) -> Result<u8> {
+    let mut byte = [0];
+    input.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// Native binary snapshot format, see the [module documentation](self)
+pub struct Binary;
+
+impl<P: Position> LoadMesh<P, (), ()> for Binary {
+    fn load(path: &Path) -> Result<Mesh<P, (), ()>> {
+        read_binary(path)
+    }
+}
+
+impl<P: Position> SaveMesh<P, (), ()> for Binary {
+    fn save(mesh: &Mesh<P, (), ()>, path: &Path) -> Result<()> {
+        write_binary(mesh, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mesh.ddm");
+        let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0], [0.5, 0.5, 1.0]];
+        let mut mesh: Mesh<[f64; 3], (), ()> =
+            Mesh::from_vertices_and_faces(positions, &[4, 3, 3], &[0, 1, 2, 3, 0, 4, 1, 1, 4, 2]).unwrap();
+        let height = mesh.add_vertex_property::<f64>("height").unwrap();
+        mesh.property_mut(height).values_mut().copy_from_slice(&[0.0, 0.1, 0.2, 0.3, 1.0]);
+        let tag = mesh.add_face_property::<bool>("selected").unwrap();
+        mesh.property_mut(tag)[FaceId::new(1)] = true;
+        mesh.add_edge_property::<String>("skipped").unwrap();
+        let e = mesh.edge_ids().find(|&e| mesh.is_flip_ok(e)).unwrap();
+        let v = mesh.split_edge(e, [0.5, 0.5, 0.0]);
+        let he = mesh.halfedge_ids().find(|&h| mesh.halfedges[h].from == v && mesh.is_collapse_ok(h)).unwrap();
+        mesh.collapse_edge(he).unwrap();
+
+        write_binary(&mesh, &path).unwrap();
+        let loaded: Mesh<[f64; 3], (), ()> = read_binary(&path).unwrap();
+        assert!(loaded.is_valid());
+        assert_eq!(loaded.vertices.len(), mesh.vertices.len());
+        assert!(loaded.is_vertex_deleted(v));
+        assert_eq!(loaded.face_vertex_indices(), mesh.face_vertex_indices());
+        for (a, b) in loaded.halfedges.iter().zip(&mesh.halfedges) {
+            assert_eq!((a.from, a.twin, a.next, a.edge, a.face), (b.from, b.twin, b.next, b.edge, b.face));
+        }
+        let height = loaded.vertex_property::<f64>("height").unwrap();
+        assert_eq!(&loaded.property(height).values()[..5], &[0.0, 0.1, 0.2, 0.3, 1.0]);
+        let tag = loaded.face_property::<bool>("selected").unwrap();
+        assert!(loaded.property(tag)[FaceId::new(1)]);
+        assert!(loaded.edge_property::<String>("skipped").is_none());
+
+        // single precision reads back the rounded positions
+        let single: Mesh<Vector3<f32>, (), ()> = read_binary(&path).unwrap();
+        assert_eq!(single.vertices[4].attr, Vector3::new(0.5, 0.5, 1.0));
+
+        std::fs::write(&path, b"OFF\n").unwrap();
+        assert!(matches!(read_binary::<[f64; 3]>(&path), Err(Error::Parse(_))));
+    }
+}
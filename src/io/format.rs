@@ -0,0 +1,103 @@
+//! File format detection and the high-level load and save entry points
+
+use crate::error::{Error, Result};
+use crate::geometry::real::Real;
+use crate::io::binary::Binary;
+use crate::io::obj::Obj;
+use crate::io::off::Off;
+use crate::io::ply::Ply;
+use crate::io::traits::{LoadMesh, SaveMesh};
+use crate::mesh::half_edge::Mesh3D;
+use std::path::Path;
+
+/// Mesh file formats known to [`Mesh3D::load`] and [`Mesh3D::save`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+    Obj,
+    Off,
+    Ply,
+    /// Native snapshot, see [`crate::io::binary`]
+    Binary,
+    #[cfg(feature = "usd")]
+    Usd,
+}
+
+impl MeshFormat {
+    /// Format of `path` from its extension, case-insensitively
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("obj") => Ok(MeshFormat::Obj),
+            Some("off") => Ok(MeshFormat::Off),
+            Some("ply") => Ok(MeshFormat::Ply),
+            Some("ddm") => Ok(MeshFormat::Binary),
+            #[cfg(feature = "usd")]
+            Some("usd" | "usda" | "usdc") => Ok(MeshFormat::Usd),
+            _ => Err(Error::Unsupported(format!("mesh file format of {}", path.display()))),
+        }
+    }
+}
+
+impl<T: Real> Mesh3D<(), (), T> {
+    /// Load a mesh, choosing the reader from the file extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match MeshFormat::from_path(path)? {
+            MeshFormat::Obj => Obj::load(path),
+            MeshFormat::Off => Off::load(path),
+            MeshFormat::Ply => Ply::load(path),
+            MeshFormat::Binary => Binary::load(path),
+            #[cfg(feature = "usd")]
+            MeshFormat::Usd => Ok(<crate::io::usd::USD as LoadMesh<_, (), ()>>::load(path)?.cast()),
+        }
+    }
+
+    /// Save the mesh, choosing the writer from the file extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        match MeshFormat::from_path(path)? {
+            MeshFormat::Obj => Obj::save(self, path),
+            MeshFormat::Off => Off::save(self, path),
+            MeshFormat::Ply => Ply::save(self, path),
+            MeshFormat::Binary => Binary::save(self, path),
+            #[cfg(feature = "usd")]
+            MeshFormat::Usd => {
+                let positions = self.vertices.iter().map(|v| v.attr.cast()).collect();
+                let mesh: Mesh3D<(), ()> = Mesh3D::from_vertices_and_faces(
+                    positions, &self.face_vertex_counts(), &self.face_vertex_indices())?;
+                crate::io::usd::USD::save(&mesh, path)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::Mesh;
+
+    #[test]
+    fn test_load_save_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0), Vector3::new(0.0, 1.0, 0.5),
+        ];
+        let mesh: Mesh3D<(), ()> = Mesh::from_vertices_and_faces(positions, &[3, 3], &[0, 1, 2, 0, 2, 3]).unwrap();
+        for name in ["square.obj", "square.off", "square.PLY", "square.ddm"] {
+            let path = dir.path().join(name);
+            mesh.save(&path).unwrap();
+            let loaded = Mesh3D::<(), ()>::load(&path).unwrap();
+            assert_eq!(loaded.face_vertex_indices(), mesh.face_vertex_indices());
+            assert_eq!(loaded.vertices[3].attr, mesh.vertices[3].attr);
+            let single: Mesh3D<(), (), f32> = Mesh3D::load(&path).unwrap();
+            assert_eq!(single.vertices[3].attr, Vector3::new(0.0f32, 1.0, 0.5));
+        }
+        let arrays: Mesh<[f64; 3], (), ()> = Ply::load(&dir.path().join("square.PLY")).unwrap();
+        let converted: Mesh3D<(), ()> = arrays.into();
+        assert_eq!(converted.vertices[2].attr, Vector3::new(1.0, 1.0, 0.0));
+        assert!(matches!(mesh.save(dir.path().join("square.stl")), Err(Error::Unsupported(_))));
+        assert!(Mesh3D::<(), ()>::load(dir.path().join("square")).is_err());
+    }
+}
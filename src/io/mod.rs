@@ -1,6 +1,8 @@
 pub mod traits;
+pub mod format;
 pub mod obj;
 pub mod off;
 pub mod ply;
+pub mod binary;
 #[cfg(feature = "usd")]
 pub mod usd;
\ No newline at end of file
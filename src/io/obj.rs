@@ -1,90 +1,108 @@
 
-use crate::mesh::half_edge::{Mesh, Vertex, HalfEdge, Edge, Face};
+use crate::geometry::real::Real;
+use crate::geometry::vector::Position;
+use crate::io::traits::{LoadMesh, SaveMesh};
+use crate::mesh::half_edge::Mesh;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
-use std::collections::HashMap;
 
 
-pub fn read_obj(path: &Path) -> crate::error::Result<Mesh<[f64;3], (), ()>> {
+pub fn read_obj<T: Real>(path: &Path) -> crate::error::Result<Mesh<[T;3], (), ()>> {
     let file = File::open(path)?;
     let reader = BufReader::new(file);
-    let mut mesh = Mesh::new();
-    let mut face_indices: Vec<[usize; 3]> = Vec::new();
+    let mut positions: Vec<[T; 3]> = Vec::new();
+    let mut face_counts: Vec<usize> = Vec::new();
+    let mut indices: Vec<usize> = Vec::new();
     for line in reader.lines() {
         let l = line.map_err(|e| crate::error::Error::Io(e))?;
         let parts: Vec<_> = l.split_whitespace().collect();
         if parts.is_empty() { continue; }
         match parts[0] {
             "v" if parts.len() >= 4 => {
-                let x: f64 = parts[1].parse().map_err(|e: std::num::ParseFloatError| crate::error::Error::Parse(e.to_string()))?;
-                let y: f64 = parts[2].parse().map_err(|e: std::num::ParseFloatError| crate::error::Error::Parse(e.to_string()))?;
-                let z: f64 = parts[3].parse().map_err(|e: std::num::ParseFloatError| crate::error::Error::Parse(e.to_string()))?;
-                mesh.vertices.push(Vertex { he_out: 0, attr: [x, y, z] });
+                let x: T = parts[1].parse().map_err(|e: std::num::ParseFloatError| crate::error::Error::Parse(e.to_string()))?;
+                let y: T = parts[2].parse().map_err(|e: std::num::ParseFloatError| crate::error::Error::Parse(e.to_string()))?;
+                let z: T = parts[3].parse().map_err(|e: std::num::ParseFloatError| crate::error::Error::Parse(e.to_string()))?;
+                positions.push([x, y, z]);
             }
             "f" if parts.len() >= 4 => {
-                let idx: Vec<usize> = parts[1..4].iter()
-                    .map(|p| p.split('/').next().unwrap().parse::<usize>().map_err(|e: std::num::ParseIntError| crate::error::Error::Parse(e.to_string())))
-                    .collect::<crate::error::Result<Vec<_>>>()?;
-                face_indices.push([idx[0]-1, idx[1]-1, idx[2]-1]);
+                for p in &parts[1..] {
+                    indices.push(parse_index(p, positions.len())?);
+                }
+                face_counts.push(parts.len() - 1);
             }
             _ => {}
         }
     }
     // Build half-edge topology
-    let mut edge_map: HashMap<(usize, usize), usize> = HashMap::new();
-    for inds in face_indices {
-        let face_id = mesh.faces.len();
-        mesh.faces.push(Face { he: 0, attr: () });
-        let he_base = mesh.halfedges.len();
-        // three half-edges
-        mesh.halfedges.push(HalfEdge { from: inds[0], twin: 0, next: he_base+1, edge: 0, face: face_id });
-        mesh.halfedges.push(HalfEdge { from: inds[1], twin: 0, next: he_base+2, edge: 0, face: face_id });
-        mesh.halfedges.push(HalfEdge { from: inds[2], twin: 0, next: he_base,   edge: 0, face: face_id });
-        mesh.faces[face_id].he = he_base;
-        // link twins and edges
-        for k in 0..3 {
-            let curr = he_base + k;
-            let next_he = mesh.halfedges[curr].next;
-            let from = mesh.halfedges[curr].from;
-            let to = mesh.halfedges[next_he].from;
-            if let Some(&opp) = edge_map.get(&(to, from)) {
-                mesh.halfedges[curr].twin = opp;
-                mesh.halfedges[opp].twin = curr;
-                // reuse edge
-                let e_id = mesh.halfedges[opp].edge;
-                mesh.halfedges[curr].edge = e_id;
-            } else {
-                // new edge
-                let e_id = mesh.edges.len();
-                mesh.edges.push(Edge { he: curr, attr: () });
-                mesh.halfedges[curr].edge = e_id;
-                edge_map.insert((from, to), curr);
-            }
-        }
-    }
-    // assign outgoing half-edge per vertex
-    for (he_idx, he) in mesh.halfedges.iter().enumerate() {
-        mesh.vertices[he.from].he_out = he_idx;
+    Mesh::from_vertices_and_faces(positions, &face_counts, &indices)
+}
+
+/// Zero-based vertex index of a face corner `v`, `v/vt` or `v/vt/vn`;
+/// negative indices count back from the last of `count` vertices read so far.
+fn parse_index(corner: &str, count: usize) -> crate::error::Result<usize> {
+    let v = corner.split('/').next().unwrap_or("");
+    let i: i64 = v.parse().map_err(|e: std::num::ParseIntError| crate::error::Error::Parse(e.to_string()))?;
+    let index = if i < 0 { count as i64 + i } else { i - 1 };
+    if index < 0 {
+        return Err(crate::error::Error::Parse(format!("invalid vertex index {}", v)));
     }
-    Ok(mesh)
+    Ok(index as usize)
 }
 
-pub fn write_obj(mesh: &Mesh<[f64;3], (), ()>, path: &Path) -> crate::error::Result<()> {
+pub fn write_obj<P: Position>(mesh: &Mesh<P, (), ()>, path: &Path) -> crate::error::Result<()> {
     let mut file = File::create(path)?;
     // write vertices
     for v in &mesh.vertices {
-        writeln!(file, "v {} {} {}", v.attr[0], v.attr[1], v.attr[2])?;
+        let [x, y, z] = v.attr.coords();
+        writeln!(file, "v {} {} {}", x, y, z)?;
     }
     // write faces
-    for face in &mesh.faces {
-        let he0 = face.he;
-        let he1 = mesh.halfedges[he0].next;
-        let he2 = mesh.halfedges[he1].next;
-        let a = mesh.halfedges[he0].from + 1;
-        let b = mesh.halfedges[he1].from + 1;
-        let c = mesh.halfedges[he2].from + 1;
-        writeln!(file, "f {} {} {}", a, b, c)?;
+    for f in mesh.face_ids() {
+        write!(file, "f")?;
+        for v in mesh.face_vertices(f) {
+            write!(file, " {}", v.index() + 1)?;
+        }
+        writeln!(file)?;
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Wavefront OBJ format: positions and polygonal faces
+pub struct Obj;
+
+impl<P: Position> LoadMesh<P, (), ()> for Obj {
+    fn load(path: &Path) -> crate::error::Result<Mesh<P, (), ()>> {
+        Ok(read_obj(path)?.map_vertex_attrs(P::from_coords))
+    }
+}
+
+impl<P: Position> SaveMesh<P, (), ()> for Obj {
+    fn save(mesh: &Mesh<P, (), ()>, path: &Path) -> crate::error::Result<()> {
+        write_obj(mesh, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_obj_quads_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quads.obj");
+        // two quads, with texture/normal references and relative indices
+        std::fs::write(&path, "v 0 0 0\nv 1 0 0\nv 2 0 0\nv 0 1 0\nv 1 1 0\nv 2 1 0\nvt 0 0\n\
+            f 1/1 2/1 5/1 4/1\nf -5//1 -4//1 -1//1 -2//1\n").unwrap();
+        let mesh = read_obj::<f64>(&path).unwrap();
+        assert_eq!(mesh.face_vertex_counts(), vec![4, 4]);
+        assert_eq!(mesh.face_vertex_indices(), vec![0, 1, 4, 3, 1, 2, 5, 4]);
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+
+        let copy = dir.path().join("copy.obj");
+        write_obj(&mesh, &copy).unwrap();
+        let loaded = read_obj::<f64>(&copy).unwrap();
+        assert_eq!(loaded.face_vertex_indices(), mesh.face_vertex_indices());
+        assert_eq!(loaded.vertices[5].attr, [2.0, 1.0, 0.0]);
+    }
+}
@@ -1,77 +1,34 @@
 use crate::error::Result;
 
-use crate::mesh::half_edge::{Mesh, Vertex, HalfEdge, Edge, Face};
+use crate::geometry::real::Real;
+use crate::geometry::vector::Position;
+use crate::io::traits::{LoadMesh, SaveMesh};
+use crate::mesh::half_edge::Mesh;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
-use std::collections::HashMap;
 
-/// OFF loader: ASCII only, triangular faces
-// Removed trait impl for LoadMesh; see read_off below
-pub fn load(path: &Path) -> Result<Mesh<[f64;3], (), ()>> {
-        let file = File::open(path)?;
-        let mut reader = BufReader::new(file);
-        let mut line = String::new();
-        reader.read_line(&mut line)?;
-        if line.trim() != "OFF" {
-            return Err(crate::error::Error::Parse("Invalid OFF header".into()));
-        }
-        line.clear();
-        reader.read_line(&mut line)?;
-        let parts: Vec<_> = line.split_whitespace().collect();
-        let nv: usize = parts[0].parse().unwrap_or(0);
-        let nf: usize = parts[1].parse().unwrap_or(0);
-        // skip ne
-        let mut mesh = Mesh::new();
-        for _ in 0..nv {
-            line.clear();
-            reader.read_line(&mut line)?;
-            let p: Vec<f64> = line.split_whitespace().map(|s: &str| s.parse::<f64>().unwrap_or(0.0)).collect();
-            mesh.vertices.push(Vertex { he_out: 0, attr: [p[0], p[1], p[2]] });
-        }
-        let mut face_indices: Vec<[usize; 3]> = Vec::new();
-        for _ in 0..nf {
-            line.clear();
-            reader.read_line(&mut line)?;
-            let items: Vec<_> = line.split_whitespace().collect();
-            if items[0] != "3" { continue; }
-            face_indices.push([items[1].parse().unwrap(), items[2].parse().unwrap(), items[3].parse().unwrap()]);
-        }
-        // build topology (same as OBJ)
-        let mut edge_map = HashMap::new();
-        for inds in face_indices {
-            let fid = mesh.faces.len();
-            mesh.faces.push(Face { he: 0, attr: () });
-            let hb = mesh.halfedges.len();
-            for k in 0..3 {
-                mesh.halfedges.push(HalfEdge { from: inds[k], twin: 0, next: hb+(k+1)%3, edge: 0, face: fid });
-            }
-            mesh.faces[fid].he = hb;
-            for k in 0..3 {
-                let curr = hb + k;
-                let next = mesh.halfedges[curr].next;
-                let from = mesh.halfedges[curr].from;
-                let to = mesh.halfedges[next].from;
-                if let Some(&opp) = edge_map.get(&(to, from)) {
-                    mesh.halfedges[curr].twin = opp;
-                    mesh.halfedges[opp].twin = curr;
-                    let eid = mesh.halfedges[opp].edge;
-                    mesh.halfedges[curr].edge = eid;
-                } else {
-                    let eid = mesh.edges.len();
-                    mesh.edges.push(Edge { he: curr, attr: () });
-                    mesh.halfedges[curr].edge = eid;
-                    edge_map.insert((from, to), curr);
-                }
-            }
-        }
-        for (i, he) in mesh.halfedges.iter().enumerate() {
-            mesh.vertices[he.from].he_out = i;
-        }
-        Ok(mesh)
+/// OFF format: ASCII only, polygonal faces
+pub struct Off;
+
+impl<P: Position> LoadMesh<P, (), ()> for Off {
+    fn load(path: &Path) -> Result<Mesh<P, (), ()>> {
+        Ok(read_off(path)?.map_vertex_attrs(P::from_coords))
     }
+}
 
-pub fn read_off(path: &Path) -> crate::error::Result<Mesh<[f64;3], (), ()>> {
+impl<P: Position> SaveMesh<P, (), ()> for Off {
+    fn save(mesh: &Mesh<P, (), ()>, path: &Path) -> Result<()> {
+        write_off(mesh, path)
+    }
+}
+
+/// OFF loader, see [`read_off`]
+pub fn load<T: Real>(path: &Path) -> Result<Mesh<[T;3], (), ()>> {
+    read_off(path)
+}
+
+pub fn read_off<T: Real>(path: &Path) -> crate::error::Result<Mesh<[T;3], (), ()>> {
     let file = File::open(path)?;
     let mut reader = BufReader::new(file);
     let mut line = String::new();
@@ -84,70 +41,45 @@ pub fn read_off(path: &Path) -> crate::error::Result<Mesh<[f64;3], (), ()>> {
     let parts: Vec<_> = line.split_whitespace().collect();
     let nv: usize = parts[0].parse().unwrap_or(0);
     let nf: usize = parts[1].parse().unwrap_or(0);
-    let mut mesh = Mesh::new();
+    let mut positions: Vec<[T; 3]> = Vec::with_capacity(nv);
     for _ in 0..nv {
         line.clear();
         reader.read_line(&mut line)?;
-        let p: Vec<f64> = line.split_whitespace().map(|s: &str| s.parse::<f64>().unwrap_or(0.0)).collect();
-        mesh.vertices.push(Vertex { he_out: 0, attr: [p[0], p[1], p[2]] });
+        let p: Vec<T> = line.split_whitespace().map(|s: &str| s.parse::<T>().unwrap_or(T::ZERO)).collect();
+        positions.push([p[0], p[1], p[2]]);
     }
-    let mut face_indices: Vec<[usize; 3]> = Vec::new();
+    let mut face_counts: Vec<usize> = Vec::with_capacity(nf);
+    let mut indices: Vec<usize> = Vec::new();
     for _ in 0..nf {
         line.clear();
         reader.read_line(&mut line)?;
-        let items: Vec<_> = line.split_whitespace().collect();
-        if items[0] != "3" { continue; }
-        face_indices.push([
-            items[1].parse().unwrap(),
-            items[2].parse().unwrap(),
-            items[3].parse().unwrap()
-        ]);
-    }
-    let mut edge_map = HashMap::new();
-    for inds in face_indices {
-        let fid = mesh.faces.len();
-        mesh.faces.push(Face { he: 0, attr: () });
-        let hb = mesh.halfedges.len();
-        for k in 0..3 {
-            mesh.halfedges.push(HalfEdge { from: inds[k], twin: 0, next: hb+(k+1)%3, edge: 0, face: fid });
-        }
-        mesh.faces[fid].he = hb;
-        for k in 0..3 {
-            let curr = hb + k;
-            let next = mesh.halfedges[curr].next;
-            let from = mesh.halfedges[curr].from;
-            let to = mesh.halfedges[next].from;
-            if let Some(&opp) = edge_map.get(&(to, from)) {
-                mesh.halfedges[curr].twin = opp;
-                mesh.halfedges[opp].twin = curr;
-                let eid = mesh.halfedges[opp].edge;
-                mesh.halfedges[curr].edge = eid;
-            } else {
-                let eid = mesh.edges.len();
-                mesh.edges.push(Edge { he: curr, attr: () });
-                mesh.halfedges[curr].edge = eid;
-                edge_map.insert((from, to), curr);
-            }
+        let items: Vec<usize> = line.split_whitespace()
+            .map(|s| s.parse::<usize>().map_err(|e| crate::error::Error::Parse(e.to_string())))
+            .collect::<crate::error::Result<Vec<_>>>()?;
+        let degree = items.first().copied().unwrap_or(0);
+        if degree < 3 || items.len() < degree + 1 {
+            return Err(crate::error::Error::Parse(format!("invalid OFF face: {}", line.trim())));
         }
+        face_counts.push(degree);
+        indices.extend_from_slice(&items[1..=degree]);
     }
-    for (i, he) in mesh.halfedges.iter().enumerate() {
-        mesh.vertices[he.from].he_out = i;
-    }
-    Ok(mesh)
+    Mesh::from_vertices_and_faces(positions, &face_counts, &indices)
 }
 
-pub fn write_off(mesh: &Mesh<[f64;3], (), ()>, path: &Path) -> crate::error::Result<()> {
+pub fn write_off<P: Position>(mesh: &Mesh<P, (), ()>, path: &Path) -> crate::error::Result<()> {
     let mut file = File::create(path)?;
     writeln!(file, "OFF")?;
-    writeln!(file, "{} {} 0", mesh.vertices.len(), mesh.faces.len())?;
+    writeln!(file, "{} {} 0", mesh.vertices.len(), mesh.face_ids().count())?;
     for v in &mesh.vertices {
-        writeln!(file, "{} {} {}", v.attr[0], v.attr[1], v.attr[2])?;
+        let [x, y, z] = v.attr.coords();
+        writeln!(file, "{} {} {}", x, y, z)?;
     }
-    for f in &mesh.faces {
-        let h0 = f.he;
-        let h1 = mesh.halfedges[h0].next;
-        let h2 = mesh.halfedges[h1].next;
-        writeln!(file, "3 {} {} {}", mesh.halfedges[h0].from, mesh.halfedges[h1].from, mesh.halfedges[h2].from)?;
+    for f in mesh.face_ids() {
+        write!(file, "{}", mesh.face_degree(f))?;
+        for v in mesh.face_vertices(f) {
+            write!(file, " {}", v)?;
+        }
+        writeln!(file)?;
     }
     Ok(())
 }
\ No newline at end of file
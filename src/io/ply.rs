@@ -1,84 +1,247 @@
-
-
-use crate::mesh::half_edge::{Mesh, Vertex, HalfEdge, Edge, Face};
+use crate::error::Error;
+use crate::geometry::real::Real;
+use crate::geometry::vector::Position;
+use crate::io::traits::{LoadMesh, SaveMesh};
+use crate::mesh::half_edge::Mesh;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
-use std::collections::HashMap;
 
+/// Property declared in a PLY header: a scalar, or a list (face indices)
+enum PlyProperty {
+    Scalar(String),
+    List,
+}
+
+/// Element declared in a PLY header
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
 
-pub fn read_ply(path: &Path) -> crate::error::Result<Mesh<[f64;3], (), ()>> {
+/// Read an ASCII PLY file.
+///
+/// Vertex properties other than `x`, `y`, `z` and scalar face properties are
+/// stored as `f64` mesh properties under their PLY names.
+pub fn read_ply<T: Real>(path: &Path) -> crate::error::Result<Mesh<[T;3], (), ()>> {
     let file = File::open(path)?;
     let mut reader = BufReader::new(file);
     let mut line = String::new();
-    let mut nv = 0usize;
-    let mut nf = 0usize;
+    let mut elements: Vec<PlyElement> = Vec::new();
     // parse header
     loop {
         line.clear();
         if reader.read_line(&mut line)? == 0 { break; }
         let tokens: Vec<_> = line.split_whitespace().collect();
         match tokens.as_slice() {
-            ["element", "vertex", num] => nv = num.parse().unwrap_or(0),
-            ["element", "face", num] => nf = num.parse().unwrap_or(0),
+            ["format", format, ..] if *format != "ascii" => {
+                return Err(Error::Unsupported(format!("PLY format {}", format)));
+            }
+            ["element", name, num] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: num.parse().unwrap_or(0),
+                properties: Vec::new(),
+            }),
+            ["property", "list", ..] => {
+                if let Some(element) = elements.last_mut() {
+                    element.properties.push(PlyProperty::List);
+                }
+            }
+            ["property", _, name] => {
+                if let Some(element) = elements.last_mut() {
+                    element.properties.push(PlyProperty::Scalar(name.to_string()));
+                }
+            }
             ["end_header"] => break,
             _ => {}
         }
     }
-    let mut mesh = Mesh::new();
-    // read vertices
-    for _ in 0..nv {
-        line.clear();
-        reader.read_line(&mut line)?;
-        let p: Vec<f64> = line.split_whitespace()
-            .take(3)
-            .map(|s| s.parse().unwrap_or(0.0))
-            .collect();
-        mesh.vertices.push(Vertex { he_out: 0, attr: [p[0], p[1], p[2]] });
+
+    let mut positions: Vec<[T; 3]> = Vec::new();
+    let mut vertex_values: Vec<(String, Vec<f64>)> = Vec::new();
+    let mut face_counts: Vec<usize> = Vec::new();
+    let mut indices: Vec<usize> = Vec::new();
+    let mut face_values: Vec<(String, Vec<f64>)> = Vec::new();
+    for element in &elements {
+        // one column per scalar property besides the position
+        let mut values: Vec<(String, Vec<f64>)> = element.properties.iter().filter_map(|p| match p {
+            PlyProperty::Scalar(name) if !matches!(name.as_str(), "x" | "y" | "z") => Some((name.clone(), Vec::new())),
+            _ => None,
+        }).collect();
+        for _ in 0..element.count {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let mut tok = line.split_whitespace();
+            let mut position = [T::ZERO; 3];
+            let mut polygon = Vec::new();
+            let mut extra = values.iter_mut();
+            for property in &element.properties {
+                match property {
+                    PlyProperty::Scalar(name) => {
+                        let value = tok.next().unwrap_or("0");
+                        match name.as_str() {
+                            "x" => position[0] = value.parse().unwrap_or(T::ZERO),
+                            "y" => position[1] = value.parse().unwrap_or(T::ZERO),
+                            "z" => position[2] = value.parse().unwrap_or(T::ZERO),
+                            _ => extra.next().unwrap().1.push(value.parse().unwrap_or(0.0)),
+                        }
+                    }
+                    PlyProperty::List => {
+                        let n: usize = tok.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+                        polygon = tok.by_ref().take(n).map(|s| s.parse().unwrap_or(0)).collect();
+                    }
+                }
+            }
+            match element.name.as_str() {
+                "vertex" => positions.push(position),
+                "face" => {
+                    face_counts.push(polygon.len());
+                    indices.extend_from_slice(&polygon);
+                }
+                _ => {}
+            }
+        }
+        match element.name.as_str() {
+            "vertex" => vertex_values = values,
+            "face" => face_values = values,
+            _ => {}
+        }
     }
-    // read faces
-    let mut face_indices: Vec<[usize; 3]> = Vec::new();
-    for _ in 0..nf {
-        line.clear();
-        reader.read_line(&mut line)?;
-        let tok: Vec<_> = line.split_whitespace().collect();
-        if tok.len() >= 4 && tok[0] == "3" {
-            let i0 = tok[1].parse().unwrap_or(0);
-            let i1 = tok[2].parse().unwrap_or(0);
-            let i2 = tok[3].parse().unwrap_or(0);
-            face_indices.push([i0, i1, i2]);
+
+    // build half-edge topology
+    let mut mesh = Mesh::from_vertices_and_faces(positions, &face_counts, &indices)?;
+    for (name, values) in vertex_values {
+        let prop = mesh.add_vertex_property::<f64>(&name)?;
+        mesh.property_mut(prop).values_mut().copy_from_slice(&values);
+    }
+    for (name, values) in face_values {
+        let prop = mesh.add_face_property::<f64>(&name)?;
+        mesh.property_mut(prop).values_mut().copy_from_slice(&values);
+    }
+    Ok(mesh)
+}
+
+/// Write an ASCII PLY file, including every `f64` vertex and face property.
+/// Positions are declared as `float` or `double` according to their scalar.
+/// Only live elements are written, with vertices renumbered consecutively.
+pub fn write_ply<P: Position>(mesh: &Mesh<P, (), ()>, path: &Path) -> crate::error::Result<()> {
+    let mut file = File::create(path)?;
+    let vertex_props: Vec<_> = mesh.properties().vertices.names()
+        .filter_map(|name| Some((name, mesh.vertex_property::<f64>(name)?)))
+        .collect();
+    let face_props: Vec<_> = mesh.properties().faces.names()
+        .filter_map(|name| Some((name, mesh.face_property::<f64>(name)?)))
+        .collect();
+
+    writeln!(file, "ply")?;
+    writeln!(file, "format ascii 1.0")?;
+    let mut index = vec![0; mesh.vertices.len()];
+    for (i, v) in mesh.vertex_ids().enumerate() {
+        index[v.index()] = i;
+    }
+    writeln!(file, "element vertex {}", mesh.vertex_ids().count())?;
+    let scalar = if std::mem::size_of::<P::Scalar>() == 4 { "float" } else { "double" };
+    for axis in ["x", "y", "z"] {
+        writeln!(file, "property {} {}", scalar, axis)?;
+    }
+    for (name, _) in &vertex_props {
+        writeln!(file, "property double {}", name)?;
+    }
+    writeln!(file, "element face {}", mesh.face_ids().count())?;
+    writeln!(file, "property list uchar int vertex_indices")?;
+    for (name, _) in &face_props {
+        writeln!(file, "property double {}", name)?;
+    }
+    writeln!(file, "end_header")?;
+
+    for v in mesh.vertex_ids() {
+        let [x, y, z] = mesh.vertices[v].attr.coords();
+        write!(file, "{} {} {}", x, y, z)?;
+        for (_, prop) in &vertex_props {
+            write!(file, " {}", mesh.property(*prop)[v])?;
         }
+        writeln!(file)?;
     }
-    // build half-edge topology (same as others)
-    let mut edge_map: HashMap<(usize, usize), usize> = HashMap::new();
-    for inds in face_indices {
-        let fid = mesh.faces.len();
-        mesh.faces.push(Face { he: 0, attr: () });
-        let hb = mesh.halfedges.len();
-        for k in 0..3 {
-            mesh.halfedges.push(HalfEdge { from: inds[k], twin: 0, next: hb + (k + 1) % 3, edge: 0, face: fid });
+    for f in mesh.face_ids() {
+        write!(file, "{}", mesh.face_degree(f))?;
+        for v in mesh.face_vertices(f) {
+            write!(file, " {}", index[v.index()])?;
         }
-        mesh.faces[fid].he = hb;
-        for k in 0..3 {
-            let curr = hb + k;
-            let next = mesh.halfedges[curr].next;
-            let from = mesh.halfedges[curr].from;
-            let to = mesh.halfedges[next].from;
-            if let Some(&opp) = edge_map.get(&(to, from)) {
-                mesh.halfedges[curr].twin = opp;
-                mesh.halfedges[opp].twin = curr;
-                let eid = mesh.halfedges[opp].edge;
-                mesh.halfedges[curr].edge = eid;
-            } else {
-                let eid = mesh.edges.len();
-                mesh.edges.push(Edge { he: curr, attr: () });
-                mesh.halfedges[curr].edge = eid;
-                edge_map.insert((from, to), curr);
-            }
+        for (_, prop) in &face_props {
+            write!(file, " {}", mesh.property(*prop)[f])?;
         }
+        writeln!(file)?;
     }
-    for (i, he) in mesh.halfedges.iter().enumerate() {
-        mesh.vertices[he.from].he_out = i;
+    Ok(())
+}
+
+/// Stanford PLY format: ASCII, with `f64` vertex and face properties
+pub struct Ply;
+
+impl<P: Position> LoadMesh<P, (), ()> for Ply {
+    fn load(path: &Path) -> crate::error::Result<Mesh<P, (), ()>> {
+        Ok(read_ply(path)?.map_vertex_attrs(P::from_coords))
     }
-    Ok(mesh)
-}
\ No newline at end of file
+}
+
+impl<P: Position> SaveMesh<P, (), ()> for Ply {
+    fn save(mesh: &Mesh<P, (), ()>, path: &Path) -> crate::error::Result<()> {
+        write_ply(mesh, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::handles::VertexId;
+
+    #[test]
+    fn test_ply_roundtrip_properties() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("square.ply");
+        let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+        let mut mesh = Mesh::<[f64; 3], (), ()>::from_vertices_and_faces(positions, &[3, 3], &[0, 1, 2, 0, 2, 3]).unwrap();
+        let temperature = mesh.add_vertex_property::<f64>("temperature").unwrap();
+        mesh.property_mut(temperature).values_mut().copy_from_slice(&[1.0, 2.0, 3.5, -4.0]);
+        let quality = mesh.add_face_property::<f64>("quality").unwrap();
+        mesh.property_mut(quality).values_mut().copy_from_slice(&[0.25, 0.75]);
+        // non-f64 properties are not written
+        mesh.add_vertex_property::<bool>("selected").unwrap();
+        write_ply(&mesh, &path).unwrap();
+
+        let loaded = read_ply::<f64>(&path).unwrap();
+        assert_eq!(loaded.vertices.len(), 4);
+        assert_eq!(loaded.faces.len(), 2);
+        assert_eq!(loaded.vertices[2].attr, [1.0, 1.0, 0.0]);
+        let temperature = loaded.vertex_property::<f64>("temperature").unwrap();
+        assert_eq!(loaded.property(temperature).values(), &[1.0, 2.0, 3.5, -4.0]);
+        let quality = loaded.face_property::<f64>("quality").unwrap();
+        assert_eq!(loaded.property(quality).values(), &[0.25, 0.75]);
+        assert!(loaded.vertex_property::<f64>("selected").is_none());
+    }
+
+    #[test]
+    fn test_ply_skips_deleted_elements() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("collapsed.ply");
+        // vertex 4 at the centre of the unit square, joined to all corners
+        let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0], [0.5, 0.5, 0.0]];
+        let mut mesh = Mesh::<[f64; 3], (), ()>::from_vertices_and_faces(
+            positions, &[3; 4], &[0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4],
+        ).unwrap();
+        let label = mesh.add_vertex_property::<f64>("label").unwrap();
+        mesh.property_mut(label).values_mut().copy_from_slice(&[0.0, 1.0, 2.0, 3.0, 4.0]);
+        let he = mesh.vertex_outgoing_halfedges(VertexId::new(4)).find(|&h| mesh.halfedge_to(h).index() == 2).unwrap();
+        mesh.collapse_edge(he).unwrap();
+        write_ply(&mesh, &path).unwrap();
+
+        let loaded = read_ply::<f64>(&path).unwrap();
+        assert!(loaded.is_valid(), "{:?}", loaded.validate());
+        assert_eq!(loaded.vertices.len(), 4);
+        assert_eq!(loaded.faces.len(), 2);
+        let label = loaded.vertex_property::<f64>("label").unwrap();
+        assert_eq!(loaded.property(label).values(), &[0.0, 1.0, 2.0, 3.0]);
+        assert!(loaded.vertex_ids().all(|v| loaded.vertex_outgoing_halfedges(v).next().is_some()));
+    }
+}
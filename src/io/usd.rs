@@ -10,7 +10,7 @@ include!(concat!(env!("OUT_DIR"), "/usd_bindings.rs"));
 /// Support for loading and saving Pixar USD meshes.
 pub struct USD;
 
-impl<EA, FA> LoadMesh<Vector3, EA, FA> for Mesh3D<EA, FA> {
+impl<EA, FA> LoadMesh<Vector3, EA, FA> for USD {
     fn load(path: &Path) -> Result<Mesh3D<EA, FA>> {
         let path_str = path.to_str().ok_or(Error::Parse(format!("Invalid path: {:?}", path)))?;
         let cpath = CString::new(path_str).unwrap();
@@ -51,7 +51,7 @@ impl<EA, FA> LoadMesh<Vector3, EA, FA> for Mesh3D<EA, FA> {
             indices.push(unsafe { VtArraySize_get(&idx_arr, i) } as usize);
         }
         // Build half-edge mesh from positions and faces
-        let mesh = Mesh3D::from_vertices_and_faces(positions, &face_counts, &indices);
+        let mut mesh = Mesh3D::from_vertices_and_faces(positions, &face_counts, &indices)?;
 
         // Load animations
         let xform = unsafe { UsdGeomXformable_Get(stage, prim) };
@@ -78,7 +78,7 @@ impl<EA, FA> LoadMesh<Vector3, EA, FA> for Mesh3D<EA, FA> {
     }
 }
 
-impl<EA, FA> SaveMesh<Vector3, EA, FA> for Mesh3D<EA, FA> {
+impl<EA, FA> SaveMesh<Vector3, EA, FA> for USD {
     fn save(mesh: &Mesh3D<EA, FA>, path: &Path) -> Result<()> {
         let path_str = path.to_str().ok_or(Error::Parse(format!("Invalid path: {:?}", path)))?;
         let cpath = CString::new(path_str).unwrap();
@@ -149,7 +149,7 @@ mod tests {
         ];
         let face_counts = vec![3];
         let indices = vec![0, 1, 2];
-        let mesh = Mesh3D::from_vertices_and_faces(positions.clone(), &face_counts, &indices);
+        let mesh = Mesh3D::<(), ()>::from_vertices_and_faces(positions.clone(), &face_counts, &indices)?;
 
         let dir = tempdir()?;
         let file = dir.path().join("test.usd");
@@ -5,6 +5,7 @@ pub mod operators;
 pub mod geometry;
 pub mod io;
 pub mod algorithms;
+pub mod dec;
 
 pub use prelude::*;
 
@@ -12,7 +13,8 @@ pub use prelude::*;
 pub mod prelude {
     //! Common imports for ease of use
     pub use crate::error::Error;
-    pub use crate::mesh::half_edge::Mesh;
+    pub use crate::geometry::vector::Vector3;
+    pub use crate::mesh::half_edge::{Mesh, Mesh3D};
     pub use crate::operators::traits::Operator;
 }
 // {{ ... }}
\ No newline at end of file
@@ -0,0 +1,298 @@
+//! Lazy circulators over one-rings, face loops and edge sides
+//!
+//! Vertex circulators start at the outgoing boundary half-edge of boundary
+//! vertices so open one-rings are visited in order, and yield nothing for
+//! isolated vertices. Face circulators follow `next` and work for faces of
+//! any degree.
+
+use crate::mesh::half_edge::{EdgeId, FaceId, HalfEdgeId, Mesh, VertexId};
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Outgoing half-edge where one-ring circulation of `vid` starts: the
+    /// outgoing boundary half-edge on the boundary, `he_out` otherwise.
+    /// Returns [`HalfEdgeId::INVALID`] for isolated vertices.
+    pub(crate) fn ring_start(&self, vid: VertexId) -> HalfEdgeId {
+        let start = self.vertices[vid].he_out;
+        if !start.is_valid() { return HalfEdgeId::INVALID; }
+        let mut he = start;
+        loop {
+            if self.is_boundary_halfedge(he) { return he; }
+            he = self.halfedges[self.halfedges[he].twin].next;
+            if he == start { return start; }
+        }
+    }
+
+    /// Target vertex of half-edge `he`
+    pub fn halfedge_to(&self, he: HalfEdgeId) -> VertexId {
+        self.halfedges[self.halfedges[he].next].from
+    }
+
+    /// Half-edge whose `next` is `he`, found by walking around its loop
+    pub fn prev_halfedge(&self, he: HalfEdgeId) -> HalfEdgeId {
+        let mut prev = he;
+        loop {
+            let next = self.halfedges[prev].next;
+            if next == he { return prev; }
+            prev = next;
+        }
+    }
+
+    /// Outgoing half-edges of vertex `vid`
+    pub fn vertex_outgoing_halfedges(&self, vid: VertexId) -> VertexOutgoingHalfedges<'_, VA, EA, FA> {
+        let start = self.ring_start(vid);
+        VertexOutgoingHalfedges { mesh: self, start, current: start }
+    }
+
+    /// Incoming half-edges of vertex `vid`
+    pub fn vertex_incoming_halfedges(&self, vid: VertexId) -> VertexIncomingHalfedges<'_, VA, EA, FA> {
+        VertexIncomingHalfedges { inner: self.vertex_outgoing_halfedges(vid) }
+    }
+
+    /// Vertices adjacent to vertex `vid`
+    pub fn vertex_vertices(&self, vid: VertexId) -> VertexVertices<'_, VA, EA, FA> {
+        VertexVertices { inner: self.vertex_outgoing_halfedges(vid) }
+    }
+
+    /// Edges incident to vertex `vid`
+    pub fn vertex_edges(&self, vid: VertexId) -> VertexEdges<'_, VA, EA, FA> {
+        VertexEdges { inner: self.vertex_outgoing_halfedges(vid) }
+    }
+
+    /// Faces incident to vertex `vid`, skipping the boundary gap
+    pub fn vertex_faces(&self, vid: VertexId) -> VertexFaces<'_, VA, EA, FA> {
+        VertexFaces { inner: self.vertex_outgoing_halfedges(vid) }
+    }
+
+    /// Same as [`Mesh::vertex_vertices`]
+    pub fn vertex_neighbors(&self, vid: VertexId) -> VertexVertices<'_, VA, EA, FA> {
+        self.vertex_vertices(vid)
+    }
+
+    /// Same as [`Mesh::vertex_faces`]
+    pub fn vertex_incident_faces(&self, vid: VertexId) -> VertexFaces<'_, VA, EA, FA> {
+        self.vertex_faces(vid)
+    }
+
+    /// Same as [`Mesh::vertex_edges`]
+    pub fn vertex_incident_edges(&self, vid: VertexId) -> VertexEdges<'_, VA, EA, FA> {
+        self.vertex_edges(vid)
+    }
+
+    /// Number of edges incident to vertex `vid`
+    pub fn vertex_valence(&self, vid: VertexId) -> usize {
+        self.vertex_outgoing_halfedges(vid).count()
+    }
+
+    /// Half-edges around face `fid`, starting at `faces[fid].he`
+    pub fn face_halfedges(&self, fid: FaceId) -> FaceHalfedges<'_, VA, EA, FA> {
+        let start = self.faces[fid].he;
+        FaceHalfedges { mesh: self, start, current: start }
+    }
+
+    /// Vertices around face `fid`, in winding order
+    pub fn face_vertices(&self, fid: FaceId) -> FaceVertices<'_, VA, EA, FA> {
+        FaceVertices { inner: self.face_halfedges(fid) }
+    }
+
+    /// Edges around face `fid`
+    pub fn face_edges(&self, fid: FaceId) -> FaceEdges<'_, VA, EA, FA> {
+        FaceEdges { inner: self.face_halfedges(fid) }
+    }
+
+    /// Faces sharing an edge with face `fid`, skipping boundary edges
+    pub fn face_faces(&self, fid: FaceId) -> FaceFaces<'_, VA, EA, FA> {
+        FaceFaces { inner: self.face_halfedges(fid) }
+    }
+
+    /// Number of vertices of face `fid`
+    pub fn face_degree(&self, fid: FaceId) -> usize {
+        self.face_halfedges(fid).count()
+    }
+
+    /// The two half-edges of edge `e`
+    pub fn edge_halfedges(&self, e: EdgeId) -> [HalfEdgeId; 2] {
+        let he = self.edges[e].he;
+        [he, self.halfedges[he].twin]
+    }
+
+    /// The two end points of edge `e`
+    pub fn edge_vertices(&self, e: EdgeId) -> [VertexId; 2] {
+        let [h0, h1] = self.edge_halfedges(e);
+        [self.halfedges[h0].from, self.halfedges[h1].from]
+    }
+
+    /// Faces on either side of edge `e`, skipping the boundary side
+    pub fn edge_faces(&self, e: EdgeId) -> EdgeFaces<'_, VA, EA, FA> {
+        EdgeFaces { mesh: self, halfedges: self.edge_halfedges(e), pos: 0 }
+    }
+}
+
+/// Outgoing half-edges of a vertex, in `twin.next` order
+pub struct VertexOutgoingHalfedges<'a, VA, EA, FA> {
+    mesh: &'a Mesh<VA, EA, FA>,
+    start: HalfEdgeId,
+    current: HalfEdgeId,
+}
+
+impl<VA, EA, FA> Iterator for VertexOutgoingHalfedges<'_, VA, EA, FA> {
+    type Item = HalfEdgeId;
+
+    fn next(&mut self) -> Option<HalfEdgeId> {
+        if !self.current.is_valid() { return None; }
+        let he = self.current;
+        let halfedges = &self.mesh.halfedges;
+        self.current = halfedges[halfedges[he].twin].next;
+        if self.current == self.start { self.current = HalfEdgeId::INVALID; }
+        Some(he)
+    }
+}
+
+/// Half-edges around a face, in `next` order
+pub struct FaceHalfedges<'a, VA, EA, FA> {
+    mesh: &'a Mesh<VA, EA, FA>,
+    start: HalfEdgeId,
+    current: HalfEdgeId,
+}
+
+impl<VA, EA, FA> Iterator for FaceHalfedges<'_, VA, EA, FA> {
+    type Item = HalfEdgeId;
+
+    fn next(&mut self) -> Option<HalfEdgeId> {
+        if !self.current.is_valid() { return None; }
+        let he = self.current;
+        self.current = self.mesh.halfedges[he].next;
+        if self.current == self.start { self.current = HalfEdgeId::INVALID; }
+        Some(he)
+    }
+}
+
+/// Faces on the two sides of an edge
+pub struct EdgeFaces<'a, VA, EA, FA> {
+    mesh: &'a Mesh<VA, EA, FA>,
+    halfedges: [HalfEdgeId; 2],
+    pos: usize,
+}
+
+impl<VA, EA, FA> Iterator for EdgeFaces<'_, VA, EA, FA> {
+    type Item = FaceId;
+
+    fn next(&mut self) -> Option<FaceId> {
+        while self.pos < 2 {
+            let face = self.mesh.halfedges[self.halfedges[self.pos]].face;
+            self.pos += 1;
+            if face.is_valid() { return Some(face); }
+        }
+        None
+    }
+}
+
+/// Defines a circulator that maps (and optionally filters) the half-edges of
+/// an underlying half-edge circulator.
+macro_rules! mapped_circulator {
+    ($(#[$doc:meta])* $name:ident, $inner:ident, $item:ty, |$mesh:ident, $he:ident| $map:expr) => {
+        $(#[$doc])*
+        pub struct $name<'a, VA, EA, FA> {
+            inner: $inner<'a, VA, EA, FA>,
+        }
+
+        impl<VA, EA, FA> Iterator for $name<'_, VA, EA, FA> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<$item> {
+                let $mesh = self.inner.mesh;
+                for $he in self.inner.by_ref() {
+                    if let Some(item) = $map {
+                        return Some(item);
+                    }
+                }
+                None
+            }
+        }
+    };
+}
+
+mapped_circulator!(
+    /// Incoming half-edges of a vertex
+    VertexIncomingHalfedges, VertexOutgoingHalfedges, HalfEdgeId,
+    |mesh, he| Some(mesh.halfedges[he].twin)
+);
+mapped_circulator!(
+    /// Vertices adjacent to a vertex
+    VertexVertices, VertexOutgoingHalfedges, VertexId,
+    |mesh, he| Some(mesh.halfedge_to(he))
+);
+mapped_circulator!(
+    /// Edges incident to a vertex
+    VertexEdges, VertexOutgoingHalfedges, EdgeId,
+    |mesh, he| Some(mesh.halfedges[he].edge)
+);
+mapped_circulator!(
+    /// Faces incident to a vertex
+    VertexFaces, VertexOutgoingHalfedges, FaceId,
+    |mesh, he| Some(mesh.halfedges[he].face).filter(|f| f.is_valid())
+);
+mapped_circulator!(
+    /// Vertices of a face
+    FaceVertices, FaceHalfedges, VertexId,
+    |mesh, he| Some(mesh.halfedges[he].from)
+);
+mapped_circulator!(
+    /// Edges of a face
+    FaceEdges, FaceHalfedges, EdgeId,
+    |mesh, he| Some(mesh.halfedges[he].edge)
+);
+mapped_circulator!(
+    /// Faces adjacent to a face across its edges
+    FaceFaces, FaceHalfedges, FaceId,
+    |mesh, he| Some(mesh.halfedges[mesh.halfedges[he].twin].face).filter(|f| f.is_valid())
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::{FaceId, Mesh3D, VertexId};
+
+    #[test]
+    fn test_circulators_open_polygon_mesh() {
+        // Quad (0,1,2,3) next to triangle (1,4,2)
+        let mesh = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 5], &[4,3], &[0,1,2,3, 1,4,2],
+        ).unwrap();
+        let (quad, tri) = (FaceId::new(0), FaceId::new(1));
+        assert_eq!(mesh.face_vertices(quad).collect::<Vec<_>>(), vec![0,1,2,3]);
+        assert_eq!(mesh.face_degree(quad), 4);
+        assert_eq!(mesh.face_degree(tri), 3);
+        assert_eq!(mesh.face_faces(quad).collect::<Vec<_>>(), vec![tri]);
+        assert_eq!(mesh.face_edges(tri).count(), 3);
+
+        // Vertex 1 is on the boundary: ring starts at the gap
+        let v1 = VertexId::new(1);
+        assert_eq!(mesh.vertex_vertices(v1).collect::<Vec<_>>(), vec![0, 2, 4]);
+        assert_eq!(mesh.vertex_faces(v1).collect::<Vec<_>>(), vec![quad, tri]);
+        assert_eq!(mesh.vertex_valence(v1), 3);
+        for he in mesh.vertex_incoming_halfedges(v1) {
+            assert_eq!(mesh.halfedge_to(he), v1);
+        }
+
+        let shared = mesh.vertex_edges(v1).find(|&e| !mesh.is_boundary_edge(e)).unwrap();
+        let mut ends = mesh.edge_vertices(shared);
+        ends.sort();
+        assert_eq!(ends, [1, 2]);
+        assert_eq!(mesh.edge_faces(shared).collect::<Vec<_>>(), vec![quad, tri]);
+        let outer = mesh.vertex_edges(VertexId::new(0)).next().unwrap();
+        assert_eq!(mesh.edge_faces(outer).count(), 1);
+    }
+
+    #[test]
+    fn test_circulators_closed_and_isolated() {
+        let tet = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 5], &[3,3,3,3], &[0,2,1, 0,1,3, 1,2,3, 2,0,3],
+        ).unwrap();
+        let mut ring: Vec<_> = tet.vertex_vertices(VertexId::new(0)).collect();
+        ring.sort();
+        assert_eq!(ring, vec![1, 2, 3]);
+        assert_eq!(tet.vertex_faces(VertexId::new(3)).count(), 3);
+        assert_eq!(tet.vertex_outgoing_halfedges(VertexId::new(4)).count(), 0);
+        assert_eq!(tet.vertex_faces(VertexId::new(4)).count(), 0);
+    }
+}
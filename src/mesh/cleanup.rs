@@ -0,0 +1,231 @@
+//! Polygon soup cleanup before half-edge construction
+//!
+//! STL-like inputs repeat every vertex once per face and contain slivers of
+//! zero area, which break the cotangent weights and gradients of the
+//! operators. [`PolygonSoup::clean`] welds coincident vertices, drops
+//! degenerate and duplicate faces and compacts the vertex list.
+
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::INVALID;
+use crate::mesh::soup::PolygonSoup;
+use std::collections::{HashMap, HashSet};
+
+/// What [`PolygonSoup::clean`] changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// Vertices merged into an earlier vertex within the tolerance
+    pub welded_vertices: usize,
+    /// Faces removed for having fewer than three distinct corners or no area
+    pub degenerate_faces: usize,
+    /// Faces removed for repeating the vertices of an earlier face
+    pub duplicate_faces: usize,
+    /// Vertices removed because no face referenced them, welded ones included
+    pub removed_vertices: usize,
+    /// New id of every original vertex, that of its weld target for welded
+    /// vertices and [`INVALID`] for unreferenced ones
+    pub vertex_map: Vec<usize>,
+    /// Original id of every remaining face
+    pub face_map: Vec<usize>,
+}
+
+impl<VA: Copy + Into<Vector3>> PolygonSoup<VA> {
+    /// Run all cleanup steps with distance `tolerance`, in the order weld,
+    /// degenerate faces, duplicate faces, unreferenced vertices.
+    pub fn clean(&mut self, tolerance: f64) -> CleanupReport {
+        let representative = self.weld(tolerance);
+        let welded_vertices = representative.iter().enumerate().filter(|&(v, &r)| v != r).count();
+        let mut face_map: Vec<usize> = (0..self.face_count()).collect();
+        let degenerate = self.remove_degenerate_faces(tolerance);
+        retain_unremoved(&mut face_map, &degenerate);
+        let duplicate = self.remove_duplicate_faces();
+        retain_unremoved(&mut face_map, &duplicate);
+        let compacted = self.remove_unreferenced_vertices();
+        CleanupReport {
+            welded_vertices,
+            degenerate_faces: degenerate.len(),
+            duplicate_faces: duplicate.len(),
+            removed_vertices: compacted.iter().filter(|&&v| v == INVALID).count(),
+            vertex_map: representative.iter().map(|&r| compacted[r]).collect(),
+            face_map,
+        }
+    }
+
+    /// Redirect face corners from every vertex to the first vertex within
+    /// `tolerance` of it, using a spatial hash with cells of that size; with
+    /// a tolerance of zero only identical positions are merged. Vertices are
+    /// not removed, see [`PolygonSoup::remove_unreferenced_vertices`].
+    /// Returns the number of merged vertices.
+    pub fn weld_vertices(&mut self, tolerance: f64) -> usize {
+        self.weld(tolerance).iter().enumerate().filter(|&(v, &r)| v != r).count()
+    }
+
+    /// Weld and return the vertex every vertex was merged into
+    fn weld(&mut self, tolerance: f64) -> Vec<usize> {
+        let cell_size = if tolerance > 0.0 { tolerance } else { 1.0 };
+        let cell = |p: Vector3| {
+            [(p.x / cell_size).floor() as i64, (p.y / cell_size).floor() as i64, (p.z / cell_size).floor() as i64]
+        };
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut representative: Vec<usize> = (0..self.vertices.len()).collect();
+        for (v, &attr) in self.vertices.iter().enumerate() {
+            let p: Vector3 = attr.into();
+            let [x, y, z] = cell(p);
+            let mut nearest = None;
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        for &w in grid.get(&[x + dx, y + dy, z + dz]).into_iter().flatten() {
+                            if (p - self.vertices[w].into()).norm() <= tolerance {
+                                nearest = Some(w);
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+            match nearest {
+                Some(w) => representative[v] = w,
+                None => grid.entry([x, y, z]).or_default().push(v),
+            }
+        }
+        for i in self.face_indices.iter_mut() {
+            *i = representative[*i];
+        }
+        representative
+    }
+
+    /// Remove repeated consecutive corners, then every face left with fewer
+    /// than three corners, a vertex visited twice, or an area of at most
+    /// `tolerance` times its longest edge (a sliver thinner than the
+    /// tolerance). Returns the removed faces.
+    pub fn remove_degenerate_faces(&mut self, tolerance: f64) -> Vec<usize> {
+        let mut removed = Vec::new();
+        let mut counts = Vec::with_capacity(self.face_count());
+        let mut indices = Vec::with_capacity(self.face_indices.len());
+        for (f, face) in self.faces().enumerate() {
+            let mut ring: Vec<usize> = face.to_vec();
+            ring.dedup();
+            while ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
+            }
+            let distinct: HashSet<usize> = ring.iter().copied().collect();
+            if ring.len() < 3 || distinct.len() < ring.len() || self.is_sliver(&ring, tolerance) {
+                removed.push(f);
+            } else {
+                counts.push(ring.len());
+                indices.extend_from_slice(&ring);
+            }
+        }
+        self.face_counts = counts;
+        self.face_indices = indices;
+        removed
+    }
+
+    fn is_sliver(&self, ring: &[usize], tolerance: f64) -> bool {
+        let points: Vec<Vector3> = ring.iter().map(|&v| self.vertices[v].into()).collect();
+        let n = points.len();
+        let normal = (0..n).fold(Vector3::zero(), |acc, i| acc + points[i].cross(&points[(i + 1) % n]));
+        let longest = (0..n).map(|i| (points[(i + 1) % n] - points[i]).norm()).fold(0.0, f64::max);
+        0.5 * normal.norm() <= tolerance * longest
+    }
+}
+
+impl<VA> PolygonSoup<VA> {
+    /// Remove faces with the same vertex set as an earlier face, whatever
+    /// their starting corner or winding. Returns the removed faces.
+    pub fn remove_duplicate_faces(&mut self) -> Vec<usize> {
+        let mut seen: HashSet<Vec<usize>> = HashSet::new();
+        let mut removed = Vec::new();
+        let mut counts = Vec::with_capacity(self.face_count());
+        let mut indices = Vec::with_capacity(self.face_indices.len());
+        for (f, face) in self.faces().enumerate() {
+            let mut key = face.to_vec();
+            key.sort_unstable();
+            if seen.insert(key) {
+                counts.push(face.len());
+                indices.extend_from_slice(face);
+            } else {
+                removed.push(f);
+            }
+        }
+        self.face_counts = counts;
+        self.face_indices = indices;
+        removed
+    }
+
+    /// Drop vertices no face refers to and renumber the rest in order.
+    /// Returns the new id of every old vertex, [`INVALID`] if it was dropped.
+    pub fn remove_unreferenced_vertices(&mut self) -> Vec<usize> {
+        let mut used = vec![false; self.vertices.len()];
+        for &i in &self.face_indices {
+            used[i] = true;
+        }
+        let mut map = vec![INVALID; self.vertices.len()];
+        let mut next = 0;
+        for (v, &keep) in used.iter().enumerate() {
+            if keep {
+                map[v] = next;
+                next += 1;
+            }
+        }
+        let mut keep = used.into_iter();
+        self.vertices.retain(|_| keep.next().unwrap_or(false));
+        for i in self.face_indices.iter_mut() {
+            *i = map[*i];
+        }
+        map
+    }
+}
+
+/// Remove the entries at the sorted positions `removed` from `map`
+fn retain_unremoved(map: &mut Vec<usize>, removed: &[usize]) {
+    let mut removed = removed.iter().peekable();
+    let mut position = 0;
+    map.retain(|_| {
+        let drop = removed.next_if_eq(&&position).is_some();
+        position += 1;
+        !drop
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::half_edge::Mesh;
+
+    #[test]
+    fn test_clean_stl_like_soup() {
+        // a unit square as two triangles with per-face vertices and noise,
+        // a duplicate of the first triangle wound the other way, a sliver
+        // and an unused vertex
+        let positions = vec![
+            [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0],
+            [1e-7, 0.0, 0.0], [1.0, 1.0, 1e-7], [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [1.0, 1.0, 0.0],
+            [0.5, 0.5, 0.0], [5.0, 5.0, 5.0],
+        ];
+        let mut soup = PolygonSoup::new(positions, vec![3; 4], vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 0, 2, 9]);
+        let report = soup.clean(1e-6);
+        assert_eq!(report.welded_vertices, 5);
+        assert_eq!(report.degenerate_faces, 1);
+        assert_eq!(report.duplicate_faces, 1);
+        assert_eq!(report.removed_vertices, 7);
+        assert_eq!(report.vertex_map, vec![0, 1, 2, 0, 2, 3, 1, 0, 2, INVALID, INVALID]);
+        assert_eq!(report.face_map, vec![0, 1]);
+        assert_eq!(soup.vertices.len(), 4);
+        assert_eq!(soup.face_indices, vec![0, 1, 2, 0, 2, 3]);
+        let mesh: Mesh<[f64; 3], (), ()> = soup.into_mesh().unwrap();
+        assert!(mesh.is_disk());
+    }
+
+    #[test]
+    fn test_degenerate_corners() {
+        let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+        // a quad with a repeated corner survives as a triangle; a face that
+        // folds back onto an edge does not
+        let mut soup = PolygonSoup::new(positions, vec![4, 3, 4], vec![0, 1, 1, 2, 0, 1, 0, 0, 2, 3, 2]);
+        assert_eq!(soup.remove_degenerate_faces(0.0), vec![1, 2]);
+        assert_eq!(soup.face_counts, vec![3]);
+        assert_eq!(soup.face_indices, vec![0, 1, 2]);
+    }
+}
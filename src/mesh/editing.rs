@@ -0,0 +1,573 @@
+//! Topological editing: edge flips, splits and collapses
+//!
+//! Editing operations keep all half-edge pointers consistent. Elements they
+//! remove are only marked as deleted, so ids of the remaining elements stay
+//! stable until [`Mesh::garbage_collect`] compacts the arrays.
+
+use crate::error::{Error, Result};
+use crate::mesh::handles::Handle;
+use crate::mesh::half_edge::{Edge, EdgeId, Face, FaceId, HalfEdge, HalfEdgeId, Mesh, Vertex, VertexId, INVALID};
+
+/// Deletion flags per element kind; missing entries mean "not deleted"
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Deleted {
+    pub vertices: Vec<bool>,
+    pub halfedges: Vec<bool>,
+    pub edges: Vec<bool>,
+    pub faces: Vec<bool>,
+}
+
+impl Deleted {
+    fn is_empty(&self) -> bool {
+        [&self.vertices, &self.halfedges, &self.edges, &self.faces]
+            .iter()
+            .all(|flags| !flags.contains(&true))
+    }
+}
+
+fn flag(flags: &[bool], id: impl Handle) -> bool {
+    flags.get(id.index()).copied().unwrap_or(false)
+}
+
+fn mark(flags: &mut Vec<bool>, id: impl Handle) {
+    let i = id.index();
+    if flags.len() <= i {
+        flags.resize(i + 1, false);
+    }
+    flags[i] = true;
+}
+
+/// Old-to-new id tables returned by [`Mesh::garbage_collect`], indexed by
+/// the old raw index; removed elements map to the `INVALID` handle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdRemap {
+    pub vertices: Vec<VertexId>,
+    pub halfedges: Vec<HalfEdgeId>,
+    pub edges: Vec<EdgeId>,
+    pub faces: Vec<FaceId>,
+}
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Whether vertex `vid` was removed by an editing operation
+    pub fn is_vertex_deleted(&self, vid: VertexId) -> bool {
+        flag(&self.deleted.vertices, vid)
+    }
+
+    /// Whether half-edge `he` was removed by an editing operation
+    pub fn is_halfedge_deleted(&self, he: HalfEdgeId) -> bool {
+        flag(&self.deleted.halfedges, he)
+    }
+
+    /// Whether edge `e` was removed by an editing operation
+    pub fn is_edge_deleted(&self, e: EdgeId) -> bool {
+        flag(&self.deleted.edges, e)
+    }
+
+    /// Whether face `fid` was removed by an editing operation
+    pub fn is_face_deleted(&self, fid: FaceId) -> bool {
+        flag(&self.deleted.faces, fid)
+    }
+
+    /// Whether any element is marked as deleted
+    pub fn has_garbage(&self) -> bool {
+        !self.deleted.is_empty()
+    }
+
+    /// Whether edge `e` can be flipped: both sides are triangles and the
+    /// opposite vertices are not already connected.
+    pub fn is_flip_ok(&self, e: EdgeId) -> bool {
+        if self.is_edge_deleted(e) || self.is_boundary_edge(e) {
+            return false;
+        }
+        let [h, t] = self.edge_halfedges(e);
+        let f0 = self.halfedges[h].face;
+        let f1 = self.halfedges[t].face;
+        if self.face_degree(f0) != 3 || self.face_degree(f1) != 3 {
+            return false;
+        }
+        let c = self.halfedge_to(self.halfedges[h].next);
+        let d = self.halfedge_to(self.halfedges[t].next);
+        c != d && !self.vertex_vertices(c).any(|w| w == d)
+    }
+
+    /// Flip edge `e` between two triangles so it connects their opposite
+    /// vertices. The edge and both faces keep their ids.
+    pub fn flip_edge(&mut self, e: EdgeId) -> Result<()> {
+        if !self.is_flip_ok(e) {
+            return Err(Error::Topology(format!("edge {} cannot be flipped", e)));
+        }
+        // Triangles (a, b, c) = h, h1, h2 and (b, a, d) = t, t1, t2
+        let [h, t] = self.edge_halfedges(e);
+        let h1 = self.halfedges[h].next;
+        let h2 = self.halfedges[h1].next;
+        let t1 = self.halfedges[t].next;
+        let t2 = self.halfedges[t1].next;
+        let a = self.halfedges[h].from;
+        let b = self.halfedges[t].from;
+        let c = self.halfedges[h2].from;
+        let d = self.halfedges[t2].from;
+        let f0 = self.halfedges[h].face;
+        let f1 = self.halfedges[t].face;
+
+        // New triangles (d, c, a) = h, h2, t1 and (c, d, b) = t, t2, h1
+        self.halfedges[h].from = d;
+        self.halfedges[t].from = c;
+        self.halfedges[h].next = h2;
+        self.halfedges[h2].next = t1;
+        self.halfedges[t1].next = h;
+        self.halfedges[t].next = t2;
+        self.halfedges[t2].next = h1;
+        self.halfedges[h1].next = t;
+        self.halfedges[t1].face = f0;
+        self.halfedges[h1].face = f1;
+        self.faces[f0].he = h;
+        self.faces[f1].he = t;
+        if self.vertices[a].he_out == h {
+            self.vertices[a].he_out = t1;
+        }
+        if self.vertices[b].he_out == t {
+            self.vertices[b].he_out = h1;
+        }
+        Ok(())
+    }
+
+    /// Insert a new vertex with attribute `attr` on edge `e`.
+    ///
+    /// Adjacent triangles are split in two by connecting the new vertex to
+    /// their opposite corner; larger faces simply gain a vertex. The half of
+    /// the edge starting at the origin of `edges[e].he` keeps id `e`.
+    pub fn split_edge(&mut self, e: EdgeId, attr: VA) -> VertexId
+    where
+        EA: Default,
+        FA: Default,
+    {
+        // h: a -> b and t: b -> a become a -> v -> b and b -> v -> a
+        let [h, t] = self.edge_halfedges(e);
+        let v = VertexId::new(self.vertices.len());
+        let e2 = EdgeId::new(self.edges.len());
+        let h2 = HalfEdgeId::new(self.halfedges.len());
+        let t2 = HalfEdgeId::new(self.halfedges.len() + 1);
+        let (fh, ft) = (self.halfedges[h].face, self.halfedges[t].face);
+        let (tri_h, tri_t) = (
+            fh.is_valid() && self.face_degree(fh) == 3,
+            ft.is_valid() && self.face_degree(ft) == 3,
+        );
+        self.halfedges.push(HalfEdge { from: v, twin: t, next: self.halfedges[h].next, edge: e2, face: fh });
+        self.halfedges.push(HalfEdge { from: v, twin: h, next: self.halfedges[t].next, edge: e, face: ft });
+        self.halfedges[h].next = h2;
+        self.halfedges[h].twin = t2;
+        self.halfedges[t].next = t2;
+        self.halfedges[t].twin = h2;
+        self.halfedges[t].edge = e2;
+        self.edges.push(Edge { he: h2, attr: EA::default() });
+        self.edges[e].he = h;
+        // the boundary side, if any, provides the outgoing half-edge
+        let he_out = if ft.is_valid() { h2 } else { t2 };
+        self.vertices.push(Vertex { he_out, attr });
+
+        if tri_h {
+            let opposite = self.halfedges[self.halfedges[h2].next].next;
+            self.insert_diagonal(h2, opposite, FA::default());
+        }
+        if tri_t {
+            let opposite = self.halfedges[self.halfedges[t2].next].next;
+            self.insert_diagonal(t2, opposite, FA::default());
+        }
+        self.sync_properties();
+        v
+    }
+
+    /// Insert a new vertex with attribute `attr` inside face `fid` and
+    /// connect it to every corner, turning an n-gon into n triangles. The
+    /// triangle on the first half-edge of the face keeps id `fid`.
+    pub fn split_face(&mut self, fid: FaceId, attr: VA) -> VertexId
+    where
+        EA: Default,
+        FA: Default,
+    {
+        let ring: Vec<HalfEdgeId> = self.face_halfedges(fid).collect();
+        let n = ring.len();
+        let v = VertexId::new(self.vertices.len());
+        let he_base = self.halfedges.len();
+        let e_base = self.edges.len();
+        let f_base = self.faces.len();
+        // triangle i is ring[i]: p_i -> p_i+1, back(i+1): p_i+1 -> v and
+        // out(i): v -> p_i, where spoke i = (out(i), back(i)) is edge e_base + i
+        let out = |i: usize| HalfEdgeId::new(he_base + 2 * (i % n));
+        let back = |i: usize| HalfEdgeId::new(he_base + 2 * (i % n) + 1);
+        let spoke = |i: usize| EdgeId::new(e_base + i);
+        let face = |i: usize| match i % n {
+            0 => fid,
+            k => FaceId::new(f_base + k - 1),
+        };
+        for (i, &h) in ring.iter().enumerate() {
+            let p_i = self.halfedges[h].from;
+            self.halfedges.push(HalfEdge { from: v, twin: back(i), next: h, edge: spoke(i), face: face(i) });
+            self.halfedges.push(HalfEdge { from: p_i, twin: out(i), next: out(i + n - 1), edge: spoke(i), face: face(i + n - 1) });
+            self.edges.push(Edge { he: out(i), attr: EA::default() });
+        }
+        for (i, &h) in ring.iter().enumerate() {
+            self.halfedges[h].next = back(i + 1);
+            self.halfedges[h].face = face(i);
+            if i > 0 {
+                self.faces.push(Face { he: h, attr: FA::default() });
+            }
+        }
+        self.faces[fid].he = ring[0];
+        self.vertices.push(Vertex { he_out: out(0), attr });
+        self.sync_properties();
+        v
+    }
+
+    /// Whether collapsing half-edge `he` (merging its origin into its
+    /// target) keeps the mesh a manifold, following the link condition of
+    /// Dey et al. with boundary vertices linked to a virtual apex.
+    pub fn is_collapse_ok(&self, he: HalfEdgeId) -> bool {
+        if self.is_halfedge_deleted(he) {
+            return false;
+        }
+        let o = self.halfedges[he].twin;
+        let v0 = self.halfedges[he].from;
+        let v1 = self.halfedges[o].from;
+        // corners of the triangles that degenerate; larger faces only shrink
+        let apex = |h: HalfEdgeId| {
+            let f = self.halfedges[h].face;
+            if f.is_valid() && self.face_degree(f) == 3 {
+                self.halfedge_to(self.halfedges[h].next)
+            } else {
+                VertexId::INVALID
+            }
+        };
+        let vl = apex(he);
+        let vr = apex(o);
+        if vl.is_valid() && vl == vr {
+            return false;
+        }
+        // an interior edge must not join two boundary parts
+        let boundary_edge = self.is_boundary_halfedge(he) || self.is_boundary_halfedge(o);
+        if !boundary_edge && self.is_boundary_vertex(v0) && self.is_boundary_vertex(v1) {
+            return false;
+        }
+        // a triangle whose other two edges are on the boundary would leave
+        // a dangling edge
+        for (h, corner) in [(he, vl), (o, vr)] {
+            if !corner.is_valid() {
+                continue;
+            }
+            let hn = self.halfedges[h].next;
+            let hp = self.halfedges[hn].next;
+            if self.is_boundary_edge(self.halfedges[hn].edge) && self.is_boundary_edge(self.halfedges[hp].edge) {
+                return false;
+            }
+        }
+        // the one-rings may only share the corners of the removed triangles
+        let ring0: Vec<VertexId> = self.vertex_vertices(v0).collect();
+        if self.vertex_vertices(v1).any(|w| ring0.contains(&w) && w != vl && w != vr) {
+            return false;
+        }
+        // ... and those corners must not span a triangle with both ends
+        // (the collapse of a tetrahedron)
+        if vl.is_valid() && vr.is_valid() {
+            let spans = |v: VertexId| {
+                self.vertex_faces(v).any(|f| {
+                    self.face_degree(f) == 3 && self.face_vertices(f).all(|w| w == v || w == vl || w == vr)
+                })
+            };
+            if spans(v0) && spans(v1) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Collapse half-edge `he`, merging its origin into its target, and
+    /// return the surviving vertex. Triangles on either side of the edge are
+    /// removed. Fails with `Error::Topology` if [`Mesh::is_collapse_ok`] does
+    /// not hold.
+    pub fn collapse_edge(&mut self, he: HalfEdgeId) -> Result<VertexId> {
+        if !self.is_collapse_ok(he) {
+            return Err(Error::Topology(format!(
+                "collapsing half-edge {} would violate the link condition", he
+            )));
+        }
+        let o = self.halfedges[he].twin;
+        let v0 = self.halfedges[he].from;
+        let v1 = self.halfedges[o].from;
+        let (hn, hp) = (self.halfedges[he].next, self.prev_halfedge(he));
+        let (on, op) = (self.halfedges[o].next, self.prev_halfedge(o));
+
+        let outgoing: Vec<HalfEdgeId> = self.vertex_outgoing_halfedges(v0).collect();
+        for h in outgoing {
+            self.halfedges[h].from = v1;
+        }
+        self.halfedges[hp].next = hn;
+        self.halfedges[op].next = on;
+        for (h, succ) in [(he, hn), (o, on)] {
+            let f = self.halfedges[h].face;
+            if f.is_valid() {
+                self.faces[f].he = succ;
+            }
+        }
+        self.vertices[v1].he_out = hn;
+        self.vertices[v0].he_out = HalfEdgeId::INVALID;
+        let e = self.halfedges[he].edge;
+        mark(&mut self.deleted.halfedges, he);
+        mark(&mut self.deleted.halfedges, o);
+        mark(&mut self.deleted.edges, e);
+        mark(&mut self.deleted.vertices, v0);
+
+        self.remove_digon(hn);
+        self.remove_digon(on);
+        self.vertices[v1].he_out = self.ring_start(v1);
+        Ok(v1)
+    }
+
+    /// Compact the element arrays and properties by dropping deleted
+    /// elements and return the old-to-new id tables. If anything was removed, the mesh
+    /// generation advances so that older [`Generational`] handles no longer
+    /// resolve.
+    ///
+    /// [`Generational`]: crate::mesh::handles::Generational
+    pub fn garbage_collect(&mut self) -> IdRemap {
+        if self.has_garbage() {
+            self.generation += 1;
+        }
+        let remap = IdRemap {
+            vertices: compact(&mut self.vertices, &self.deleted.vertices),
+            halfedges: compact(&mut self.halfedges, &self.deleted.halfedges),
+            edges: compact(&mut self.edges, &self.deleted.edges),
+            faces: compact(&mut self.faces, &self.deleted.faces),
+        };
+        for v in &mut self.vertices {
+            v.he_out = lookup(&remap.halfedges, v.he_out);
+        }
+        for he in &mut self.halfedges {
+            he.from = lookup(&remap.vertices, he.from);
+            he.twin = lookup(&remap.halfedges, he.twin);
+            he.next = lookup(&remap.halfedges, he.next);
+            he.edge = lookup(&remap.edges, he.edge);
+            he.face = lookup(&remap.faces, he.face);
+        }
+        for e in &mut self.edges {
+            e.he = lookup(&remap.halfedges, e.he);
+        }
+        for f in &mut self.faces {
+            f.he = lookup(&remap.halfedges, f.he);
+        }
+        self.compact_properties();
+        self.deleted = Deleted::default();
+        remap
+    }
+
+    /// Close the boundary loop of half-edge `he` with a single polygonal face
+    /// with attribute `attr`. Returns `Error::Topology` if `he` is not a
+    /// boundary half-edge.
+    pub fn fill_boundary_loop(&mut self, he: HalfEdgeId, attr: FA) -> Result<FaceId> {
+        if self.is_halfedge_deleted(he) || !self.is_boundary_halfedge(he) {
+            return Err(Error::Topology(format!("half-edge {} is not on the boundary", he)));
+        }
+        let f = FaceId::new(self.faces.len());
+        let mut h = he;
+        loop {
+            self.halfedges[h].face = f;
+            h = self.halfedges[h].next;
+            if h == he { break; }
+        }
+        self.faces.push(Face { he, attr });
+        self.sync_properties();
+        Ok(f)
+    }
+
+    /// Split the face of half-edges `ha` and `hb` by a new edge joining their
+    /// origins. The part starting at `ha` keeps the face id; the other part
+    /// becomes a new face with attribute `attr`.
+    pub(crate) fn insert_diagonal(&mut self, ha: HalfEdgeId, hb: HalfEdgeId, attr: FA)
+    where
+        EA: Default,
+    {
+        let f = self.halfedges[ha].face;
+        let x = self.halfedges[ha].from;
+        let y = self.halfedges[hb].from;
+        let (pa, pb) = (self.prev_halfedge(ha), self.prev_halfedge(hb));
+        let g = FaceId::new(self.faces.len());
+        let e = EdgeId::new(self.edges.len());
+        let n1 = HalfEdgeId::new(self.halfedges.len());
+        let n2 = HalfEdgeId::new(self.halfedges.len() + 1);
+        // ha .. pb, then n1: y -> x; hb .. pa, then n2: x -> y
+        self.halfedges.push(HalfEdge { from: y, twin: n2, next: ha, edge: e, face: f });
+        self.halfedges.push(HalfEdge { from: x, twin: n1, next: hb, edge: e, face: g });
+        self.halfedges[pb].next = n1;
+        self.halfedges[pa].next = n2;
+        self.edges.push(Edge { he: n1, attr: EA::default() });
+        self.faces.push(Face { he: hb, attr });
+        self.faces[f].he = ha;
+        let mut h = hb;
+        while h != n2 {
+            self.halfedges[h].face = g;
+            h = self.halfedges[h].next;
+        }
+    }
+
+    /// If the loop of `h` has shrunk to two half-edges, remove it: drop its
+    /// face (if any) and merge its two edges into one.
+    fn remove_digon(&mut self, h: HalfEdgeId) {
+        let g = self.halfedges[h].next;
+        if self.halfedges[g].next != h {
+            return;
+        }
+        // h: a -> b, g: b -> a, with twins ht: b -> a and gt: a -> b
+        let ht = self.halfedges[h].twin;
+        let gt = self.halfedges[g].twin;
+        let e = self.halfedges[h].edge;
+        let eg = self.halfedges[g].edge;
+        self.halfedges[ht].twin = gt;
+        self.halfedges[gt].twin = ht;
+        self.halfedges[gt].edge = e;
+        self.edges[e].he = ht;
+        for (x, replacement) in [(h, gt), (g, ht)] {
+            let v = self.halfedges[x].from;
+            if self.vertices[v].he_out == x {
+                self.vertices[v].he_out = replacement;
+            }
+        }
+        let f = self.halfedges[h].face;
+        if f.is_valid() {
+            mark(&mut self.deleted.faces, f);
+        }
+        mark(&mut self.deleted.halfedges, h);
+        mark(&mut self.deleted.halfedges, g);
+        mark(&mut self.deleted.edges, eg);
+    }
+}
+
+/// Drop the flagged items and return the old-to-new handle table
+fn compact<T, H: Handle>(items: &mut Vec<T>, deleted: &[bool]) -> Vec<H> {
+    let live = |i: usize| !deleted.get(i).copied().unwrap_or(false);
+    let mut table = vec![H::new(INVALID); items.len()];
+    let mut next = 0;
+    for (i, slot) in table.iter_mut().enumerate() {
+        if live(i) {
+            *slot = H::new(next);
+            next += 1;
+        }
+    }
+    let mut i = 0;
+    items.retain(|_| {
+        let keep = live(i);
+        i += 1;
+        keep
+    });
+    table
+}
+
+/// New handle of `id` in a table built by [`compact`]
+fn lookup<H: Handle>(table: &[H], id: H) -> H {
+    if id.index() == INVALID { id } else { table[id.index()] }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::half_edge::{EdgeId, FaceId, Mesh, VertexId, INVALID};
+
+    /// Unit square split along the diagonal 0-2
+    fn square() -> Mesh<[f64; 2], (), ()> {
+        let positions = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        Mesh::from_vertices_and_faces(positions, &[3, 3], &[0, 1, 2, 0, 2, 3]).unwrap()
+    }
+
+    /// Vertex 4 at the centre of the unit square, joined to all corners
+    fn fan() -> Mesh<[f64; 2], (), ()> {
+        let positions = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.5, 0.5]];
+        let indices = [0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4];
+        Mesh::from_vertices_and_faces(positions, &[3; 4], &indices).unwrap()
+    }
+
+    fn find_edge<VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, a: usize, b: usize) -> Option<EdgeId> {
+        mesh.edge_ids().find(|&e| {
+            let [x, y] = mesh.edge_vertices(e);
+            (x == a && y == b) || (x == b && y == a)
+        })
+    }
+
+    #[test]
+    fn test_flip_edge() {
+        let mut mesh = square();
+        let e = find_edge(&mesh, 0, 2).unwrap();
+        assert!(!mesh.is_flip_ok(find_edge(&mesh, 0, 1).unwrap()));
+        mesh.flip_edge(e).unwrap();
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        let mut ends = mesh.edge_vertices(e);
+        ends.sort();
+        assert_eq!(ends, [1, 3]);
+        assert_eq!(mesh.faces.len(), 2);
+        assert!(mesh.face_ids().all(|f| mesh.face_degree(f) == 3));
+    }
+
+    #[test]
+    fn test_split_edge_and_face() {
+        let mut mesh = square();
+        let e = find_edge(&mesh, 0, 2).unwrap();
+        let v = mesh.split_edge(e, [0.5, 0.5]);
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert_eq!((mesh.vertices.len(), mesh.edges.len(), mesh.faces.len()), (5, 8, 4));
+        assert_eq!(mesh.vertex_valence(v), 4);
+        assert!(!mesh.is_boundary_vertex(v));
+
+        // splitting a boundary edge adds one triangle
+        let b = find_edge(&mesh, 0, 1).unwrap();
+        let w = mesh.split_edge(b, [0.5, 0.0]);
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert_eq!(mesh.faces.len(), 5);
+        assert!(mesh.is_boundary_vertex(w));
+        assert_eq!(mesh.vertex_valence(w), 3);
+
+        // a quad becomes four triangles
+        let mut quad = Mesh::<[f64; 2], (), ()>::from_vertices_and_faces(
+            vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]], &[4], &[0, 1, 2, 3],
+        ).unwrap();
+        let c = quad.split_face(FaceId::new(0), [0.5, 0.5]);
+        assert!(quad.is_valid(), "{:?}", quad.validate());
+        assert_eq!((quad.edges.len(), quad.faces.len()), (8, 4));
+        assert_eq!(quad.vertex_vertices(c).count(), 4);
+        assert!(quad.face_ids().all(|f| quad.face_degree(f) == 3));
+    }
+
+    #[test]
+    fn test_collapse_and_garbage_collect() {
+        let mut mesh = fan();
+        // merge the centre into corner 0
+        let centre = VertexId::new(4);
+        let he = mesh.vertex_outgoing_halfedges(centre).find(|&h| mesh.halfedge_to(h) == 0).unwrap();
+        assert!(mesh.is_collapse_ok(he));
+        let v = mesh.collapse_edge(he).unwrap();
+        assert_eq!(v, 0);
+        assert!(mesh.is_vertex_deleted(centre));
+        assert!(mesh.has_garbage());
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert_eq!(mesh.vertex_faces(v).count(), 2);
+
+        let remap = mesh.garbage_collect();
+        assert!(!mesh.has_garbage());
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert_eq!((mesh.vertices.len(), mesh.edges.len(), mesh.faces.len()), (4, 5, 2));
+        assert_eq!(remap.vertices, vec![0, 1, 2, 3, INVALID]);
+        assert_eq!(remap.faces.iter().filter(|f| !f.is_valid()).count(), 2);
+    }
+
+    #[test]
+    fn test_collapse_link_condition() {
+        // collapsing a tetrahedron edge would leave a doubled triangle
+        let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let mut tet = Mesh::<[f64; 3], (), ()>::from_vertices_and_faces(
+            positions, &[3; 4], &[0, 2, 1, 0, 1, 3, 1, 2, 3, 0, 3, 2],
+        ).unwrap();
+        assert!(tet.halfedge_ids().all(|h| !tet.is_collapse_ok(h)));
+        assert!(tet.collapse_edge(0.into()).is_err());
+
+        // the diagonal of a square joins two boundary vertices
+        let square = square();
+        let [h, t] = square.edge_halfedges(find_edge(&square, 0, 2).unwrap());
+        assert!(!square.is_collapse_ok(h) && !square.is_collapse_ok(t));
+    }
+}
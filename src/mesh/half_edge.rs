@@ -1,24 +1,39 @@
 //! Half-edge mesh data structure
 
-use crate::geometry::{area, vector::Vector3};
+use crate::error::{Error, Result};
+use crate::geometry::{area, real::Real, vector::Vector3};
+use crate::mesh::editing::Deleted;
+use crate::mesh::properties::Properties;
+use std::collections::HashMap;
 
-/// Newtype IDs for mesh entities
-pub type VertexId = usize;
-pub type HalfEdgeId = usize;
-pub type EdgeId = usize;
-pub type FaceId = usize;
+pub use crate::mesh::handles::{EdgeId, FaceId, HalfEdgeId, VertexId};
+
+/// Raw sentinel index for a missing element: the face of a boundary
+/// half-edge, or the outgoing half-edge of an isolated vertex. Each handle
+/// type wraps it as its `INVALID` constant.
+pub const INVALID: usize = usize::MAX;
 
 /// Generic half-edge mesh
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mesh<VA = (), EA = (), FA = ()> {
     pub vertices: Vec<Vertex<VA>>,
     pub halfedges: Vec<HalfEdge>,
     pub edges: Vec<Edge<EA>>,
     pub faces: Vec<Face<FA>>,
+    /// Elements removed by editing operations, until `garbage_collect`
+    pub(crate) deleted: Deleted,
+    /// Advanced by `garbage_collect` whenever it renumbers elements
+    pub(crate) generation: u32,
+    /// Named properties registered at runtime; not serialized, see
+    /// [`crate::io::binary`] for snapshots that keep them
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) properties: Properties,
 }
 
 /// Vertex with outgoing half-edge and attribute
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vertex<VA> {
     pub he_out: HalfEdgeId,
     pub attr: VA,
@@ -26,6 +41,7 @@ pub struct Vertex<VA> {
 
 /// Half-edge linking topology
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HalfEdge {
     pub from: VertexId,
     pub twin: HalfEdgeId,
@@ -36,6 +52,7 @@ pub struct HalfEdge {
 
 /// Edge with representative half-edge and attribute
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Edge<EA> {
     pub he: HalfEdgeId,
     pub attr: EA,
@@ -43,21 +60,13 @@ pub struct Edge<EA> {
 
 /// Face with one half-edge and attribute
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Face<FA> {
     pub he: HalfEdgeId,
     pub attr: FA,
 }
 
 impl<VA, EA, FA> Mesh<VA, EA, FA> {
-    /// Return the vertex indices of the face with the given face index (assumes triangle)
-    pub fn face_vertices(&self, f_id: usize) -> [usize; 3] {
-        let face = &self.faces[f_id];
-        let h0 = face.he;
-        let h1 = self.halfedges[h0].next;
-        let h2 = self.halfedges[h1].next;
-        [self.halfedges[h0].from, self.halfedges[h1].from, self.halfedges[h2].from]
-    }
-
     /// Create an empty mesh
     pub fn new() -> Self where VA: Default, EA: Default, FA: Default {
         Default::default()
@@ -65,76 +74,338 @@ impl<VA, EA, FA> Mesh<VA, EA, FA> {
     // For mesh types that do not implement Default, provide a custom constructor as needed.
 
 
-    /// Return the IDs of faces incident to vertex `vid`.
-    pub fn vertex_incident_faces(&self, vid: VertexId) -> Vec<FaceId> {
-        let mut faces = Vec::new();
-        let start = self.vertices[vid].he_out;
-        let mut he = start;
-        loop {
-            faces.push(self.halfedges[he].face);
-            he = self.halfedges[self.halfedges[he].twin].next;
-            if he == start { break; }
-        }
-        faces
-    }
-
-    /// Return the IDs of vertices adjacent to vertex `vid`.
-    pub fn vertex_neighbors(&self, vid: VertexId) -> Vec<VertexId> {
-        let mut nbrs = Vec::new();
-        let start = self.vertices[vid].he_out;
-        let mut he = start;
-        loop {
-            let next_he = self.halfedges[he].next;
-            nbrs.push(self.halfedges[next_he].from);
-            he = self.halfedges[self.halfedges[he].twin].next;
-            if he == start { break; }
-        }
-        nbrs
-    }
-
-    /// Return the IDs of edges incident to vertex `vid`.
-    pub fn vertex_incident_edges(&self, vid: VertexId) -> Vec<EdgeId> {
-        let mut edges = Vec::new();
-        let start = self.vertices[vid].he_out;
-        let mut he = start;
-        loop {
-            edges.push(self.halfedges[he].edge);
-            he = self.halfedges[self.halfedges[he].twin].next;
-            if he == start { break; }
+    /// Whether half-edge `he` lies on the boundary (has no incident face)
+    pub fn is_boundary_halfedge(&self, he: HalfEdgeId) -> bool {
+        self.halfedges[he].face == FaceId::INVALID
+    }
+
+    /// Whether edge `e` has a boundary half-edge on either side
+    pub fn is_boundary_edge(&self, e: EdgeId) -> bool {
+        let he = self.edges[e].he;
+        self.is_boundary_halfedge(he) || self.is_boundary_halfedge(self.halfedges[he].twin)
+    }
+
+    /// Whether vertex `vid` is isolated or has an outgoing boundary half-edge
+    pub fn is_boundary_vertex(&self, vid: VertexId) -> bool {
+        let start = self.ring_start(vid);
+        start == HalfEdgeId::INVALID || self.is_boundary_halfedge(start)
+    }
+
+    /// Whether face `fid` has at least one edge on the boundary
+    pub fn is_boundary_face(&self, fid: FaceId) -> bool {
+        self.face_halfedges(fid).any(|he| self.is_boundary_halfedge(self.halfedges[he].twin))
+    }
+
+    /// Return every boundary loop as the ordered cycle of its vertices.
+    ///
+    /// Loops follow the boundary half-edges, i.e. they run clockwise when
+    /// faces are oriented counter-clockwise.
+    pub fn boundary_loops(&self) -> Vec<Vec<VertexId>> {
+        self.boundary_halfedge_loops()
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(|he| self.halfedges[he].from).collect())
+            .collect()
+    }
+
+    /// Same as [`Mesh::boundary_loops`], as the cycles of boundary
+    /// half-edges; the `k`-th half-edge leaves the `k`-th vertex
+    pub fn boundary_halfedge_loops(&self) -> Vec<Vec<HalfEdgeId>> {
+        let mut visited = vec![false; self.halfedges.len()];
+        let mut loops = Vec::new();
+        for start in self.halfedge_ids() {
+            if visited[start.index()] || !self.is_boundary_halfedge(start) { continue; }
+            let mut cycle = Vec::new();
+            let mut he = start;
+            while !visited[he.index()] {
+                visited[he.index()] = true;
+                cycle.push(he);
+                he = self.halfedges[he].next;
+            }
+            loops.push(cycle);
         }
-        edges
+        loops
     }
 }
 
-/// 3D mesh with Vector3 attributes
-pub type Mesh3D<EA, FA> = Mesh<Vector3, EA, FA>;
+/// 3D mesh with Vector3 attributes, in `f64` unless another [`Real`] is given
+pub type Mesh3D<EA, FA, T = f64> = Mesh<Vector3<T>, EA, FA>;
 
-impl<EA, FA> Mesh<Vector3, EA, FA> {
+impl<EA, FA, T: Real> Mesh<Vector3<T>, EA, FA> {
     /// Compute per-vertex barycentric area (sum of one-third of each incident face)
-    pub fn vertex_areas(&self) -> Vec<f64> {
+    ///
+    /// Polygons are split into a fan of triangles around their first vertex.
+    pub fn vertex_areas(&self) -> Vec<T> {
         let n = self.vertices.len();
-        let mut areas = vec![0.0; n];
-        for face in &self.faces {
-            let h0 = face.he;
-            let h1 = self.halfedges[h0].next;
-            let h2 = self.halfedges[h1].next;
-            let vs = [
-                self.halfedges[h0].from,
-                self.halfedges[h1].from,
-                self.halfedges[h2].from,
-            ];
-            let pa = self.vertices[vs[0]].attr;
-            let pb = self.vertices[vs[1]].attr;
-            let pc = self.vertices[vs[2]].attr;
-            let ba = area::mixed_area(&pa, &pb, &pc);
-            for (i, &vi) in vs.iter().enumerate() {
-                areas[vi] += ba[i];
+        let mut areas = vec![T::ZERO; n];
+        for f in self.face_ids() {
+            let vs: Vec<VertexId> = self.face_vertices(f).collect();
+            for k in 1..vs.len() - 1 {
+                let tri = [vs[0], vs[k], vs[k + 1]];
+                let pa = self.vertices[tri[0]].attr;
+                let pb = self.vertices[tri[1]].attr;
+                let pc = self.vertices[tri[2]].attr;
+                let ba = area::mixed_area(&pa, &pb, &pc);
+                for (i, &vi) in tri.iter().enumerate() {
+                    areas[vi.index()] += ba[i];
+                }
             }
         }
         areas
     }
 }
 
+impl<VA, EA: Default, FA: Default> Mesh<VA, EA, FA> {
+    /// Build a mesh from per-vertex attributes and a polygon soup given as
+    /// face-vertex counts and flattened face-vertex indices.
+    pub fn from_vertices_and_faces(
+        vertices: Vec<VA>,
+        face_counts: &[usize],
+        face_indices: &[usize],
+    ) -> Result<Self> {
+        MeshBuilder::new(vertices, face_counts, face_indices).build()
+    }
+}
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Number of vertices of every face, the inverse of the `face_counts`
+    /// of [`Mesh::from_vertices_and_faces`]. Deleted faces are skipped.
+    pub fn face_vertex_counts(&self) -> Vec<usize> {
+        self.face_ids().map(|f| self.face_degree(f)).collect()
+    }
+
+    /// Vertex indices of every face in winding order, flattened; the inverse
+    /// of the `face_indices` of [`Mesh::from_vertices_and_faces`].
+    pub fn face_vertex_indices(&self) -> Vec<usize> {
+        self.face_ids().flat_map(|f| self.face_vertices(f)).map(|v| v.index()).collect()
+    }
+
+    /// Convert every vertex attribute with `f`, keeping connectivity, edge
+    /// and face attributes and properties.
+    pub fn map_vertex_attrs<VB>(self, mut f: impl FnMut(VA) -> VB) -> Mesh<VB, EA, FA> {
+        Mesh {
+            vertices: self.vertices.into_iter().map(|v| Vertex { he_out: v.he_out, attr: f(v.attr) }).collect(),
+            halfedges: self.halfedges,
+            edges: self.edges,
+            faces: self.faces,
+            deleted: self.deleted,
+            generation: self.generation,
+            properties: self.properties,
+        }
+    }
+}
+
+/// Positions as loaded by the readers in [`crate::io`]
+impl<EA, FA, T: Real> From<Mesh<[T; 3], EA, FA>> for Mesh3D<EA, FA, T> {
+    fn from(mesh: Mesh<[T; 3], EA, FA>) -> Self {
+        mesh.map_vertex_attrs(Vector3::from)
+    }
+}
+
+impl<EA, FA, T: Real> From<Mesh3D<EA, FA, T>> for Mesh<[T; 3], EA, FA> {
+    fn from(mesh: Mesh3D<EA, FA, T>) -> Self {
+        mesh.map_vertex_attrs(|p| p.to_array())
+    }
+}
+
+impl<EA, FA, T: Real> Mesh3D<EA, FA, T> {
+    /// Convert positions to another precision
+    pub fn cast<U: Real>(self) -> Mesh3D<EA, FA, U> {
+        self.map_vertex_attrs(|p| p.cast())
+    }
+}
+
+/// Builds half-edge connectivity from a polygon soup.
+///
+/// Faces may be arbitrary n-gons. Every edge with a single incident face gets
+/// an explicit boundary half-edge whose face is [`INVALID`]; boundary
+/// half-edges are linked by `next` into closed loops, and boundary vertices
+/// store their outgoing boundary half-edge in `he_out`.
+#[derive(Debug)]
+pub struct MeshBuilder<VA> {
+    vertices: Vec<VA>,
+    face_counts: Vec<usize>,
+    face_indices: Vec<usize>,
+}
+
+impl<VA> Default for MeshBuilder<VA> {
+    fn default() -> Self {
+        MeshBuilder { vertices: Vec::new(), face_counts: Vec::new(), face_indices: Vec::new() }
+    }
+}
+
+impl<VA> MeshBuilder<VA> {
+    /// Create a builder from vertex attributes, face-vertex counts and indices
+    pub fn new(vertices: Vec<VA>, face_counts: &[usize], face_indices: &[usize]) -> Self {
+        MeshBuilder {
+            vertices,
+            face_counts: face_counts.to_vec(),
+            face_indices: face_indices.to_vec(),
+        }
+    }
+
+    /// Append a vertex and return its ID
+    pub fn add_vertex(&mut self, attr: VA) -> VertexId {
+        self.vertices.push(attr);
+        VertexId::new(self.vertices.len() - 1)
+    }
+
+    /// Append a polygonal face given by its vertex loop and return its ID
+    pub fn add_face(&mut self, vertices: &[VertexId]) -> FaceId {
+        self.face_counts.push(vertices.len());
+        self.face_indices.extend(vertices.iter().map(|v| v.index()));
+        FaceId::new(self.face_counts.len() - 1)
+    }
+
+    /// Construct the half-edge mesh.
+    ///
+    /// Returns `Error::Topology` for faces with fewer than three or repeated
+    /// vertices, out-of-range indices, edges shared by more than two faces (or
+    /// by two inconsistently oriented faces), and non-manifold vertices.
+    pub fn build<EA: Default, FA: Default>(self) -> Result<Mesh<VA, EA, FA>> {
+        let nv = self.vertices.len();
+        let total: usize = self.face_counts.iter().sum();
+        if total != self.face_indices.len() {
+            return Err(Error::Topology(format!(
+                "face counts sum to {} but {} face indices were given",
+                total,
+                self.face_indices.len()
+            )));
+        }
+
+        let mut mesh = Mesh {
+            vertices: self.vertices.into_iter().map(|attr| Vertex { he_out: HalfEdgeId::INVALID, attr }).collect(),
+            halfedges: Vec::with_capacity(2 * total),
+            edges: Vec::new(),
+            faces: Vec::with_capacity(self.face_counts.len()),
+            deleted: Deleted::default(),
+            generation: 0,
+            properties: Properties::default(),
+        };
+
+        // Interior half-edges, one loop per face
+        let mut directed: HashMap<(usize, usize), usize> = HashMap::with_capacity(total);
+        let mut offset = 0;
+        for (f_id, &count) in self.face_counts.iter().enumerate() {
+            let inds = &self.face_indices[offset..offset + count];
+            offset += count;
+            if count < 3 {
+                return Err(Error::Topology(format!("face {} has only {} vertices", f_id, count)));
+            }
+            for (k, &v) in inds.iter().enumerate() {
+                if v >= nv {
+                    return Err(Error::Topology(format!(
+                        "face {} references vertex {} but the mesh has {} vertices",
+                        f_id, v, nv
+                    )));
+                }
+                if inds[..k].contains(&v) {
+                    return Err(Error::Topology(format!("face {} repeats vertex {}", f_id, v)));
+                }
+            }
+            let hb = mesh.halfedges.len();
+            for k in 0..count {
+                let from = inds[k];
+                let to = inds[(k + 1) % count];
+                if directed.insert((from, to), hb + k).is_some() {
+                    return Err(Error::Topology(format!(
+                        "non-manifold edge ({}, {}): shared by more than two faces or by inconsistently oriented faces",
+                        from, to
+                    )));
+                }
+                mesh.halfedges.push(HalfEdge {
+                    from: VertexId::new(from),
+                    twin: HalfEdgeId::INVALID,
+                    next: HalfEdgeId::new(hb + (k + 1) % count),
+                    edge: EdgeId::INVALID,
+                    face: FaceId::new(f_id),
+                });
+            }
+            mesh.faces.push(Face { he: HalfEdgeId::new(hb), attr: FA::default() });
+        }
+
+        // Twins and edges; unmatched half-edges get a boundary twin
+        let n_interior = mesh.halfedges.len();
+        let mut boundary_out = vec![HalfEdgeId::INVALID; nv];
+        for h in (0..n_interior).map(HalfEdgeId::new) {
+            if mesh.halfedges[h].twin.is_valid() {
+                continue;
+            }
+            let from = mesh.halfedges[h].from.index();
+            let to = mesh.halfedges[mesh.halfedges[h].next].from.index();
+            let e_id = EdgeId::new(mesh.edges.len());
+            mesh.edges.push(Edge { he: h, attr: EA::default() });
+            let twin = match directed.get(&(to, from)) {
+                Some(&opp) => HalfEdgeId::new(opp),
+                None => {
+                    if boundary_out[to] != HalfEdgeId::INVALID {
+                        return Err(Error::Topology(format!(
+                            "non-manifold vertex {}: more than one boundary gap in its one-ring",
+                            to
+                        )));
+                    }
+                    let b = HalfEdgeId::new(mesh.halfedges.len());
+                    boundary_out[to] = b;
+                    mesh.halfedges.push(HalfEdge {
+                        from: VertexId::new(to),
+                        twin: HalfEdgeId::INVALID,
+                        next: HalfEdgeId::INVALID,
+                        edge: e_id,
+                        face: FaceId::INVALID,
+                    });
+                    b
+                }
+            };
+            mesh.halfedges[h].twin = twin;
+            mesh.halfedges[h].edge = e_id;
+            mesh.halfedges[twin].twin = h;
+            mesh.halfedges[twin].edge = e_id;
+        }
+
+        // Close boundary loops: a boundary half-edge b -> a continues from a
+        for b in (n_interior..mesh.halfedges.len()).map(HalfEdgeId::new) {
+            let to = mesh.halfedges[mesh.halfedges[b].twin].from;
+            mesh.halfedges[b].next = boundary_out[to.index()];
+        }
+
+        // Outgoing half-edges, preferring the boundary one so circulation
+        // around a boundary vertex starts at the gap
+        let mut valence = vec![0usize; nv];
+        for (h, he) in mesh.halfedges.iter().enumerate() {
+            valence[he.from.index()] += 1;
+            if !mesh.vertices[he.from].he_out.is_valid() {
+                mesh.vertices[he.from].he_out = HalfEdgeId::new(h);
+            }
+        }
+        for (vertex, &b) in mesh.vertices.iter_mut().zip(&boundary_out) {
+            if b.is_valid() {
+                vertex.he_out = b;
+            }
+        }
+
+        // A manifold vertex is reached by a single one-ring circulation
+        for (v, vertex) in mesh.vertices.iter().enumerate() {
+            if !vertex.he_out.is_valid() {
+                continue;
+            }
+            let mut count = 0;
+            let mut he = vertex.he_out;
+            loop {
+                count += 1;
+                he = mesh.halfedges[mesh.halfedges[he].twin].next;
+                if he == vertex.he_out { break; }
+            }
+            if count != valence[v] {
+                return Err(Error::Topology(format!(
+                    "non-manifold vertex {}: its incident faces form more than one fan",
+                    v
+                )));
+            }
+        }
+
+        Ok(mesh)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,21 +416,21 @@ mod tests {
         // Single triangle (0,0,0), (1,0,0), (0,1,0)
         let mut mesh = Mesh3D::<(),()>::new();
         mesh.vertices = vec![
-            Vertex { he_out: 0, attr: Vector3::new(0.0,0.0,0.0) },
-            Vertex { he_out: 1, attr: Vector3::new(1.0,0.0,0.0) },
-            Vertex { he_out: 2, attr: Vector3::new(0.0,1.0,0.0) },
+            Vertex { he_out: 0.into(), attr: Vector3::new(0.0,0.0,0.0) },
+            Vertex { he_out: 1.into(), attr: Vector3::new(1.0,0.0,0.0) },
+            Vertex { he_out: 2.into(), attr: Vector3::new(0.0,1.0,0.0) },
         ];
         mesh.halfedges = vec![
-            HalfEdge { from:0, twin:0, next:1, edge:0, face:0 },
-            HalfEdge { from:1, twin:1, next:2, edge:1, face:0 },
-            HalfEdge { from:2, twin:2, next:0, edge:2, face:0 },
+            HalfEdge { from:0.into(), twin:0.into(), next:1.into(), edge:0.into(), face:0.into() },
+            HalfEdge { from:1.into(), twin:1.into(), next:2.into(), edge:1.into(), face:0.into() },
+            HalfEdge { from:2.into(), twin:2.into(), next:0.into(), edge:2.into(), face:0.into() },
         ];
         mesh.edges = vec![
-            Edge { he:0, attr:() },
-            Edge { he:1, attr:() },
-            Edge { he:2, attr:() },
+            Edge { he:0.into(), attr:() },
+            Edge { he:1.into(), attr:() },
+            Edge { he:2.into(), attr:() },
         ];
-        mesh.faces = vec![ Face { he:0, attr:() } ];
+        mesh.faces = vec![ Face { he:0.into(), attr:() } ];
 
         let areas = mesh.vertex_areas();
         // Mixed area for right triangle: angles at vertex 0 is 90°, areas = [0.25, 0.125, 0.125]
@@ -173,27 +444,160 @@ mod tests {
         // Reuse same mesh
         let mut mesh = Mesh3D::<(),()>::new();
         mesh.vertices = vec![
-            Vertex { he_out: 0, attr: Vector3::zero() },
-            Vertex { he_out: 1, attr: Vector3::zero() },
-            Vertex { he_out: 2, attr: Vector3::zero() },
+            Vertex { he_out: 0.into(), attr: Vector3::zero() },
+            Vertex { he_out: 1.into(), attr: Vector3::zero() },
+            Vertex { he_out: 2.into(), attr: Vector3::zero() },
         ];
         mesh.halfedges = vec![
-            HalfEdge { from:0, twin:0, next:1, edge:0, face:0 },
-            HalfEdge { from:1, twin:1, next:2, edge:1, face:0 },
-            HalfEdge { from:2, twin:2, next:0, edge:2, face:0 },
+            HalfEdge { from:0.into(), twin:0.into(), next:1.into(), edge:0.into(), face:0.into() },
+            HalfEdge { from:1.into(), twin:1.into(), next:2.into(), edge:1.into(), face:0.into() },
+            HalfEdge { from:2.into(), twin:2.into(), next:0.into(), edge:2.into(), face:0.into() },
         ];
         mesh.edges = vec![
-            Edge { he:0, attr:() },
-            Edge { he:1, attr:() },
-            Edge { he:2, attr:() },
+            Edge { he:0.into(), attr:() },
+            Edge { he:1.into(), attr:() },
+            Edge { he:2.into(), attr:() },
         ];
-        mesh.faces = vec![ Face { he:0, attr:() } ];
+        mesh.faces = vec![ Face { he:0.into(), attr:() } ];
 
         // Incident faces for vertex 0: face 0 thrice
-        assert_eq!(mesh.vertex_incident_faces(0), vec![0,0,0]);
+        let v0 = VertexId::new(0);
+        assert_eq!(mesh.vertex_incident_faces(v0).collect::<Vec<_>>(), vec![0,0,0]);
         // Neighbors for vertex 0: 1,2,0
-        assert_eq!(mesh.vertex_neighbors(0), vec![1,2,0]);
+        assert_eq!(mesh.vertex_neighbors(v0).collect::<Vec<_>>(), vec![1,2,0]);
         // Incident edges for vertex 0: 0,1,2
-        assert_eq!(mesh.vertex_incident_edges(0), vec![0,1,2]);
+        assert_eq!(mesh.vertex_incident_edges(v0).collect::<Vec<_>>(), vec![0,1,2]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_builder_single_triangle_boundary() {
+        let positions = vec![
+            Vector3::new(0.0,0.0,0.0),
+            Vector3::new(1.0,0.0,0.0),
+            Vector3::new(0.0,1.0,0.0),
+        ];
+        let mesh = Mesh3D::<(),()>::from_vertices_and_faces(positions, &[3], &[0,1,2]).unwrap();
+        assert_eq!(mesh.faces.len(), 1);
+        assert_eq!(mesh.edges.len(), 3);
+        assert_eq!(mesh.halfedges.len(), 6);
+        for h in mesh.halfedge_ids() {
+            let he = &mesh.halfedges[h];
+            assert_eq!(mesh.halfedges[he.twin].twin, h);
+            assert_eq!(mesh.halfedges[he.twin].edge, he.edge);
+        }
+        // Boundary half-edges form a loop running opposite to the face
+        let b0 = mesh.vertices[0].he_out;
+        assert_eq!(mesh.halfedges[b0].face, FaceId::INVALID);
+        let b1 = mesh.halfedges[b0].next;
+        let b2 = mesh.halfedges[b1].next;
+        assert_eq!(mesh.halfedges[b2].next, b0);
+        assert_eq!([mesh.halfedges[b0].from, mesh.halfedges[b1].from, mesh.halfedges[b2].from], [0, 2, 1]);
+        assert_eq!(mesh.face_vertices(FaceId::new(0)).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_builder_polygons_and_closed_surface() {
+        // Quad split off a triangle: shared edge (1,2)
+        let mut builder = MeshBuilder::default();
+        let v: Vec<VertexId> = [[0.0,0.0,0.0], [1.0,0.0,0.0], [1.0,1.0,0.0], [0.0,1.0,0.0], [2.0,0.5,0.0]]
+            .into_iter()
+            .map(|p| builder.add_vertex(Vector3::from_array(p)))
+            .collect();
+        builder.add_face(&[v[0], v[1], v[2], v[3]]);
+        assert_eq!(builder.add_face(&[v[1], v[4], v[2]]), 1);
+        let mesh: Mesh3D<(),()> = builder.build().unwrap();
+        assert_eq!(mesh.edges.len(), 6);
+        assert_eq!(mesh.halfedges.iter().filter(|he| !he.face.is_valid()).count(), 5);
+
+        // Tetrahedron has no boundary half-edges
+        let tet = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 4],
+            &[3,3,3,3],
+            &[0,2,1, 0,1,3, 1,2,3, 2,0,3],
+        ).unwrap();
+        assert_eq!(tet.edges.len(), 6);
+        assert!(tet.halfedges.iter().all(|he| he.face.is_valid()));
+        assert_eq!(tet.vertex_neighbors(VertexId::new(0)).count(), 3);
+    }
+
+    #[test]
+    fn test_builder_rejects_non_manifold() {
+        // Three triangles on edge (0,1)
+        let fin = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 5],
+            &[3,3,3],
+            &[0,1,2, 1,0,3, 0,1,4],
+        );
+        assert!(matches!(fin, Err(Error::Topology(_))));
+        // Bow-tie: two triangles touching at vertex 0
+        let bowtie = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 5],
+            &[3,3],
+            &[0,1,2, 0,3,4],
+        );
+        assert!(matches!(bowtie, Err(Error::Topology(_))));
+        // Out-of-range index and count mismatch
+        assert!(Mesh3D::<(),()>::from_vertices_and_faces(vec![Vector3::zero(); 3], &[3], &[0,1,3]).is_err());
+        assert!(Mesh3D::<(),()>::from_vertices_and_faces(vec![Vector3::zero(); 3], &[4], &[0,1,2]).is_err());
+    }
+
+    #[test]
+    fn test_boundary_queries_open_square() {
+        // Unit square split along the diagonal (0,2)
+        let positions = vec![
+            Vector3::new(0.0,0.0,0.0),
+            Vector3::new(1.0,0.0,0.0),
+            Vector3::new(1.0,1.0,0.0),
+            Vector3::new(0.0,1.0,0.0),
+        ];
+        let mesh = Mesh3D::<(),()>::from_vertices_and_faces(positions, &[3,3], &[0,1,2, 0,2,3]).unwrap();
+        assert!(mesh.vertex_ids().all(|v| mesh.is_boundary_vertex(v)));
+        assert!(mesh.face_ids().all(|f| mesh.is_boundary_face(f)));
+        let diagonal = mesh.edge_ids().filter(|&e| !mesh.is_boundary_edge(e)).collect::<Vec<_>>();
+        assert_eq!(diagonal.len(), 1);
+        assert_eq!(mesh.halfedge_ids().filter(|&h| mesh.is_boundary_halfedge(h)).count(), 4);
+
+        let loops = mesh.boundary_loops();
+        assert_eq!(loops, vec![vec![1, 0, 3, 2]]);
+        let halfedges = &mesh.boundary_halfedge_loops()[0];
+        assert!(halfedges.iter().all(|&h| mesh.is_boundary_halfedge(h)));
+        assert!(halfedges.iter().zip(&loops[0]).all(|(&h, &v)| mesh.halfedges[h].from == v));
+
+        // One-ring of vertex 0 starts at its outgoing boundary half-edge
+        let v0 = VertexId::new(0);
+        let first = mesh.vertex_outgoing_halfedges(v0).next().unwrap();
+        assert!(mesh.is_boundary_halfedge(first));
+        assert_eq!(mesh.vertex_neighbors(v0).collect::<Vec<_>>(), vec![3, 2, 1]);
+        assert_eq!(mesh.vertex_incident_faces(v0).collect::<Vec<_>>(), vec![1, 0]);
+        assert_eq!(mesh.vertex_incident_edges(v0).count(), 3);
+    }
+
+    #[test]
+    fn test_boundary_queries_closed_and_isolated() {
+        let tet = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 5],
+            &[3,3,3,3],
+            &[0,2,1, 0,1,3, 1,2,3, 2,0,3],
+        ).unwrap();
+        assert!(tet.boundary_loops().is_empty());
+        assert!(tet.vertex_ids().take(4).all(|v| !tet.is_boundary_vertex(v)));
+        assert!(tet.face_ids().all(|f| !tet.is_boundary_face(f)));
+        // Vertex 4 is not referenced by any face
+        let v4 = VertexId::new(4);
+        assert!(tet.is_boundary_vertex(v4));
+        assert!(tet.vertex_neighbors(v4).next().is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let positions = vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)];
+        let mesh: Mesh3D<(), u8> = Mesh::from_vertices_and_faces(positions, &[3], &[0, 1, 2]).unwrap();
+        let json = serde_json::to_string(&mesh).unwrap();
+        let loaded: Mesh3D<(), u8> = serde_json::from_str(&json).unwrap();
+        assert!(loaded.is_valid());
+        assert_eq!(loaded.vertices[1].attr, Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(loaded.halfedges[0].twin, mesh.halfedges[0].twin);
+        assert_eq!(loaded.face_vertex_indices(), vec![0, 1, 2]);
+    }
+}
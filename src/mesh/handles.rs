@@ -0,0 +1,245 @@
+//! Type-safe element handles
+//!
+//! Every element kind has its own index newtype, so a face id cannot be
+//! passed where a vertex id is expected. Handles index the element arrays of
+//! a [`Mesh`] directly and convert to and from `usize` for raw interop.
+//! [`Generational`] handles additionally remember the mesh generation they
+//! were taken in, which changes whenever `garbage_collect` renumbers ids.
+
+use crate::error::{Error, Result};
+use crate::mesh::half_edge::{Edge, Face, HalfEdge, Mesh, Vertex, INVALID};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Common interface of the element handles
+pub trait Handle: Copy + Eq + fmt::Debug + fmt::Display {
+    /// Element kind, used in diagnostics
+    const KIND: &'static str;
+
+    /// Handle for the element at `index`
+    fn new(index: usize) -> Self;
+
+    /// Raw array index
+    fn index(self) -> usize;
+
+    /// Number of element slots of this kind in `mesh`, deleted ones included
+    fn count<VA, EA, FA>(mesh: &Mesh<VA, EA, FA>) -> usize;
+
+    /// Whether the element was removed from `mesh` by an editing operation
+    fn is_deleted<VA, EA, FA>(self, mesh: &Mesh<VA, EA, FA>) -> bool;
+}
+
+/// Defines a handle newtype and indexing of its element array
+macro_rules! handle {
+    ($(#[$doc:meta])* $name:ident, $kind:literal, $elements:ident, $deleted:ident, $element:ty $(, $param:ident)?) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+        pub struct $name(usize);
+
+        impl $name {
+            /// Handle of no element
+            pub const INVALID: Self = $name(INVALID);
+
+            /// Handle for the element at `index`
+            pub const fn new(index: usize) -> Self {
+                $name(index)
+            }
+
+            /// Raw array index
+            pub const fn index(self) -> usize {
+                self.0
+            }
+
+            /// Whether this is not [`Self::INVALID`]
+            pub const fn is_valid(self) -> bool {
+                self.0 != INVALID
+            }
+        }
+
+        impl Handle for $name {
+            const KIND: &'static str = $kind;
+
+            fn new(index: usize) -> Self {
+                $name(index)
+            }
+
+            fn index(self) -> usize {
+                self.0
+            }
+
+            fn count<VA, EA, FA>(mesh: &Mesh<VA, EA, FA>) -> usize {
+                mesh.$elements.len()
+            }
+
+            fn is_deleted<VA, EA, FA>(self, mesh: &Mesh<VA, EA, FA>) -> bool {
+                mesh.$deleted(self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl From<usize> for $name {
+            fn from(index: usize) -> Self {
+                $name(index)
+            }
+        }
+
+        impl From<$name> for usize {
+            fn from(handle: $name) -> usize {
+                handle.0
+            }
+        }
+
+        impl PartialEq<usize> for $name {
+            fn eq(&self, other: &usize) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl$(<$param>)? Index<$name> for Vec<$element> {
+            type Output = $element;
+
+            fn index(&self, handle: $name) -> &$element {
+                &self[handle.0]
+            }
+        }
+
+        impl$(<$param>)? IndexMut<$name> for Vec<$element> {
+            fn index_mut(&mut self, handle: $name) -> &mut $element {
+                &mut self[handle.0]
+            }
+        }
+    };
+}
+
+handle!(
+    /// Handle of a vertex
+    VertexId, "vertex", vertices, is_vertex_deleted, Vertex<VA>, VA
+);
+handle!(
+    /// Handle of a half-edge
+    HalfEdgeId, "half-edge", halfedges, is_halfedge_deleted, HalfEdge
+);
+handle!(
+    /// Handle of an (undirected) edge
+    EdgeId, "edge", edges, is_edge_deleted, Edge<EA>, EA
+);
+handle!(
+    /// Handle of a face
+    FaceId, "face", faces, is_face_deleted, Face<FA>, FA
+);
+
+/// A handle tagged with the mesh generation it was taken in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Generational<H> {
+    pub handle: H,
+    pub generation: u32,
+}
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Current generation; it advances whenever `garbage_collect` renumbers
+    /// elements.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Tag `handle` with the current generation
+    pub fn generational<H: Handle>(&self, handle: H) -> Generational<H> {
+        Generational { handle, generation: self.generation }
+    }
+
+    /// Return the handle if it still refers to the same live element, or
+    /// `Error::Topology` if it was deleted or ids have since been renumbered.
+    pub fn resolve<H: Handle>(&self, handle: Generational<H>) -> Result<H> {
+        let Generational { handle, generation } = handle;
+        if generation != self.generation {
+            return Err(Error::Topology(format!(
+                "stale {} handle {}: taken in generation {}, mesh is at generation {}",
+                H::KIND, handle, generation, self.generation
+            )));
+        }
+        if handle.index() >= H::count(self) || handle.is_deleted(self) {
+            return Err(Error::Topology(format!("stale {} handle {}: element no longer exists", H::KIND, handle)));
+        }
+        Ok(handle)
+    }
+
+    /// Handles of all vertices not marked as deleted
+    pub fn vertex_ids(&self) -> impl Iterator<Item = VertexId> + '_ {
+        (0..self.vertices.len()).map(VertexId).filter(|&v| !self.is_vertex_deleted(v))
+    }
+
+    /// Handles of all half-edges not marked as deleted
+    pub fn halfedge_ids(&self) -> impl Iterator<Item = HalfEdgeId> + '_ {
+        (0..self.halfedges.len()).map(HalfEdgeId).filter(|&h| !self.is_halfedge_deleted(h))
+    }
+
+    /// Handles of all edges not marked as deleted
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeId> + '_ {
+        (0..self.edges.len()).map(EdgeId).filter(|&e| !self.is_edge_deleted(e))
+    }
+
+    /// Handles of all faces not marked as deleted
+    pub fn face_ids(&self) -> impl Iterator<Item = FaceId> + '_ {
+        (0..self.faces.len()).map(FaceId).filter(|&f| !self.is_face_deleted(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vertex 4 at the centre of the unit square, joined to all corners
+    fn fan() -> Mesh<[f64; 2], (), ()> {
+        let positions = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.5, 0.5]];
+        let indices = [0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4];
+        Mesh::from_vertices_and_faces(positions, &[3; 4], &indices).unwrap()
+    }
+
+    #[test]
+    fn test_handle_conversions_and_indexing() {
+        let mesh = fan();
+        let v = VertexId::new(4);
+        assert_eq!(v.index(), 4);
+        assert_eq!(usize::from(v), 4);
+        assert_eq!(VertexId::from(4), v);
+        assert_eq!(v.to_string(), "4");
+        assert!(!VertexId::INVALID.is_valid());
+        assert_eq!(mesh.vertices[v].attr, [0.5, 0.5]);
+        let he = mesh.vertices[v].he_out;
+        assert_eq!(mesh.halfedges[he].from, v);
+        assert_eq!(mesh.vertex_ids().count(), 5);
+        assert_eq!(mesh.face_ids().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_generational_handles_detect_stale_ids() {
+        let mut mesh = fan();
+        let corner = mesh.generational(VertexId::new(3));
+        let centre = mesh.generational(VertexId::new(4));
+        assert_eq!(mesh.resolve(corner).unwrap(), 3);
+
+        let he = mesh.vertex_outgoing_halfedges(VertexId::new(4)).find(|&h| mesh.halfedge_to(h) == 0).unwrap();
+        mesh.collapse_edge(he).unwrap();
+        // deletion is detected before ids change
+        assert!(matches!(mesh.resolve(centre), Err(Error::Topology(_))));
+        assert_eq!(mesh.resolve(corner).unwrap(), 3);
+
+        mesh.garbage_collect();
+        assert_eq!(mesh.generation(), 1);
+        assert!(mesh.resolve(corner).is_err());
+        let fresh = mesh.generational(VertexId::new(3));
+        assert_eq!(mesh.resolve(fresh).unwrap(), 3);
+        assert!(mesh.resolve(mesh.generational(VertexId::new(4))).is_err());
+
+        // nothing to collect: ids stay valid
+        mesh.garbage_collect();
+        assert!(mesh.resolve(fresh).is_ok());
+    }
+}
@@ -0,0 +1,418 @@
+//! Intrinsic triangulations with signpost angles
+//!
+//! The cotangent weights of an extrinsic triangulation turn negative on
+//! obtuse triangles, which breaks the maximum principle of the Laplacian. An
+//! [`IntrinsicTriangulation`] keeps the vertices and the surface of an input
+//! triangle mesh but replaces its edges by geodesics, described only by
+//! their lengths and by the direction ("signpost") in which each one leaves
+//! its tail vertex. Intrinsic edge flips then reach an intrinsic Delaunay
+//! triangulation, whose cotangent weights are all non-negative.
+//!
+//! Signpost angles are measured clockwise (in the order of
+//! [`Mesh::vertex_outgoing_halfedges`]) from the first outgoing half-edge of
+//! each vertex in the input mesh, and rescaled so that they sum to `2π`
+//! around interior vertices and `π` around boundary vertices.
+
+use crate::error::{Error, Result};
+use crate::geometry::area::{angle_from_lengths, triangle_from_lengths};
+use crate::geometry::metric::{EdgeLengths, Geometry};
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::{EdgeId, HalfEdgeId, Mesh, Mesh3D, VertexId};
+use crate::operators::assembly::cotan_stiffness;
+use sprs::CsMat;
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+
+/// Intrinsic triangulation of the surface of an input triangle mesh
+pub struct IntrinsicTriangulation<'a> {
+    /// The input mesh, whose surface the intrinsic edges run on
+    pub input: &'a Mesh3D<(), ()>,
+    /// Intrinsic connectivity on the vertices of the input mesh
+    pub mesh: Mesh,
+    /// Length of every intrinsic edge, a [`Geometry`] for `mesh`
+    pub edge_lengths: EdgeLengths,
+    /// Rescaled direction of every intrinsic half-edge at its tail vertex
+    pub signposts: Vec<f64>,
+    /// Signposts of the input half-edges
+    input_signposts: Vec<f64>,
+    /// Factor from actual to rescaled angles at every vertex
+    angle_scales: Vec<f64>,
+}
+
+/// Point on the input surface
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfacePoint {
+    /// An input vertex
+    Vertex(VertexId),
+    /// The point at parameter `t` from the tail to the head of an input
+    /// half-edge
+    Edge { halfedge: HalfEdgeId, t: f64 },
+}
+
+impl SurfacePoint {
+    /// Position of the point on `mesh`
+    pub fn position(&self, mesh: &Mesh3D<(), ()>) -> Vector3 {
+        match *self {
+            SurfacePoint::Vertex(v) => mesh.vertices[v].attr,
+            SurfacePoint::Edge { halfedge, t } => {
+                let a = mesh.vertices[mesh.halfedges[halfedge].from].attr;
+                let b = mesh.vertices[mesh.halfedge_to(halfedge)].attr;
+                a * (1.0 - t) + b * t
+            }
+        }
+    }
+}
+
+impl<'a> IntrinsicTriangulation<'a> {
+    /// Start from the input triangulation itself. Returns `Error::Topology`
+    /// if the input has deleted elements or faces other than triangles.
+    pub fn new(input: &'a Mesh3D<(), ()>) -> Result<Self> {
+        if input.face_ids().count() != input.faces.len() || input.vertex_ids().count() != input.vertices.len() {
+            return Err(Error::Topology("intrinsic triangulation of a mesh with deleted elements".into()));
+        }
+        if input.face_ids().any(|f| input.face_degree(f) != 3) {
+            return Err(Error::Topology("intrinsic triangulation of a mesh with non-triangular faces".into()));
+        }
+        let length = |h: HalfEdgeId| (input.vertices[input.halfedge_to(h)].attr - input.vertices[input.halfedges[h].from].attr).norm();
+        let input_lengths: Vec<f64> = input.edges.iter().map(|e| length(e.he)).collect();
+        let (input_signposts, angle_scales) = signposts(input, &input_lengths);
+
+        let mesh: Mesh = Mesh::from_vertices_and_faces(
+            vec![(); input.vertices.len()],
+            &input.face_vertex_counts(),
+            &input.face_vertex_indices(),
+        )?;
+        let input_halfedges: HashMap<(VertexId, VertexId), HalfEdgeId> =
+            input.halfedge_ids().map(|h| ((input.halfedges[h].from, input.halfedge_to(h)), h)).collect();
+        let original = |h: HalfEdgeId| input_halfedges[&(mesh.halfedges[h].from, mesh.halfedge_to(h))];
+        let edge_lengths = EdgeLengths(mesh.edges.iter().map(|e| input_lengths[input.halfedges[original(e.he)].edge.index()]).collect());
+        let signposts = mesh.halfedge_ids().map(|h| input_signposts[original(h).index()]).collect();
+        Ok(IntrinsicTriangulation { input, mesh, edge_lengths, signposts, input_signposts, angle_scales })
+    }
+
+    /// Length of the intrinsic edge of half-edge `h`
+    fn length(&self, h: HalfEdgeId) -> f64 {
+        self.edge_lengths.halfedge_length(&self.mesh, h)
+    }
+
+    /// Cotangent weight `(cot α + cot β) / 2` of edge `e`, from the angles
+    /// opposite to it; boundary sides contribute nothing.
+    pub fn cotan_weight(&self, e: EdgeId) -> f64 {
+        self.edge_lengths.cotan_weight(&self.mesh, e)
+    }
+
+    /// Whether edge `e` is locally Delaunay, i.e. has a non-negative
+    /// cotangent weight
+    pub fn is_delaunay(&self, e: EdgeId) -> bool {
+        self.cotan_weight(e) >= -1e-12
+    }
+
+    /// Flip edge `e` intrinsically: it becomes the geodesic between the
+    /// opposite corners of its two triangles, laid out in the plane. Fails
+    /// where [`Mesh::flip_edge`] does or if the two triangles do not form a
+    /// convex quadrilateral.
+    pub fn flip_edge(&mut self, e: EdgeId) -> Result<()> {
+        if !self.mesh.is_flip_ok(e) {
+            return Err(Error::Topology(format!("edge {} cannot be flipped", e)));
+        }
+        // Triangles (a, b, c) = h, h1, h2 and (b, a, d) = t, t1, t2
+        let [h, t] = self.mesh.edge_halfedges(e);
+        let h1 = self.mesh.halfedges[h].next;
+        let h2 = self.mesh.halfedges[h1].next;
+        let t1 = self.mesh.halfedges[t].next;
+        let t2 = self.mesh.halfedges[t1].next;
+        let l_ab = self.length(h);
+        let (l_bc, l_ca) = (self.length(h1), self.length(h2));
+        let (l_ad, l_db) = (self.length(t1), self.length(t2));
+        // a at the origin, b on the x axis, c above and d below it; the
+        // quadrilateral is convex if c-d crosses the x axis between a and b
+        let (cx, cy) = layout(l_ab, l_ca, l_bc);
+        let (dx, dy) = layout(l_ab, l_ad, l_db);
+        let crossing = cx + (dx - cx) * cy / (cy + dy);
+        if !(cy > 0.0 && dy > 0.0 && crossing > 0.0 && crossing < l_ab) {
+            return Err(Error::Topology(format!("edge {} is not in a convex quadrilateral", e)));
+        }
+        let l_cd = ((cx - dx).powi(2) + (cy + dy).powi(2)).sqrt();
+
+        let (c, d) = (self.mesh.halfedges[h2].from, self.mesh.halfedges[t2].from);
+        self.mesh.flip_edge(e)?;
+        self.edge_lengths[e] = l_cd;
+        // New triangles (d, c, a) = h, h2, t1 and (c, d, b) = t, t2, h1; the
+        // new half-edges follow d -> a and c -> b clockwise
+        let angle_d = angle_from_lengths(l_cd, l_ad, l_ca);
+        let angle_c = angle_from_lengths(l_cd, l_bc, l_db);
+        self.signposts[h.index()] = self.rotate(d, self.signposts[self.mesh.halfedges[t1].twin.index()], angle_d);
+        self.signposts[t.index()] = self.rotate(c, self.signposts[self.mesh.halfedges[h1].twin.index()], angle_c);
+        Ok(())
+    }
+
+    /// Signpost `theta` at vertex `v` turned clockwise by `angle`
+    fn rotate(&self, v: VertexId, theta: f64, angle: f64) -> f64 {
+        let theta = theta + self.angle_scales[v.index()] * angle;
+        if self.input.is_boundary_vertex(v) { theta } else { theta.rem_euclid(2.0 * PI) }
+    }
+
+    /// Flip non-Delaunay edges until every flippable edge is locally
+    /// Delaunay. Returns the number of flips.
+    pub fn flip_to_delaunay(&mut self) -> usize {
+        let mut queue: VecDeque<EdgeId> = self.mesh.edge_ids().collect();
+        let mut queued = vec![true; self.mesh.edges.len()];
+        let mut flips = 0;
+        while let Some(e) = queue.pop_front() {
+            queued[e.index()] = false;
+            if self.is_delaunay(e) || self.flip_edge(e).is_err() {
+                continue;
+            }
+            flips += 1;
+            for h in self.mesh.edge_halfedges(e) {
+                let n = self.mesh.halfedges[h].next;
+                for side in [n, self.mesh.halfedges[n].next] {
+                    let s = self.mesh.halfedges[side].edge;
+                    if !queued[s.index()] {
+                        queued[s.index()] = true;
+                        queue.push_back(s);
+                    }
+                }
+            }
+        }
+        flips
+    }
+
+    /// Barycentric vertex areas: a third of the area of every incident
+    /// intrinsic triangle
+    pub fn vertex_areas(&self) -> Vec<f64> {
+        let mut areas = vec![0.0; self.mesh.vertices.len()];
+        for f in self.mesh.face_ids() {
+            let hs: Vec<HalfEdgeId> = self.mesh.face_halfedges(f).collect();
+            let area = triangle_from_lengths(self.length(hs[0]), self.length(hs[1]), self.length(hs[2]));
+            for h in hs {
+                areas[self.mesh.halfedges[h].from.index()] += area / 3.0;
+            }
+        }
+        areas
+    }
+
+    /// Cotangent Laplacian on the intrinsic triangulation, with the sign
+    /// convention of [`crate::operators::Laplacian`]: `(L f)_i = Σ_j w_ij
+    /// (f_j - f_i)`, not divided by vertex areas. After
+    /// [`IntrinsicTriangulation::flip_to_delaunay`] all off-diagonal entries
+    /// are non-negative.
+    pub fn cotan_laplacian(&self) -> CsMat<f64> {
+        cotan_stiffness(&self.mesh, &self.edge_lengths).map(|w| -w)
+    }
+
+    /// Trace intrinsic half-edge `h` over the input surface, as the input
+    /// vertices and edge crossings it passes through from its tail to its
+    /// head. Intrinsic vertex values need no mapping since both
+    /// triangulations share their vertices; this maps intrinsic edges, and
+    /// with them intrinsic faces, back to the input.
+    pub fn trace_halfedge(&self, h: HalfEdgeId) -> Vec<SurfacePoint> {
+        let input = self.input;
+        let (from, to) = (self.mesh.halfedges[h].from, self.mesh.halfedge_to(h));
+        let length = self.length(h);
+        let mut path = vec![SurfacePoint::Vertex(from)];
+
+        // the input wedge the signpost points into, between a and the next
+        // outgoing half-edge clockwise
+        let theta = self.signposts[h.index()];
+        let Some(a) = input
+            .vertex_outgoing_halfedges(from)
+            .filter(|&a| !input.is_boundary_halfedge(input.halfedges[a].twin))
+            .take_while(|&a| self.input_signposts[a.index()] <= theta + 1e-12)
+            .last()
+        else {
+            path.push(SurfacePoint::Vertex(to));
+            return path;
+        };
+        let phi = (theta - self.input_signposts[a.index()]) / self.angle_scales[from.index()];
+        let direction = (phi.cos(), -phi.sin());
+
+        // lay out the wedge triangle (p, from, q) with `from` at the origin
+        let input_length = |h: HalfEdgeId| (input.vertices[input.halfedge_to(h)].attr - input.vertices[input.halfedges[h].from].attr).norm();
+        let twin = input.halfedges[a].twin;
+        let q_he = input.halfedges[twin].next;
+        let (l_p, l_q) = (input_length(a), input_length(q_he));
+        let alpha = angle_from_lengths(l_p, l_q, input_length(input.halfedges[q_he].next));
+        let mut corners = [(twin, (l_p, 0.0)), (q_he, (0.0, 0.0)), (input.halfedges[q_he].next, (l_q * alpha.cos(), -l_q * alpha.sin()))];
+        let mut entry = [twin, q_he];
+        let mut point = (0.0, 0.0);
+        let mut remaining = length;
+
+        for _ in 0..input.halfedges.len() {
+            // nearest exit through an edge of the current face
+            let mut exit: Option<(f64, f64, usize)> = None;
+            for k in 0..3 {
+                let (he, p0) = corners[k];
+                if entry.contains(&he) { continue; }
+                let p1 = corners[(k + 1) % 3].1;
+                if let Some((t, s)) = ray_segment(point, direction, p0, p1) {
+                    if !matches!(exit, Some((best, _, _)) if best <= t) {
+                        exit = Some((t, s, k));
+                    }
+                }
+            }
+            let Some((t, s, k)) = exit else { break };
+            if t >= remaining - 1e-9 * length.max(1.0) {
+                break;
+            }
+            let (he, p0) = corners[k];
+            let p1 = corners[(k + 1) % 3].1;
+            let twin = input.halfedges[he].twin;
+            if input.is_boundary_halfedge(twin) {
+                break;
+            }
+            path.push(SurfacePoint::Edge { halfedge: he, t: s.clamp(0.0, 1.0) });
+            point = (point.0 + t * direction.0, point.1 + t * direction.1);
+            remaining -= t;
+
+            // unfold the neighbouring triangle (p1, p0, c) across the edge
+            let next = input.halfedges[twin].next;
+            let last = input.halfedges[next].next;
+            let l_e = input_length(he);
+            let (x, y) = layout(l_e, input_length(next), input_length(last));
+            let u = ((p1.0 - p0.0) / l_e, (p1.1 - p0.1) / l_e);
+            let c = (p0.0 + x * u.0 + y * u.1, p0.1 + x * u.1 - y * u.0);
+            corners = [(twin, p1), (next, p0), (last, c)];
+            entry = [twin, twin];
+        }
+        path.push(SurfacePoint::Vertex(to));
+        path
+    }
+}
+
+/// Signposts of the half-edges of `mesh` with edge lengths `lengths`, and the
+/// angle scale of every vertex
+fn signposts<VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, lengths: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let length = |h: HalfEdgeId| lengths[mesh.halfedges[h].edge.index()];
+    let mut angles = vec![0.0; mesh.halfedges.len()];
+    let mut scales = vec![1.0; mesh.vertices.len()];
+    for v in mesh.vertex_ids() {
+        let mut theta = 0.0;
+        for h in mesh.vertex_outgoing_halfedges(v) {
+            angles[h.index()] = theta;
+            let twin = mesh.halfedges[h].twin;
+            if !mesh.is_boundary_halfedge(twin) {
+                let n = mesh.halfedges[twin].next;
+                theta += angle_from_lengths(length(h), length(n), length(mesh.halfedges[n].next));
+            }
+        }
+        if theta > 0.0 {
+            let total = if mesh.is_boundary_vertex(v) { PI } else { 2.0 * PI };
+            scales[v.index()] = total / theta;
+        }
+        for h in mesh.vertex_outgoing_halfedges(v) {
+            angles[h.index()] *= scales[v.index()];
+        }
+    }
+    (angles, scales)
+}
+
+/// Position of the apex of a triangle on base `(0, 0)`–`(base, 0)` with
+/// sides `left` and `right` to it, above the base
+fn layout(base: f64, left: f64, right: f64) -> (f64, f64) {
+    let x = (base * base + left * left - right * right) / (2.0 * base);
+    (x, (left * left - x * x).max(0.0).sqrt())
+}
+
+/// Parameters `(t, s)` where the ray `origin + t dir` crosses segment
+/// `p0 + s (p1 - p0)`, for `t > 0`
+fn ray_segment(origin: (f64, f64), dir: (f64, f64), p0: (f64, f64), p1: (f64, f64)) -> Option<(f64, f64)> {
+    let e = (p1.0 - p0.0, p1.1 - p0.1);
+    let denom = dir.0 * e.1 - dir.1 * e.0;
+    if denom.abs() < 1e-15 { return None; }
+    let w = (p0.0 - origin.0, p0.1 - origin.1);
+    let t = (w.0 * e.1 - w.1 * e.0) / denom;
+    let s = (w.0 * dir.1 - w.1 * dir.0) / denom;
+    (t > 1e-12 && (-1e-9..=1.0 + 1e-9).contains(&s)).then_some((t, s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two triangles hinged on the long edge (0, 1), with the opposite
+    /// corners 2 and 3 folded by `fold` radians out of the plane
+    fn thin_diamond(fold: f64) -> Mesh3D<(), ()> {
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.1, 0.0),
+            Vector3::new(1.0, -0.1 * fold.cos(), -0.1 * fold.sin()),
+        ];
+        Mesh::from_vertices_and_faces(positions, &[3, 3], &[0, 1, 2, 1, 0, 3]).unwrap()
+    }
+
+    #[test]
+    fn test_flip_to_delaunay() {
+        let input = thin_diamond(0.0);
+        let mut intrinsic = IntrinsicTriangulation::new(&input).unwrap();
+        let long = intrinsic.mesh.edge_ids().find(|&e| (intrinsic.edge_lengths[e] - 2.0).abs() < 1e-12).unwrap();
+        assert!(intrinsic.cotan_weight(long) < 0.0);
+        assert_eq!(intrinsic.flip_to_delaunay(), 1);
+        assert!((intrinsic.edge_lengths[long] - 0.2).abs() < 1e-12);
+        assert!(intrinsic.mesh.edge_ids().all(|e| intrinsic.is_delaunay(e)));
+        assert!(intrinsic.mesh.is_valid());
+
+        let laplacian = intrinsic.cotan_laplacian();
+        for (row, vec) in laplacian.outer_iterator().enumerate() {
+            assert!(vec.iter().map(|(_, w)| w).sum::<f64>().abs() < 1e-12);
+            assert!(vec.iter().all(|(col, &w)| col == row || w >= 0.0));
+        }
+        let total: f64 = intrinsic.vertex_areas().iter().sum();
+        assert!((total - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_trace_flipped_edge_over_fold() {
+        // folding the diamond does not change its intrinsic geometry, so the
+        // flipped edge is the unfolded diagonal rather than the 3D chord
+        let input = thin_diamond(std::f64::consts::FRAC_PI_2);
+        let mut intrinsic = IntrinsicTriangulation::new(&input).unwrap();
+        assert_eq!(intrinsic.flip_to_delaunay(), 1);
+        let h = intrinsic.mesh.halfedge_ids().find(|&h| intrinsic.mesh.halfedges[h].from == VertexId::new(2) && intrinsic.mesh.halfedge_to(h) == VertexId::new(3)).unwrap();
+        assert!((intrinsic.length(h) - 0.2).abs() < 1e-12);
+
+        let path = intrinsic.trace_halfedge(h);
+        assert_eq!(path.len(), 3);
+        assert_eq!(path[0], SurfacePoint::Vertex(VertexId::new(2)));
+        assert_eq!(path[2], SurfacePoint::Vertex(VertexId::new(3)));
+        assert!((path[1].position(&input) - Vector3::new(1.0, 0.0, 0.0)).norm() < 1e-9);
+
+        // unflipped edges trace along themselves
+        let e = intrinsic.mesh.halfedge_ids().find(|&h| intrinsic.mesh.halfedges[h].from == VertexId::new(0) && intrinsic.mesh.halfedge_to(h) == VertexId::new(2)).unwrap();
+        assert_eq!(intrinsic.trace_halfedge(e), vec![SurfacePoint::Vertex(VertexId::new(0)), SurfacePoint::Vertex(VertexId::new(2))]);
+    }
+
+    #[test]
+    fn test_traces_are_straight_on_planar_mesh() {
+        // a sheared grid split along its long diagonals
+        let n = 5;
+        let positions: Vec<Vector3> = (0..n * n)
+            .map(|v| Vector3::new((v % n) as f64 + 0.9 * (v / n) as f64, 0.35 * (v / n) as f64, 0.0))
+            .collect();
+        let mut indices = Vec::new();
+        for j in 0..n - 1 {
+            for i in 0..n - 1 {
+                let v = j * n + i;
+                indices.extend_from_slice(&[v, v + 1, v + n + 1, v, v + n + 1, v + n]);
+            }
+        }
+        let input: Mesh3D<(), ()> = Mesh::from_vertices_and_faces(positions, &vec![3; indices.len() / 3], &indices).unwrap();
+        let mut intrinsic = IntrinsicTriangulation::new(&input).unwrap();
+        assert!(intrinsic.flip_to_delaunay() > 0);
+        assert!(intrinsic.mesh.edge_ids().all(|e| intrinsic.is_delaunay(e)));
+        let mut crossings = 0;
+        for h in intrinsic.mesh.halfedge_ids() {
+            let path = intrinsic.trace_halfedge(h);
+            crossings += path.len() - 2;
+            let a = input.vertices[intrinsic.mesh.halfedges[h].from].attr;
+            let b = input.vertices[intrinsic.mesh.halfedge_to(h)].attr;
+            assert!(((b - a).norm() - intrinsic.length(h)).abs() < 1e-9);
+            for point in &path[1..path.len() - 1] {
+                let p = point.position(&input);
+                assert!((p - a).cross(&(b - a)).norm() < 1e-9, "{:?} leaves the segment", point);
+            }
+        }
+        assert!(crossings > 0);
+    }
+}
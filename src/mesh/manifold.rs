@@ -0,0 +1,187 @@
+//! Non-manifold repair of polygon soups
+//!
+//! Edges shared by more than two faces and vertices whose faces form more
+//! than one fan ("bow-ties") cannot be represented with half-edges.
+//! [`PolygonSoup::make_manifold`] keeps one pair of faces glued along every
+//! edge and gives each fan of faces around a vertex its own copy of it.
+
+use crate::mesh::soup::PolygonSoup;
+use std::collections::HashMap;
+
+/// Result of [`PolygonSoup::make_manifold`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifoldRepair {
+    /// Original id of every vertex of the repaired soup; vertices that were
+    /// not duplicated map to themselves
+    pub vertex_map: Vec<usize>,
+    /// Number of edges that were shared by more than two faces
+    pub non_manifold_edges: usize,
+    /// Original vertices that had to be duplicated, in increasing order
+    pub split_vertices: Vec<usize>,
+}
+
+/// Corners `(from, to)` of the first face traversing an edge from its
+/// smaller to its larger vertex, and of the first one going back
+type EdgeSides = [Option<(usize, usize)>; 2];
+
+impl<VA: Clone> PolygonSoup<VA> {
+    /// Duplicate vertices until the soup describes a manifold.
+    ///
+    /// Along every edge, the first faces traversing it in either direction
+    /// are glued; all other faces are cut off, leaving the edge on their
+    /// boundary. Corners around a vertex that are connected through glued
+    /// edges form a fan; the first fan keeps the vertex, every other fan
+    /// gets a copy with a clone of its attribute.
+    ///
+    /// Faces must not repeat vertices and should be oriented consistently
+    /// first (see [`PolygonSoup::orient`]); otherwise edges between
+    /// inconsistently wound faces are cut as well.
+    pub fn make_manifold(&mut self) -> ManifoldRepair {
+        let mut vertex_map: Vec<usize> = (0..self.vertices.len()).collect();
+        let non_manifold_edges = self.edge_faces().values().filter(|faces| faces.len() > 2).count();
+        // detaching a face can split a fan again, so repeat until stable;
+        // every round either adds vertices or ends the loop
+        loop {
+            let split = self.split_fans(&mut vertex_map);
+            let detached = self.detach_duplicate_edges(&mut vertex_map);
+            if !split && !detached { break; }
+        }
+        let mut split_vertices: Vec<usize> = vertex_map.iter().enumerate().filter(|&(v, &o)| v != o).map(|(_, &o)| o).collect();
+        split_vertices.sort_unstable();
+        split_vertices.dedup();
+        ManifoldRepair { vertex_map, non_manifold_edges, split_vertices }
+    }
+
+    /// Append a copy of vertex `v` and return its id
+    fn duplicate_vertex(&mut self, v: usize, vertex_map: &mut Vec<usize>) -> usize {
+        self.vertices.push(self.vertices[v].clone());
+        vertex_map.push(vertex_map[v]);
+        self.vertices.len() - 1
+    }
+
+    /// Give every fan of corners around a vertex its own vertex. Returns
+    /// whether any vertex was duplicated.
+    fn split_fans(&mut self, vertex_map: &mut Vec<usize>) -> bool {
+        let offsets = self.face_offsets();
+        let next = |c: usize, f: usize| if c + 1 == offsets[f + 1] { offsets[f] } else { c + 1 };
+
+        // glue the first corner pair traversing each edge in either direction
+        let mut sides: HashMap<(usize, usize), EdgeSides> = HashMap::new();
+        for f in 0..self.face_count() {
+            for c in offsets[f]..offsets[f + 1] {
+                let d = next(c, f);
+                let (a, b) = (self.face_indices[c], self.face_indices[d]);
+                let side = &mut sides.entry((a.min(b), a.max(b))).or_default()[usize::from(a > b)];
+                if side.is_none() {
+                    *side = Some((c, d));
+                }
+            }
+        }
+        let mut parent: Vec<usize> = (0..self.face_indices.len()).collect();
+        for side in sides.values() {
+            if let [Some((c_a, c_b)), Some((d_b, d_a))] = *side {
+                union(&mut parent, c_a, d_a);
+                union(&mut parent, c_b, d_b);
+            }
+        }
+
+        // the fan of the first corner of each vertex keeps it
+        let mut fan_vertex: HashMap<usize, usize> = HashMap::new();
+        let mut has_fan = vec![false; self.vertices.len()];
+        let mut split = false;
+        for c in 0..self.face_indices.len() {
+            let root = find(&mut parent, c);
+            let v = self.face_indices[c];
+            let target = match fan_vertex.get(&root) {
+                Some(&target) => target,
+                None => {
+                    let target = if has_fan[v] {
+                        split = true;
+                        self.duplicate_vertex(v, vertex_map)
+                    } else {
+                        v
+                    };
+                    has_fan[v] = true;
+                    fan_vertex.insert(root, target);
+                    target
+                }
+            };
+            self.face_indices[c] = target;
+        }
+        split
+    }
+
+    /// Give faces that repeat a directed edge of an earlier face their own
+    /// copies of its endpoints. Returns whether any face was detached.
+    fn detach_duplicate_edges(&mut self, vertex_map: &mut Vec<usize>) -> bool {
+        let offsets = self.face_offsets();
+        let mut seen: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut detached = false;
+        for f in 0..self.face_count() {
+            for c in offsets[f]..offsets[f + 1] {
+                let d = if c + 1 == offsets[f + 1] { offsets[f] } else { c + 1 };
+                let edge = (self.face_indices[c], self.face_indices[d]);
+                if *seen.entry(edge).or_insert(f) != f {
+                    self.face_indices[c] = self.duplicate_vertex(edge.0, vertex_map);
+                    self.face_indices[d] = self.duplicate_vertex(edge.1, vertex_map);
+                    detached = true;
+                }
+            }
+        }
+        detached
+    }
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (ra, rb) = (find(parent, a), find(parent, b));
+    parent[ra.max(rb)] = ra.min(rb);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::half_edge::Mesh;
+
+    #[test]
+    fn test_split_non_manifold_edge() {
+        // three triangles hinged on edge (0, 1)
+        let mut soup = PolygonSoup::new(vec![(); 5], vec![3; 3], vec![0, 1, 2, 1, 0, 3, 0, 1, 4]);
+        assert!(soup.clone().into_mesh::<(), ()>().is_err());
+        let repair = soup.make_manifold();
+        assert_eq!(repair.non_manifold_edges, 1);
+        assert_eq!(repair.vertex_map, vec![0, 1, 2, 3, 4, 0, 1]);
+        assert_eq!(repair.split_vertices, vec![0, 1]);
+        assert_eq!(soup.face_indices, vec![0, 1, 2, 1, 0, 3, 5, 6, 4]);
+        let mesh: Mesh<(), (), ()> = soup.into_mesh().unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.components().count, 2);
+    }
+
+    #[test]
+    fn test_split_bow_tie_vertex() {
+        // two triangles touching at vertex 0
+        let mut soup = PolygonSoup::new(vec![[0.5]; 5], vec![3, 3], vec![0, 1, 2, 0, 3, 4]);
+        assert!(soup.clone().into_mesh::<(), ()>().is_err());
+        let repair = soup.make_manifold();
+        assert_eq!(repair.non_manifold_edges, 0);
+        assert_eq!(repair.vertex_map, vec![0, 1, 2, 3, 4, 0]);
+        assert_eq!(soup.face_indices, vec![0, 1, 2, 5, 3, 4]);
+        assert_eq!(soup.vertices.len(), 6);
+        let mesh: Mesh<[f64; 1], (), ()> = soup.into_mesh().unwrap();
+        assert!(mesh.is_valid());
+
+        // a manifold soup is left untouched
+        let mut square = PolygonSoup::new(vec![(); 4], vec![3, 3], vec![0, 1, 2, 0, 2, 3]);
+        let repair = square.make_manifold();
+        assert_eq!(repair.vertex_map, vec![0, 1, 2, 3]);
+        assert!(repair.split_vertices.is_empty());
+    }
+}
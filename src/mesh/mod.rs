@@ -1,2 +1,17 @@
 pub mod half_edge;
-pub mod attributes;
\ No newline at end of file
+pub mod attributes;
+pub mod validation;
+pub mod circulators;
+pub mod editing;
+pub mod handles;
+pub mod properties;
+pub mod triangulation;
+pub mod topology;
+pub mod soup;
+pub mod orientation;
+pub mod manifold;
+pub mod cleanup;
+pub mod submesh;
+pub mod intrinsic;
+pub mod tet;
+pub mod tree_cotree;
@@ -0,0 +1,173 @@
+//! Consistent orientation of polygon soups
+//!
+//! The half-edge builder only pairs half-edges `(a, b)` and `(b, a)`, so
+//! faces wound inconsistently with their neighbours cannot be connected.
+//! [`PolygonSoup::orient`] flips faces until every manifold edge is
+//! traversed in opposite directions by its two faces.
+
+use crate::geometry::predicate::orient3d;
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::INVALID;
+use crate::mesh::soup::PolygonSoup;
+use std::collections::VecDeque;
+
+/// Result of an orientation pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrientationReport {
+    /// Number of edge-connected face components
+    pub components: usize,
+    /// Component of every face
+    pub face_components: Vec<usize>,
+    /// Faces whose winding was reversed
+    pub flipped: Vec<usize>,
+    /// Components that admit no consistent winding (e.g. a Möbius strip);
+    /// their faces are left as the search reached them.
+    pub non_orientable: Vec<usize>,
+}
+
+impl<VA> PolygonSoup<VA> {
+    /// Make the winding of every face consistent with its neighbours.
+    ///
+    /// Components are explored breadth-first from their lowest face, which
+    /// keeps its winding. Orientation propagates across edges shared by
+    /// exactly two faces; non-manifold edges are ignored.
+    pub fn orient(&mut self) -> OrientationReport {
+        let nf = self.face_count();
+        let edges = self.edge_faces();
+        let offsets = self.face_offsets();
+        let mut flip = vec![false; nf];
+        let mut face_components = vec![INVALID; nf];
+        let mut non_orientable = Vec::new();
+        let mut components = 0;
+        let mut queue = VecDeque::new();
+        for seed in 0..nf {
+            if face_components[seed] != INVALID { continue; }
+            face_components[seed] = components;
+            queue.push_back(seed);
+            let mut consistent = true;
+            while let Some(f) = queue.pop_front() {
+                let face = &self.face_indices[offsets[f]..offsets[f + 1]];
+                for k in 0..face.len() {
+                    let (a, b) = (face[k], face[(k + 1) % face.len()]);
+                    let incident = &edges[&(a.min(b), a.max(b))];
+                    if incident.len() != 2 { continue; }
+                    let (f_dir, (g, g_dir)) = if incident[0].0 == f {
+                        (incident[0].1, incident[1])
+                    } else {
+                        (incident[1].1, incident[0])
+                    };
+                    // g must traverse the edge against f's effective direction
+                    let g_flip = g_dir == (f_dir != flip[f]);
+                    if face_components[g] == INVALID {
+                        face_components[g] = components;
+                        flip[g] = g_flip;
+                        queue.push_back(g);
+                    } else if flip[g] != g_flip {
+                        consistent = false;
+                    }
+                }
+            }
+            if !consistent {
+                non_orientable.push(components);
+            }
+            components += 1;
+        }
+        let flipped: Vec<usize> = (0..nf).filter(|&f| flip[f]).collect();
+        for &f in &flipped {
+            self.face_indices[offsets[f] + 1..offsets[f + 1]].reverse();
+        }
+        OrientationReport { components, face_components, flipped, non_orientable }
+    }
+
+    /// Orient consistently with [`PolygonSoup::orient`], then reverse every
+    /// orientable component whose signed volume is negative so that normals
+    /// point outward. Only meaningful for closed components; faces with
+    /// fewer than three vertices enclose no volume and are skipped.
+    pub fn orient_outward(&mut self) -> OrientationReport
+    where
+        VA: Copy + Into<Vector3>,
+    {
+        let mut report = self.orient();
+        let offsets = self.face_offsets();
+        let mut volume = vec![0.0; report.components];
+        let mut centroid = vec![(Vector3::zero(), 0usize); report.components];
+        for (f, face) in self.faces().enumerate() {
+            let c = &mut centroid[report.face_components[f]];
+            for &v in face {
+                c.0 = c.0 + self.vertices[v].into();
+                c.1 += 1;
+            }
+        }
+        for (f, face) in self.faces().enumerate() {
+            if face.len() < 3 { continue; }
+            let c = report.face_components[f];
+            let origin = centroid[c].0 / centroid[c].1 as f64;
+            let p0 = self.vertices[face[0]].into();
+            for k in 1..face.len() - 1 {
+                let (p1, p2) = (self.vertices[face[k]].into(), self.vertices[face[k + 1]].into());
+                volume[c] += orient3d(&origin, &p0, &p1, &p2);
+            }
+        }
+        let mut flipped = vec![false; self.face_count()];
+        for &f in &report.flipped {
+            flipped[f] = true;
+        }
+        for f in 0..self.face_count() {
+            let c = report.face_components[f];
+            if volume[c] < 0.0 && !report.non_orientable.contains(&c) {
+                self.face_indices[offsets[f] + 1..offsets[f + 1]].reverse();
+                flipped[f] = !flipped[f];
+            }
+        }
+        report.flipped = (0..self.face_count()).filter(|&f| flipped[f]).collect();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::half_edge::Mesh;
+
+    #[test]
+    fn test_orient_inconsistent_soup() {
+        // tetrahedron with two inward-facing triangles
+        let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let mut soup = PolygonSoup::new(positions, vec![3; 4], vec![0, 2, 1, 0, 3, 1, 1, 2, 3, 0, 2, 3]);
+        assert!(soup.clone().into_mesh::<(), ()>().is_err());
+
+        let report = soup.orient();
+        assert_eq!(report.components, 1);
+        assert_eq!(report.flipped, vec![1, 3]);
+        assert!(report.non_orientable.is_empty());
+        let mesh: Mesh<[f64; 3], (), ()> = soup.clone().into_mesh().unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.euler_characteristic(), 2);
+
+        // inward-facing after orient(): the seed face decided the winding
+        let mut inverted = PolygonSoup::new(soup.vertices.clone(), vec![3; 4], vec![0, 1, 2, 0, 3, 1, 1, 3, 2, 0, 2, 3]);
+        let report = inverted.orient_outward();
+        assert_eq!(report.flipped.len(), 4);
+        assert_eq!(inverted.face_indices, soup.face_indices);
+
+        // degenerate faces are left alone
+        let mut degenerate = PolygonSoup::new(
+            soup.vertices.clone(), vec![3, 0, 3, 3, 1, 3], vec![0, 1, 2, 0, 3, 1, 1, 3, 2, 1, 0, 2, 3],
+        );
+        let report = degenerate.orient_outward();
+        assert_eq!(report.flipped, vec![0, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_mobius_strip_is_non_orientable() {
+        // a band of four quads closed with a half twist
+        let mut soup = PolygonSoup::new(
+            vec![(); 8],
+            vec![4; 4],
+            vec![0, 1, 5, 4, 1, 2, 6, 5, 2, 3, 7, 6, 3, 4, 0, 7],
+        );
+        let report = soup.orient();
+        assert_eq!(report.components, 1);
+        assert_eq!(report.non_orientable, vec![0]);
+    }
+}
@@ -0,0 +1,516 @@
+//! Named, runtime-registered element properties
+//!
+//! Besides the compile-time `VA`/`EA`/`FA` attributes, a mesh can carry any
+//! number of typed properties per element kind, registered by name at
+//! runtime. Values are stored densely, one per element slot, and are kept in
+//! sync by the editing operations: new elements get `T::default()` and
+//! `garbage_collect` drops the values of removed elements.
+
+use crate::error::{Error, Result};
+use crate::mesh::half_edge::{EdgeId, FaceId, HalfEdgeId, Mesh, VertexId};
+use crate::mesh::handles::Handle;
+use std::any::{type_name, Any};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Values of one property, indexed by element handle
+pub struct Property<H, T> {
+    values: Vec<T>,
+    _kind: PhantomData<fn(H)>,
+}
+
+impl<H: Handle, T> Property<H, T> {
+    /// Value for element `h`, if in range
+    pub fn get(&self, h: H) -> Option<&T> {
+        self.values.get(h.index())
+    }
+
+    /// All values, indexed by raw element index
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// All values, mutably
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+}
+
+impl<H: Handle, T> Index<H> for Property<H, T> {
+    type Output = T;
+
+    fn index(&self, h: H) -> &T {
+        &self.values[h.index()]
+    }
+}
+
+impl<H: Handle, T> IndexMut<H> for Property<H, T> {
+    fn index_mut(&mut self, h: H) -> &mut T {
+        &mut self.values[h.index()]
+    }
+}
+
+impl<H, T: fmt::Debug> fmt::Debug for Property<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.values.fmt(f)
+    }
+}
+
+/// Type-erased property storage
+trait Column: fmt::Debug {
+    fn resize(&mut self, n: usize);
+    fn retain(&mut self, deleted: &[bool]);
+    fn prepend_defaults(&mut self, n: usize);
+    fn append(&mut self, other: &mut dyn Column);
+    fn value_type(&self) -> &'static str;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<H: 'static, T: Clone + Default + fmt::Debug + 'static> Column for Property<H, T> {
+    fn resize(&mut self, n: usize) {
+        self.values.resize(n, T::default());
+    }
+
+    fn retain(&mut self, deleted: &[bool]) {
+        let mut i = 0;
+        self.values.retain(|_| {
+            let keep = !deleted.get(i).copied().unwrap_or(false);
+            i += 1;
+            keep
+        });
+    }
+
+    fn prepend_defaults(&mut self, n: usize) {
+        self.values.splice(0..0, std::iter::repeat_n(T::default(), n));
+    }
+
+    fn append(&mut self, other: &mut dyn Column) {
+        if let Some(other) = other.as_any_mut().downcast_mut::<Self>() {
+            self.values.append(&mut other.values);
+        }
+    }
+
+    fn value_type(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The properties of one element kind
+#[derive(Debug, Default)]
+pub struct PropertyStore {
+    /// Slots are never reused, so a removed property's handle stays dead
+    columns: Vec<Option<(String, Box<dyn Column>)>>,
+}
+
+impl PropertyStore {
+    /// Names of the registered properties, in registration order
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.columns.iter().flatten().map(|(name, _)| name.as_str())
+    }
+
+    fn slot(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| matches!(c, Some((n, _)) if n == name))
+    }
+
+    fn resize(&mut self, n: usize) {
+        for (_, column) in self.columns.iter_mut().flatten() {
+            column.resize(n);
+        }
+    }
+
+    fn retain(&mut self, deleted: &[bool]) {
+        for (_, column) in self.columns.iter_mut().flatten() {
+            column.retain(deleted);
+        }
+    }
+
+    /// Name and value types of the first property of `other` whose name is
+    /// registered here with another type
+    fn conflict<'a>(&self, other: &'a PropertyStore) -> Option<(&'a str, &'static str, &'static str)> {
+        other.columns.iter().flatten().find_map(|(name, theirs)| {
+            let (_, ours) = self.columns[self.slot(name)?].as_ref()?;
+            let same = Any::type_id(ours.as_any()) == Any::type_id(theirs.as_any());
+            (!same).then(|| (name.as_str(), ours.value_type(), theirs.value_type()))
+        })
+    }
+
+    /// Append the values of `other` after the first `n` of this store;
+    /// properties only registered in `other` are added, with defaults for
+    /// those `n` elements
+    fn append(&mut self, other: PropertyStore, n: usize) {
+        for (name, mut theirs) in other.columns.into_iter().flatten() {
+            match self.slot(&name) {
+                Some(slot) => {
+                    let (_, ours) = self.columns[slot].as_mut().expect("slot found by name");
+                    ours.resize(n);
+                    ours.append(theirs.as_mut());
+                }
+                None => {
+                    theirs.prepend_defaults(n);
+                    self.columns.push(Some((name, theirs)));
+                }
+            }
+        }
+    }
+}
+
+/// Property stores of all element kinds
+#[derive(Debug)]
+pub struct Properties {
+    pub vertices: PropertyStore,
+    pub halfedges: PropertyStore,
+    pub edges: PropertyStore,
+    pub faces: PropertyStore,
+    /// Unique per store, recorded in every [`PropertyId`] it hands out
+    owner: usize,
+}
+
+impl Default for Properties {
+    fn default() -> Self {
+        static NEXT_OWNER: AtomicUsize = AtomicUsize::new(0);
+        Properties {
+            vertices: PropertyStore::default(),
+            halfedges: PropertyStore::default(),
+            edges: PropertyStore::default(),
+            faces: PropertyStore::default(),
+            owner: NEXT_OWNER.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl Properties {
+    /// Check that [`Properties::append`] can merge `other` into these
+    /// properties. Returns `Error::Unsupported` if a property of `other` has
+    /// the name of one here but stores another type.
+    pub(crate) fn check_append(&self, other: &Properties) -> Result<()> {
+        for (ours, theirs, kind) in [
+            (&self.vertices, &other.vertices, VertexId::KIND),
+            (&self.halfedges, &other.halfedges, HalfEdgeId::KIND),
+            (&self.edges, &other.edges, EdgeId::KIND),
+            (&self.faces, &other.faces, FaceId::KIND),
+        ] {
+            if let Some((name, ours, theirs)) = ours.conflict(theirs) {
+                return Err(Error::Unsupported(format!(
+                    "{} property {:?} stores {}, but {} in the appended mesh",
+                    kind, name, ours, theirs
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the properties of a mesh whose elements were moved after the
+    /// `counts` vertices, half-edges, edges and faces of this one, matching
+    /// properties by name. Call [`Properties::check_append`] first.
+    pub(crate) fn append(&mut self, other: Properties, counts: [usize; 4]) {
+        let [nv, nh, ne, nf] = counts;
+        self.vertices.append(other.vertices, nv);
+        self.halfedges.append(other.halfedges, nh);
+        self.edges.append(other.edges, ne);
+        self.faces.append(other.faces, nf);
+    }
+}
+
+/// Element kinds that can carry properties
+pub trait PropertyKind: Handle + 'static {
+    fn store(properties: &Properties) -> &PropertyStore;
+    fn store_mut(properties: &mut Properties) -> &mut PropertyStore;
+}
+
+macro_rules! property_kind {
+    ($handle:ty, $store:ident) => {
+        impl PropertyKind for $handle {
+            fn store(properties: &Properties) -> &PropertyStore {
+                &properties.$store
+            }
+
+            fn store_mut(properties: &mut Properties) -> &mut PropertyStore {
+                &mut properties.$store
+            }
+        }
+    };
+}
+
+property_kind!(VertexId, vertices);
+property_kind!(HalfEdgeId, halfedges);
+property_kind!(EdgeId, edges);
+property_kind!(FaceId, faces);
+
+/// Typed handle of a registered property. It is only valid for the mesh
+/// that registered it; handles of the meshes merged by [`Mesh::append`] do
+/// not carry over, look the property up again by name instead.
+pub struct PropertyId<H, T> {
+    owner: usize,
+    slot: usize,
+    _marker: PhantomData<fn() -> (H, T)>,
+}
+
+impl<H, T> Clone for PropertyId<H, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H, T> Copy for PropertyId<H, T> {}
+
+impl<H, T> fmt::Debug for PropertyId<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PropertyId({})", self.slot)
+    }
+}
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Property stores of all element kinds
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// Register a property named `name` on elements of kind `H`, with every
+    /// value set to `T::default()`. Returns the existing property if one with
+    /// the same name and type is already registered, and `Error::Unsupported`
+    /// if the name is taken by a property of another type.
+    pub fn add_property<H, T>(&mut self, name: &str) -> Result<PropertyId<H, T>>
+    where
+        H: PropertyKind,
+        T: Clone + Default + fmt::Debug + 'static,
+    {
+        if let Some(existing) = self.find_property_slot::<H>(name) {
+            return self.typed_property(name, existing);
+        }
+        let mut column = Property::<H, T> { values: Vec::new(), _kind: PhantomData };
+        Column::resize(&mut column, H::count(self));
+        let store = H::store_mut(&mut self.properties);
+        store.columns.push(Some((name.to_string(), Box::new(column))));
+        let slot = store.columns.len() - 1;
+        Ok(PropertyId { owner: self.properties.owner, slot, _marker: PhantomData })
+    }
+
+    /// Look up the property named `name` on elements of kind `H`; `None` if
+    /// there is none or it stores another type.
+    pub fn find_property<H, T>(&self, name: &str) -> Option<PropertyId<H, T>>
+    where
+        H: PropertyKind,
+        T: 'static,
+    {
+        let slot = self.find_property_slot::<H>(name)?;
+        self.typed_property(name, slot).ok()
+    }
+
+    /// Unregister a property; its handle becomes invalid. Handles of other
+    /// meshes are ignored.
+    pub fn remove_property<H: PropertyKind, T>(&mut self, prop: PropertyId<H, T>) {
+        if prop.owner != self.properties.owner {
+            return;
+        }
+        if let Some(column) = H::store_mut(&mut self.properties).columns.get_mut(prop.slot) {
+            *column = None;
+        }
+    }
+
+    /// Values of a property. Returns `Error::Unsupported` if the property
+    /// was registered on another mesh or removed.
+    pub fn try_property<H: PropertyKind, T: 'static>(&self, prop: PropertyId<H, T>) -> Result<&Property<H, T>> {
+        self.check_owner(prop)?;
+        H::store(&self.properties).columns[prop.slot]
+            .as_ref()
+            .and_then(|(_, column)| column.as_any().downcast_ref())
+            .ok_or_else(|| Error::Unsupported(format!("{} property was removed", H::KIND)))
+    }
+
+    /// Mutable values of a property, see [`Mesh::try_property`]
+    pub fn try_property_mut<H: PropertyKind, T: 'static>(&mut self, prop: PropertyId<H, T>) -> Result<&mut Property<H, T>> {
+        self.check_owner(prop)?;
+        H::store_mut(&mut self.properties).columns[prop.slot]
+            .as_mut()
+            .and_then(|(_, column)| column.as_any_mut().downcast_mut())
+            .ok_or_else(|| Error::Unsupported(format!("{} property was removed", H::KIND)))
+    }
+
+    /// Values of a property.
+    ///
+    /// # Panics
+    /// If the property was registered on another mesh or removed.
+    pub fn property<H: PropertyKind, T: 'static>(&self, prop: PropertyId<H, T>) -> &Property<H, T> {
+        self.try_property(prop).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Mutable values of a property.
+    ///
+    /// # Panics
+    /// If the property was registered on another mesh or removed.
+    pub fn property_mut<H: PropertyKind, T: 'static>(&mut self, prop: PropertyId<H, T>) -> &mut Property<H, T> {
+        self.try_property_mut(prop).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [`Mesh::add_property`] for vertices
+    pub fn add_vertex_property<T: Clone + Default + fmt::Debug + 'static>(&mut self, name: &str) -> Result<PropertyId<VertexId, T>> {
+        self.add_property(name)
+    }
+
+    /// Same as [`Mesh::add_property`] for half-edges
+    pub fn add_halfedge_property<T: Clone + Default + fmt::Debug + 'static>(&mut self, name: &str) -> Result<PropertyId<HalfEdgeId, T>> {
+        self.add_property(name)
+    }
+
+    /// Same as [`Mesh::add_property`] for edges
+    pub fn add_edge_property<T: Clone + Default + fmt::Debug + 'static>(&mut self, name: &str) -> Result<PropertyId<EdgeId, T>> {
+        self.add_property(name)
+    }
+
+    /// Same as [`Mesh::add_property`] for faces
+    pub fn add_face_property<T: Clone + Default + fmt::Debug + 'static>(&mut self, name: &str) -> Result<PropertyId<FaceId, T>> {
+        self.add_property(name)
+    }
+
+    /// Same as [`Mesh::find_property`] for vertices
+    pub fn vertex_property<T: 'static>(&self, name: &str) -> Option<PropertyId<VertexId, T>> {
+        self.find_property(name)
+    }
+
+    /// Same as [`Mesh::find_property`] for half-edges
+    pub fn halfedge_property<T: 'static>(&self, name: &str) -> Option<PropertyId<HalfEdgeId, T>> {
+        self.find_property(name)
+    }
+
+    /// Same as [`Mesh::find_property`] for edges
+    pub fn edge_property<T: 'static>(&self, name: &str) -> Option<PropertyId<EdgeId, T>> {
+        self.find_property(name)
+    }
+
+    /// Same as [`Mesh::find_property`] for faces
+    pub fn face_property<T: 'static>(&self, name: &str) -> Option<PropertyId<FaceId, T>> {
+        self.find_property(name)
+    }
+
+    /// Resize every property to the current element counts. Editing
+    /// operations do this themselves; it is only needed after pushing to the
+    /// element arrays directly.
+    pub fn sync_properties(&mut self) {
+        let (nv, nh, ne, nf) = (self.vertices.len(), self.halfedges.len(), self.edges.len(), self.faces.len());
+        self.properties.vertices.resize(nv);
+        self.properties.halfedges.resize(nh);
+        self.properties.edges.resize(ne);
+        self.properties.faces.resize(nf);
+    }
+
+    /// Drop the values of deleted elements, mirroring `garbage_collect`
+    pub(crate) fn compact_properties(&mut self) {
+        self.properties.vertices.retain(&self.deleted.vertices);
+        self.properties.halfedges.retain(&self.deleted.halfedges);
+        self.properties.edges.retain(&self.deleted.edges);
+        self.properties.faces.retain(&self.deleted.faces);
+    }
+
+    fn check_owner<H: PropertyKind, T>(&self, prop: PropertyId<H, T>) -> Result<()> {
+        if prop.owner == self.properties.owner {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(format!("{} property belongs to another mesh", H::KIND)))
+        }
+    }
+
+    fn find_property_slot<H: PropertyKind>(&self, name: &str) -> Option<usize> {
+        H::store(&self.properties).slot(name)
+    }
+
+    fn typed_property<H: PropertyKind, T: 'static>(&self, name: &str, slot: usize) -> Result<PropertyId<H, T>> {
+        let (_, column) = H::store(&self.properties).columns[slot].as_ref().expect("slot found by name");
+        if column.as_any().is::<Property<H, T>>() {
+            Ok(PropertyId { owner: self.properties.owner, slot, _marker: PhantomData })
+        } else {
+            Err(Error::Unsupported(format!(
+                "{} property {:?} stores {}, not {}",
+                H::KIND, name, column.value_type(), type_name::<T>()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::mesh::half_edge::{FaceId, Mesh, VertexId};
+
+    /// Vertex 4 at the centre of the unit square, joined to all corners
+    fn fan() -> Mesh<[f64; 2], (), ()> {
+        let positions = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.5, 0.5]];
+        let indices = [0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4];
+        Mesh::from_vertices_and_faces(positions, &[3; 4], &indices).unwrap()
+    }
+
+    #[test]
+    fn test_register_and_access_properties() {
+        let mut mesh = fan();
+        let temperature = mesh.add_vertex_property::<f64>("temperature").unwrap();
+        assert_eq!(mesh.property(temperature).values(), &[0.0; 5]);
+        mesh.property_mut(temperature)[VertexId::new(4)] = 36.6;
+        assert_eq!(mesh.property(temperature)[VertexId::new(4)], 36.6);
+
+        // same name and type returns the existing property
+        let again = mesh.add_vertex_property::<f64>("temperature").unwrap();
+        assert_eq!(mesh.property(again)[VertexId::new(4)], 36.6);
+        assert!(matches!(mesh.add_vertex_property::<i32>("temperature"), Err(Error::Unsupported(_))));
+        assert!(mesh.vertex_property::<i32>("temperature").is_none());
+        // names are per element kind
+        let label = mesh.add_face_property::<String>("temperature").unwrap();
+        mesh.property_mut(label)[FaceId::new(1)] = "warm".to_string();
+        assert_eq!(mesh.properties().faces.names().collect::<Vec<_>>(), vec!["temperature"]);
+
+        mesh.remove_property(temperature);
+        assert!(mesh.vertex_property::<f64>("temperature").is_none());
+        assert_eq!(mesh.properties().vertices.names().count(), 0);
+    }
+
+    #[test]
+    fn test_properties_follow_edits() {
+        let mut mesh = fan();
+        let id = mesh.add_vertex_property::<usize>("original").unwrap();
+        let area = mesh.add_face_property::<f64>("area").unwrap();
+        for v in mesh.vertex_ids().collect::<Vec<_>>() {
+            mesh.property_mut(id)[v] = v.index() + 100;
+        }
+        mesh.property_mut(area).values_mut().fill(0.25);
+
+        // a boundary split adds a vertex and a face with default values
+        let e = mesh.vertex_edges(VertexId::new(0)).find(|&e| mesh.is_boundary_edge(e)).unwrap();
+        let w = mesh.split_edge(e, [0.5, 0.0]);
+        assert_eq!(mesh.property(id).values().len(), 6);
+        assert_eq!(mesh.property(id)[w], 0);
+        assert_eq!(mesh.property(area).values().len(), 5);
+
+        // collapsing the centre into corner 2 and compacting drops its value
+        let centre = VertexId::new(4);
+        let he = mesh.vertex_outgoing_halfedges(centre).find(|&h| mesh.halfedge_to(h) == 2).unwrap();
+        mesh.collapse_edge(he).unwrap();
+        let remap = mesh.garbage_collect();
+        assert_eq!(mesh.property(id).values(), &[100, 101, 102, 103, 0]);
+        assert_eq!(remap.vertices[w.index()], 4);
+        assert_eq!(mesh.property(area).values().len(), mesh.faces.len());
+    }
+
+    #[test]
+    fn test_property_of_another_mesh() {
+        let mut mesh = fan();
+        let mut other = fan();
+        let temperature = mesh.add_vertex_property::<f64>("temperature").unwrap();
+        other.add_vertex_property::<f64>("temperature").unwrap();
+        assert!(matches!(other.try_property(temperature), Err(Error::Unsupported(_))));
+        assert!(matches!(other.try_property_mut(temperature), Err(Error::Unsupported(_))));
+        other.remove_property(temperature);
+        assert!(other.vertex_property::<f64>("temperature").is_some());
+
+        mesh.remove_property(temperature);
+        assert!(matches!(mesh.try_property(temperature), Err(Error::Unsupported(_))));
+    }
+}
@@ -0,0 +1,104 @@
+//! Polygon soups: faces as vertex loops before half-edge construction
+//!
+//! Repair passes that the half-edge builder cannot express (reorienting
+//! faces, splitting non-manifold configurations, ...) operate on a
+//! [`PolygonSoup`], which is then turned into a [`Mesh`].
+
+use crate::error::Result;
+use crate::mesh::half_edge::Mesh;
+use std::collections::HashMap;
+
+/// Vertex attributes and polygonal faces given by flattened vertex indices,
+/// in the layout of [`Mesh::from_vertices_and_faces`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolygonSoup<VA> {
+    pub vertices: Vec<VA>,
+    pub face_counts: Vec<usize>,
+    pub face_indices: Vec<usize>,
+}
+
+impl<VA> Default for PolygonSoup<VA> {
+    fn default() -> Self {
+        PolygonSoup { vertices: Vec::new(), face_counts: Vec::new(), face_indices: Vec::new() }
+    }
+}
+
+impl<VA> PolygonSoup<VA> {
+    /// Create a soup from vertex attributes, face-vertex counts and indices
+    pub fn new(vertices: Vec<VA>, face_counts: Vec<usize>, face_indices: Vec<usize>) -> Self {
+        PolygonSoup { vertices, face_counts, face_indices }
+    }
+
+    /// Copy the vertices and live faces of `mesh`
+    pub fn from_mesh<EA, FA>(mesh: &Mesh<VA, EA, FA>) -> Self
+    where
+        VA: Clone,
+    {
+        PolygonSoup {
+            vertices: mesh.vertices.iter().map(|v| v.attr.clone()).collect(),
+            face_counts: mesh.face_vertex_counts(),
+            face_indices: mesh.face_vertex_indices(),
+        }
+    }
+
+    /// Number of faces
+    pub fn face_count(&self) -> usize {
+        self.face_counts.len()
+    }
+
+    /// Start of every face in `face_indices`, plus the total length
+    pub fn face_offsets(&self) -> Vec<usize> {
+        let mut offsets = Vec::with_capacity(self.face_counts.len() + 1);
+        offsets.push(0);
+        for &count in &self.face_counts {
+            offsets.push(offsets[offsets.len() - 1] + count);
+        }
+        offsets
+    }
+
+    /// Vertex loop of every face
+    pub fn faces(&self) -> impl Iterator<Item = &[usize]> + '_ {
+        let mut rest = self.face_indices.as_slice();
+        self.face_counts.iter().map(move |&count| {
+            let (face, tail) = rest.split_at(count.min(rest.len()));
+            rest = tail;
+            face
+        })
+    }
+
+    /// Faces incident to every undirected edge `(min, max)`, each with
+    /// whether the face traverses it from the smaller to the larger vertex
+    pub fn edge_faces(&self) -> HashMap<(usize, usize), Vec<(usize, bool)>> {
+        let mut edges: HashMap<(usize, usize), Vec<(usize, bool)>> = HashMap::new();
+        for (f, face) in self.faces().enumerate() {
+            for k in 0..face.len() {
+                let (a, b) = (face[k], face[(k + 1) % face.len()]);
+                edges.entry((a.min(b), a.max(b))).or_default().push((f, a < b));
+            }
+        }
+        edges
+    }
+
+    /// Build the half-edge mesh, see [`Mesh::from_vertices_and_faces`]
+    pub fn into_mesh<EA: Default, FA: Default>(self) -> Result<Mesh<VA, EA, FA>> {
+        Mesh::from_vertices_and_faces(self.vertices, &self.face_counts, &self.face_indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soup_faces_and_mesh_roundtrip() {
+        let soup = PolygonSoup::new(vec![(); 5], vec![4, 3], vec![0, 1, 2, 3, 1, 4, 2]);
+        assert_eq!(soup.faces().collect::<Vec<_>>(), vec![&[0, 1, 2, 3][..], &[1, 4, 2][..]]);
+        assert_eq!(soup.face_offsets(), vec![0, 4, 7]);
+        let edges = soup.edge_faces();
+        assert_eq!(edges.len(), 6);
+        assert_eq!(edges[&(1, 2)], vec![(0, true), (1, false)]);
+
+        let mesh: Mesh<(), (), ()> = soup.clone().into_mesh().unwrap();
+        assert_eq!(PolygonSoup::from_mesh(&mesh), soup);
+    }
+}
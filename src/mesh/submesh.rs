@@ -0,0 +1,353 @@
+//! Submesh extraction, appending and stitching of meshes
+//!
+//! Operators and algorithms run on whole meshes; restricting them to a
+//! region of interest means extracting that region as a mesh of its own and
+//! mapping the results back through a [`SubmeshMap`]. Pieces are put back
+//! together with [`Mesh::append`] or glued along coincident boundary loops
+//! with [`Mesh::stitch`].
+
+use crate::error::{Error, Result};
+use crate::geometry::vector::Vector3;
+use crate::mesh::editing::IdRemap;
+use crate::mesh::half_edge::{EdgeId, FaceId, HalfEdge, HalfEdgeId, Mesh, MeshBuilder, VertexId};
+
+/// Correspondence between a submesh and the mesh it was extracted from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmeshMap {
+    /// Parent vertex of every submesh vertex
+    pub vertices: Vec<VertexId>,
+    /// Parent face of every submesh face
+    pub faces: Vec<FaceId>,
+    /// Submesh vertex of every parent vertex, `INVALID` outside the selection
+    pub parent_vertices: Vec<VertexId>,
+    /// Submesh face of every parent face, `INVALID` outside the selection
+    pub parent_faces: Vec<FaceId>,
+}
+
+/// Result of [`Mesh::stitch`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StitchMap {
+    /// Vertex of the stitched mesh for every vertex of the first mesh
+    pub first_vertices: Vec<VertexId>,
+    /// Vertex of the stitched mesh for every vertex of the second mesh;
+    /// vertices on a stitched loop map to their twin of the first mesh
+    pub second_vertices: Vec<VertexId>,
+    /// Number of boundary loops glued together
+    pub stitched_loops: usize,
+}
+
+impl<VA: Clone, EA: Clone + Default, FA: Clone + Default> Mesh<VA, EA, FA> {
+    /// Extract the faces `faces` and their vertices as a new mesh, keeping
+    /// vertex, edge and face attributes. Vertices and faces are numbered in
+    /// order of first appearance in the selection; repeated faces are
+    /// ignored.
+    ///
+    /// Returns `Error::Topology` if the selection touches a vertex in two
+    /// separate fans, which cannot be represented on its own.
+    pub fn extract_submesh(&self, faces: &[FaceId]) -> Result<(Self, SubmeshMap)> {
+        let mut parent_vertices = vec![VertexId::INVALID; self.vertices.len()];
+        let mut parent_faces = vec![FaceId::INVALID; self.faces.len()];
+        let mut vertices = Vec::new();
+        let mut selected = Vec::new();
+        let mut builder = MeshBuilder::default();
+        for &f in faces {
+            if self.is_face_deleted(f) {
+                return Err(Error::Topology(format!("face {} is deleted", f.index())));
+            }
+            if parent_faces[f.index()].is_valid() { continue; }
+            let mut ring = Vec::with_capacity(self.face_degree(f));
+            for v in self.face_vertices(f) {
+                if !parent_vertices[v.index()].is_valid() {
+                    parent_vertices[v.index()] = builder.add_vertex(self.vertices[v].attr.clone());
+                    vertices.push(v);
+                }
+                ring.push(parent_vertices[v.index()]);
+            }
+            parent_faces[f.index()] = builder.add_face(&ring);
+            selected.push(f);
+        }
+        let mut submesh = builder.build::<EA, FA>()?;
+        let mut copied = vec![false; submesh.edges.len()];
+        submesh.copy_face_attrs(self, selected.iter().enumerate().map(|(i, &f)| (FaceId::new(i), f)), &mut copied);
+        Ok((submesh, SubmeshMap { vertices, faces: selected, parent_vertices, parent_faces }))
+    }
+
+    /// Copy face attributes, and the edge attributes around them, from the
+    /// `source` face of every `(target, source)` pair. The faces must list
+    /// corresponding vertices starting from their first half-edge; edges
+    /// already marked in `copied` keep their attribute.
+    fn copy_face_attrs(&mut self, source: &Self, pairs: impl Iterator<Item = (FaceId, FaceId)>, copied: &mut [bool]) {
+        for (f, g) in pairs {
+            self.faces[f].attr = source.faces[g].attr.clone();
+            let halfedges: Vec<HalfEdgeId> = self.face_halfedges(f).collect();
+            for (h, k) in halfedges.into_iter().zip(source.face_halfedges(g)) {
+                let e = self.halfedges[h].edge;
+                if !copied[e.index()] {
+                    copied[e.index()] = true;
+                    self.edges[e].attr = source.edges[source.halfedges[k].edge].attr.clone();
+                }
+            }
+        }
+    }
+
+    /// Glue `other` to this mesh along every boundary loop of `other` whose
+    /// vertices all lie within `tolerance` of those of a boundary loop of
+    /// this mesh, and return the result as a new mesh.
+    ///
+    /// The stitched mesh holds the live vertices of this mesh, then those of
+    /// `other` off the stitched loops; its faces are the live faces of this
+    /// mesh followed by those of `other`, with their attributes. Attributes
+    /// of stitched vertices and edges are taken from this mesh. Runtime
+    /// properties of either mesh are not carried over, unlike with
+    /// [`Mesh::append`]; register them again on the result and copy values
+    /// through the [`StitchMap`].
+    ///
+    /// Both meshes must be oriented so that the loops run in opposite
+    /// directions; coincident loops running the same way give
+    /// `Error::Topology`.
+    pub fn stitch(&self, other: &Self, tolerance: f64) -> Result<(Self, StitchMap)>
+    where
+        VA: Copy + Into<Vector3>,
+    {
+        let position = |mesh: &Self, v: VertexId| -> Vector3 { mesh.vertices[v].attr.into() };
+        let coincide = |a: VertexId, b: VertexId| (position(self, a) - position(other, b)).norm() <= tolerance;
+
+        let mut builder = MeshBuilder::default();
+        let mut first_vertices = vec![VertexId::INVALID; self.vertices.len()];
+        for v in self.vertex_ids() {
+            first_vertices[v.index()] = builder.add_vertex(self.vertices[v].attr);
+        }
+        let mut second_vertices = vec![VertexId::INVALID; other.vertices.len()];
+        let mut stitched_loops = 0;
+        let mut unmatched = other.boundary_loops();
+        for boundary in self.boundary_loops() {
+            let n = boundary.len();
+            // the loop of `other` must visit the same points backwards
+            let matches = |candidate: &Vec<VertexId>, step: usize| {
+                candidate.len() == n
+                    && (0..n).find(|&j| coincide(boundary[0], candidate[j])).is_some_and(|j| {
+                        (0..n).all(|k| coincide(boundary[k], candidate[(j + step * k) % n]))
+                    })
+            };
+            if let Some(i) = unmatched.iter().position(|candidate| matches(candidate, n - 1)) {
+                let candidate = unmatched.swap_remove(i);
+                let j = (0..n).find(|&j| coincide(boundary[0], candidate[j])).unwrap_or(0);
+                for (k, &v) in boundary.iter().enumerate() {
+                    second_vertices[candidate[(j + (n - 1) * k) % n].index()] = first_vertices[v.index()];
+                }
+                stitched_loops += 1;
+            } else if unmatched.iter().any(|candidate| matches(candidate, 1)) {
+                return Err(Error::Topology(format!(
+                    "boundary loop at vertex {} coincides with a loop of the same orientation",
+                    boundary[0].index()
+                )));
+            }
+        }
+        for v in other.vertex_ids() {
+            if !second_vertices[v.index()].is_valid() {
+                second_vertices[v.index()] = builder.add_vertex(other.vertices[v].attr);
+            }
+        }
+
+        for f in self.face_ids() {
+            let ring: Vec<VertexId> = self.face_vertices(f).map(|v| first_vertices[v.index()]).collect();
+            builder.add_face(&ring);
+        }
+        for f in other.face_ids() {
+            let ring: Vec<VertexId> = other.face_vertices(f).map(|v| second_vertices[v.index()]).collect();
+            builder.add_face(&ring);
+        }
+        let mut stitched = builder.build::<EA, FA>()?;
+        let offset = self.face_ids().count();
+        let mut copied = vec![false; stitched.edges.len()];
+        stitched.copy_face_attrs(self, self.face_ids().enumerate().map(|(i, f)| (FaceId::new(i), f)), &mut copied);
+        stitched.copy_face_attrs(other, other.face_ids().enumerate().map(|(i, f)| (FaceId::new(offset + i), f)), &mut copied);
+        Ok((stitched, StitchMap { first_vertices, second_vertices, stitched_loops }))
+    }
+}
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Move all elements of `other` into this mesh, after the existing ones,
+    /// without connecting them. Deleted elements of `other` stay deleted.
+    /// Properties are matched by name: values of `other` follow those of
+    /// this mesh, and properties registered on only one of the two meshes
+    /// are padded with defaults. Returns the new id of every element of
+    /// `other`, or `Error::Unsupported`, leaving this mesh unchanged, if a
+    /// property name is registered on both with different types.
+    pub fn append(&mut self, other: Self) -> Result<IdRemap> {
+        self.properties.check_append(&other.properties)?;
+        let (nv, nh, ne, nf) = (self.vertices.len(), self.halfedges.len(), self.edges.len(), self.faces.len());
+        let shift_he = |h: HalfEdgeId| if h.is_valid() { HalfEdgeId::new(h.index() + nh) } else { h };
+        let shift_face = |f: FaceId| if f.is_valid() { FaceId::new(f.index() + nf) } else { f };
+
+        for mut vertex in other.vertices {
+            vertex.he_out = shift_he(vertex.he_out);
+            self.vertices.push(vertex);
+        }
+        for he in other.halfedges {
+            self.halfedges.push(HalfEdge {
+                from: VertexId::new(he.from.index() + nv),
+                twin: shift_he(he.twin),
+                next: shift_he(he.next),
+                edge: EdgeId::new(he.edge.index() + ne),
+                face: shift_face(he.face),
+            });
+        }
+        for mut edge in other.edges {
+            edge.he = shift_he(edge.he);
+            self.edges.push(edge);
+        }
+        for mut face in other.faces {
+            face.he = shift_he(face.he);
+            self.faces.push(face);
+        }
+
+        let deleted = &mut self.deleted;
+        for (flags, theirs, n) in [
+            (&mut deleted.vertices, other.deleted.vertices, nv),
+            (&mut deleted.halfedges, other.deleted.halfedges, nh),
+            (&mut deleted.edges, other.deleted.edges, ne),
+            (&mut deleted.faces, other.deleted.faces, nf),
+        ] {
+            if theirs.contains(&true) {
+                flags.resize(n, false);
+                flags.extend(theirs);
+            }
+        }
+        self.properties.append(other.properties, [nv, nh, ne, nf]);
+        self.sync_properties();
+
+        Ok(IdRemap {
+            vertices: (nv..self.vertices.len()).map(VertexId::new).collect(),
+            halfedges: (nh..self.halfedges.len()).map(HalfEdgeId::new).collect(),
+            edges: (ne..self.edges.len()).map(EdgeId::new).collect(),
+            faces: (nf..self.faces.len()).map(FaceId::new).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Open cylinder of two rows of `n` quads; faces carry their index and
+    /// edges their sorted endpoints
+    fn cylinder(n: usize) -> Mesh<Vector3, (usize, usize), usize> {
+        let angle = |i: usize| 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+        let positions: Vec<Vector3> = (0..3 * n)
+            .map(|v| Vector3::new(angle(v % n).cos(), angle(v % n).sin(), (v / n) as f64))
+            .collect();
+        let indices: Vec<usize> = (0..2 * n)
+            .flat_map(|f| {
+                let (r, i) = (f / n, f % n);
+                [r * n + i, r * n + (i + 1) % n, (r + 1) * n + (i + 1) % n, (r + 1) * n + i]
+            })
+            .collect();
+        let mut mesh: Mesh<Vector3, (usize, usize), usize> =
+            Mesh::from_vertices_and_faces(positions, &vec![4; 2 * n], &indices).unwrap();
+        for (i, face) in mesh.faces.iter_mut().enumerate() {
+            face.attr = i;
+        }
+        for e in mesh.edge_ids().collect::<Vec<_>>() {
+            let [a, b] = mesh.edge_vertices(e);
+            mesh.edges[e].attr = (a.index().min(b.index()), a.index().max(b.index()));
+        }
+        mesh
+    }
+
+    #[test]
+    fn test_extract_and_stitch_cylinder() {
+        let mesh = cylinder(6);
+        let bottom_faces: Vec<FaceId> = (0..6).map(FaceId::new).collect();
+        let top_faces: Vec<FaceId> = (6..12).map(FaceId::new).collect();
+        let (bottom, _) = mesh.extract_submesh(&bottom_faces).unwrap();
+        let (top, map) = mesh.extract_submesh(&top_faces).unwrap();
+        assert_eq!(top.vertices.len(), 12);
+        assert_eq!(top.boundary_loop_count(), 2);
+        assert_eq!(map.faces, top_faces);
+        assert_eq!(map.parent_faces[7], FaceId::new(1));
+        assert_eq!(map.parent_vertices[0], VertexId::INVALID);
+        for (i, &v) in map.vertices.iter().enumerate() {
+            assert_eq!(map.parent_vertices[v.index()], VertexId::new(i));
+            assert_eq!(top.vertices[i].attr, mesh.vertices[v].attr);
+        }
+        for e in top.edge_ids() {
+            let [a, b] = top.edge_vertices(e).map(|v| map.vertices[v.index()].index());
+            assert_eq!(top.edges[e].attr, (a.min(b), a.max(b)));
+        }
+
+        let (stitched, stitch) = bottom.stitch(&top, 1e-9).unwrap();
+        assert_eq!(stitch.stitched_loops, 1);
+        assert!(stitched.is_valid());
+        assert_eq!(stitched.vertices.len(), 18);
+        assert_eq!(stitched.edge_ids().count(), mesh.edge_ids().count());
+        assert_eq!(stitched.boundary_loop_count(), 2);
+        assert_eq!(stitched.faces.iter().map(|f| f.attr).collect::<Vec<_>>(), (0..12).collect::<Vec<_>>());
+        assert!(stitched.edges.iter().all(|e| e.attr != (0, 0)));
+        // the middle ring of the top piece is glued onto the bottom one
+        for (i, &v) in map.vertices.iter().enumerate() {
+            assert_eq!(stitch.second_vertices[i].index() < 12, v.index() < 12);
+        }
+    }
+
+    #[test]
+    fn test_stitch_rejects_loops_of_the_same_orientation() {
+        // a copy of a mesh has every boundary loop running the same way
+        let mesh = cylinder(4);
+        assert!(matches!(mesh.stitch(&cylinder(4), 1e-9), Err(Error::Topology(_))));
+    }
+
+    #[test]
+    fn test_append() {
+        let mut mesh = cylinder(4);
+        let other = cylinder(3);
+        let remap = mesh.append(other).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.vertices.len(), 12 + 9);
+        assert_eq!(mesh.components().count, 2);
+        assert_eq!(remap.vertices[0], VertexId::new(12));
+        assert_eq!(remap.faces[5], FaceId::new(13));
+        assert_eq!(mesh.faces[13].attr, 5);
+    }
+
+    #[test]
+    fn test_append_properties() {
+        let mut mesh = cylinder(4);
+        let weight = mesh.add_vertex_property::<f64>("weight").unwrap();
+        mesh.property_mut(weight).values_mut().fill(1.0);
+        let mut other = cylinder(3);
+        let other_weight = other.add_vertex_property::<f64>("weight").unwrap();
+        other.property_mut(other_weight).values_mut().fill(2.0);
+        let label = other.add_face_property::<u32>("label").unwrap();
+        other.property_mut(label)[FaceId::new(1)] = 7;
+
+        mesh.append(other).unwrap();
+        let weights = mesh.property(weight).values();
+        assert_eq!(weights.len(), 21);
+        assert!(weights[..12].iter().all(|&w| w == 1.0) && weights[12..].iter().all(|&w| w == 2.0));
+        let label = mesh.face_property::<u32>("label").unwrap();
+        assert_eq!(mesh.property(label).values().len(), 14);
+        assert_eq!(mesh.property(label)[FaceId::new(9)], 7);
+        assert_eq!(mesh.property(label)[FaceId::new(1)], 0);
+
+        // a name registered with another type leaves the mesh untouched
+        let mut other = cylinder(3);
+        other.add_vertex_property::<i32>("weight").unwrap();
+        assert!(matches!(mesh.append(other), Err(Error::Unsupported(_))));
+        assert_eq!(mesh.vertices.len(), 21);
+    }
+
+    #[test]
+    fn test_stitch_drops_properties() {
+        let mesh = cylinder(4);
+        let (mut bottom, _) = mesh.extract_submesh(&(0..4).map(FaceId::new).collect::<Vec<_>>()).unwrap();
+        let (mut top, _) = mesh.extract_submesh(&(4..8).map(FaceId::new).collect::<Vec<_>>()).unwrap();
+        bottom.add_vertex_property::<f64>("weight").unwrap();
+        top.add_face_property::<u32>("label").unwrap();
+
+        let (stitched, stitch) = bottom.stitch(&top, 1e-9).unwrap();
+        assert_eq!(stitch.stitched_loops, 1);
+        assert_eq!(stitched.properties().vertices.names().count(), 0);
+        assert_eq!(stitched.properties().faces.names().count(), 0);
+    }
+}
@@ -0,0 +1,236 @@
+//! Tetrahedral meshes
+//!
+//! A [`TetMesh`] stores its tetrahedra as vertex quadruples together with the
+//! unique triangles and edges they share, so volumetric operators can
+//! accumulate per-tetrahedron quantities onto faces, edges and vertices.
+//! Elements are plain indices into the mesh arrays.
+
+use crate::error::{Error, Result};
+use crate::geometry::predicate::orient3d;
+use crate::geometry::real::Real;
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::{Mesh, Mesh3D, INVALID};
+use std::collections::HashMap;
+
+/// Local vertex pairs of the six edges of a tetrahedron
+pub const TET_EDGES: [[usize; 2]; 6] = [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]];
+
+/// Tetrahedral mesh over scalars `T`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TetMesh<T = f64> {
+    /// Vertex positions
+    pub vertices: Vec<Vector3<T>>,
+    /// Vertices of every tetrahedron, positively oriented
+    pub tets: Vec<[usize; 4]>,
+    /// Triangles, with their vertices in ascending order
+    pub faces: Vec<[usize; 3]>,
+    /// Edges, with their vertices in ascending order
+    pub edges: Vec<[usize; 2]>,
+    /// Face opposite to every corner of every tetrahedron
+    pub tet_faces: Vec<[usize; 4]>,
+    /// Edges of every tetrahedron, in the order of [`TET_EDGES`]
+    pub tet_edges: Vec<[usize; 6]>,
+    /// Tetrahedra on either side of every face; `INVALID` on the second
+    /// side of boundary faces
+    pub face_tets: Vec<[usize; 2]>,
+    /// Tetrahedra incident to every vertex
+    pub vertex_tets: Vec<Vec<usize>>,
+}
+
+impl<T: Real> TetMesh<T> {
+    /// Build a tet mesh from vertex positions and vertex quadruples.
+    /// Negatively oriented tetrahedra are reoriented; degenerate ones return
+    /// `Error::Numeric`, faces shared by more than two tetrahedra
+    /// `Error::Topology`.
+    pub fn new(vertices: Vec<Vector3<T>>, tets: &[[usize; 4]]) -> Result<Self> {
+        let n = vertices.len();
+        let mut mesh = TetMesh {
+            vertices,
+            tets: Vec::with_capacity(tets.len()),
+            faces: Vec::new(),
+            edges: Vec::new(),
+            tet_faces: Vec::with_capacity(tets.len()),
+            tet_edges: Vec::with_capacity(tets.len()),
+            face_tets: Vec::new(),
+            vertex_tets: vec![Vec::new(); n],
+        };
+        let mut face_ids: HashMap<[usize; 3], usize> = HashMap::new();
+        let mut edge_ids: HashMap<[usize; 2], usize> = HashMap::new();
+        for (t, &tet) in tets.iter().enumerate() {
+            if let Some(&v) = tet.iter().find(|&&v| v >= n) {
+                return Err(Error::Topology(format!("tetrahedron {} references missing vertex {}", t, v)));
+            }
+            let mut tet = tet;
+            let [a, b, c, d] = tet.map(|v| mesh.vertices[v]);
+            let volume = orient3d(&a, &b, &c, &d);
+            if volume == T::ZERO {
+                return Err(Error::Numeric(format!("tetrahedron {} is degenerate", t)));
+            }
+            if volume < T::ZERO {
+                tet.swap(2, 3);
+            }
+
+            let mut faces = [0; 4];
+            for (corner, face) in faces.iter_mut().enumerate() {
+                let mut key = [tet[(corner + 1) % 4], tet[(corner + 2) % 4], tet[(corner + 3) % 4]];
+                key.sort_unstable();
+                *face = *face_ids.entry(key).or_insert_with(|| {
+                    mesh.faces.push(key);
+                    mesh.face_tets.push([INVALID; 2]);
+                    mesh.faces.len() - 1
+                });
+                let sides = &mut mesh.face_tets[*face];
+                if sides[0] == INVALID {
+                    sides[0] = t;
+                } else if sides[1] == INVALID {
+                    sides[1] = t;
+                } else {
+                    return Err(Error::Topology(format!("face {:?} is shared by more than two tetrahedra", key)));
+                }
+            }
+            let mut edges = [0; 6];
+            for (edge, [i, j]) in edges.iter_mut().zip(TET_EDGES) {
+                let key = [tet[i].min(tet[j]), tet[i].max(tet[j])];
+                *edge = *edge_ids.entry(key).or_insert_with(|| {
+                    mesh.edges.push(key);
+                    mesh.edges.len() - 1
+                });
+            }
+            for &v in &tet {
+                mesh.vertex_tets[v].push(t);
+            }
+            mesh.tets.push(tet);
+            mesh.tet_faces.push(faces);
+            mesh.tet_edges.push(edges);
+        }
+        Ok(mesh)
+    }
+
+    /// Volume of tetrahedron `t`
+    pub fn tet_volume(&self, t: usize) -> T {
+        let [a, b, c, d] = self.tets[t].map(|v| self.vertices[v]);
+        orient3d(&a, &b, &c, &d) / T::from_f64(6.0)
+    }
+
+    /// Total volume
+    pub fn volume(&self) -> T {
+        (0..self.tets.len()).map(|t| self.tet_volume(t)).sum()
+    }
+
+    /// Gradients of the four linear hat functions of tetrahedron `t`,
+    /// in the order of its corners
+    pub fn hat_gradients(&self, t: usize) -> [Vector3<T>; 4] {
+        let tet = self.tets[t];
+        let scale = T::ONE / (T::from_f64(6.0) * self.tet_volume(t));
+        std::array::from_fn(|corner| {
+            // normal of the opposite face, pointing towards the corner
+            let [j, k, l] = [1, 2, 3].map(|o| self.vertices[tet[(corner + o) % 4]]);
+            let normal = (k - j).cross(&(l - j));
+            let normal = if normal.dot(&(self.vertices[tet[corner]] - j)) < T::ZERO { -normal } else { normal };
+            normal * scale
+        })
+    }
+
+    /// Whether face `f` lies on the boundary
+    pub fn is_boundary_face(&self, f: usize) -> bool {
+        self.face_tets[f][1] == INVALID
+    }
+
+    /// Lumped (barycentric) mass matrix diagonal: a quarter of the volume of
+    /// every incident tetrahedron per vertex
+    pub fn lumped_mass(&self) -> Vec<T> {
+        let mut mass = vec![T::ZERO; self.vertices.len()];
+        for (t, tet) in self.tets.iter().enumerate() {
+            let quarter = self.tet_volume(t) / T::from_f64(4.0);
+            for &v in tet {
+                mass[v] += quarter;
+            }
+        }
+        mass
+    }
+
+    /// Boundary surface as a triangle mesh with outward-facing triangles,
+    /// and the tet mesh vertex of every surface vertex.
+    pub fn boundary_surface(&self) -> Result<(Mesh3D<(), (), T>, Vec<usize>)> {
+        let mut surface_ids = vec![INVALID; self.vertices.len()];
+        let mut parents = Vec::new();
+        let mut indices = Vec::new();
+        for f in (0..self.faces.len()).filter(|&f| self.is_boundary_face(f)) {
+            let t = self.face_tets[f][0];
+            let corner = (0..4).find(|&c| self.tet_faces[t][c] == f).unwrap();
+            let tet = self.tets[t];
+            // the cyclically next three corners of a positive tetrahedron
+            // face outward from even corners and inward from odd ones
+            let tri = if corner % 2 == 0 {
+                [tet[(corner + 1) % 4], tet[(corner + 2) % 4], tet[(corner + 3) % 4]]
+            } else {
+                [tet[(corner + 1) % 4], tet[(corner + 3) % 4], tet[(corner + 2) % 4]]
+            };
+            for v in tri {
+                if surface_ids[v] == INVALID {
+                    surface_ids[v] = parents.len();
+                    parents.push(v);
+                }
+                indices.push(surface_ids[v]);
+            }
+        }
+        let positions = parents.iter().map(|&v| self.vertices[v]).collect();
+        let surface = Mesh::from_vertices_and_faces(positions, &vec![3; indices.len() / 3], &indices)?;
+        Ok((surface, parents))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Unit cube split into six tetrahedra around its main diagonal, with
+    /// its vertices stretched by `scale`
+    pub(crate) fn cube(scale: Vector3) -> TetMesh {
+        let vertices = (0..8)
+            .map(|i| Vector3::new((i & 1) as f64 * scale.x, ((i >> 1) & 1) as f64 * scale.y, ((i >> 2) & 1) as f64 * scale.z))
+            .collect();
+        let tets = [[0, 1, 3, 7], [0, 3, 2, 7], [0, 2, 6, 7], [0, 6, 4, 7], [0, 4, 5, 7], [0, 5, 1, 7]];
+        TetMesh::new(vertices, &tets).unwrap()
+    }
+
+    #[test]
+    fn test_cube_adjacency() {
+        let mesh = cube(Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(mesh.tets.len(), 6);
+        assert_eq!(mesh.edges.len(), 19);
+        assert_eq!(mesh.faces.len(), 18);
+        assert!((mesh.volume() - 6.0).abs() < 1e-12);
+        assert!((mesh.lumped_mass().iter().sum::<f64>() - 6.0).abs() < 1e-12);
+        assert_eq!(mesh.vertex_tets[0].len(), 6);
+
+        let (surface, parents) = mesh.boundary_surface().unwrap();
+        assert_eq!(surface.faces.len(), 12);
+        assert_eq!(parents.len(), 8);
+        assert!(surface.validate().is_empty());
+        // outward orientation: the divergence theorem gives the volume
+        let volume: f64 = surface
+            .face_ids()
+            .map(|f| {
+                let p: Vec<Vector3> = surface.face_vertices(f).map(|v| surface.vertices[v].attr).collect();
+                p[0].dot(&p[1].cross(&p[2])) / 6.0
+            })
+            .sum();
+        assert!((volume - 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_non_manifold_face() {
+        let vertices = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(1.0, 1.0, 1.0),
+        ];
+        let result = TetMesh::new(vertices, &[[0, 1, 2, 3], [0, 1, 2, 4], [0, 1, 2, 5]]);
+        assert!(matches!(result, Err(Error::Topology(_))));
+    }
+}
@@ -0,0 +1,208 @@
+//! Topological invariants: connected components, Euler characteristic,
+//! boundary loops, genus and orientability
+//!
+//! All counts ignore elements marked as deleted.
+
+use crate::error::Result;
+use crate::mesh::half_edge::{FaceId, Mesh, MeshBuilder, VertexId, INVALID};
+
+/// Labelling of the connected components of a mesh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    /// Number of components; an isolated vertex is a component of its own
+    pub count: usize,
+    /// Component of every vertex, [`INVALID`] for deleted vertices
+    pub vertex_labels: Vec<usize>,
+    /// Component of every face, [`INVALID`] for deleted faces
+    pub face_labels: Vec<usize>,
+}
+
+/// A mesh extracted from a larger one, with the original id of each of its
+/// vertices
+pub type Submesh<VA, EA, FA> = (Mesh<VA, EA, FA>, Vec<VertexId>);
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Label the connected components by a breadth-first search over edges
+    pub fn components(&self) -> Components {
+        let mut vertex_labels = vec![INVALID; self.vertices.len()];
+        let mut count = 0;
+        let mut queue = Vec::new();
+        for seed in self.vertex_ids() {
+            if vertex_labels[seed.index()] != INVALID { continue; }
+            vertex_labels[seed.index()] = count;
+            queue.push(seed);
+            while let Some(v) = queue.pop() {
+                for w in self.vertex_vertices(v) {
+                    if vertex_labels[w.index()] == INVALID {
+                        vertex_labels[w.index()] = count;
+                        queue.push(w);
+                    }
+                }
+            }
+            count += 1;
+        }
+        let mut face_labels = vec![INVALID; self.faces.len()];
+        for f in self.face_ids() {
+            face_labels[f.index()] = vertex_labels[self.halfedges[self.faces[f].he].from.index()];
+        }
+        Components { count, vertex_labels, face_labels }
+    }
+
+    /// Euler characteristic `V - E + F`
+    pub fn euler_characteristic(&self) -> i64 {
+        let v = self.vertex_ids().count() as i64;
+        let e = self.edge_ids().count() as i64;
+        let f = self.face_ids().count() as i64;
+        v - e + f
+    }
+
+    /// Number of boundary loops, see [`Mesh::boundary_loops`]
+    pub fn boundary_loop_count(&self) -> usize {
+        self.boundary_loops().len()
+    }
+
+    /// Genus of an orientable surface, summed over all components.
+    ///
+    /// Each component satisfies `χ = 2 - 2g - b`, so the total is
+    /// `(2c - χ - b) / 2` for `c` components with `b` boundary loops. Only
+    /// components with at least one face count, so isolated and unreferenced
+    /// vertices do not change the genus.
+    pub fn genus(&self) -> usize {
+        let components = self.components();
+        let mut has_face = vec![false; components.count];
+        for f in self.face_ids() {
+            has_face[components.face_labels[f.index()]] = true;
+        }
+        let c = has_face.iter().filter(|&&h| h).count() as i64;
+        let isolated = self.vertex_ids().filter(|v| !has_face[components.vertex_labels[v.index()]]).count() as i64;
+        let chi = self.euler_characteristic() - isolated;
+        let b = self.boundary_loop_count() as i64;
+        ((2 * c - chi - b) / 2).max(0) as usize
+    }
+
+    /// Whether the mesh is a topological disk: one component with a single
+    /// boundary loop and Euler characteristic 1. This is what harmonic and
+    /// Tutte parameterizations require. Orientability needs no check, since
+    /// half-edge meshes are consistently oriented by construction.
+    pub fn is_disk(&self) -> bool {
+        self.components().count == 1 && self.boundary_loop_count() == 1 && self.euler_characteristic() == 1
+    }
+
+    /// Split the mesh into one mesh per connected component.
+    ///
+    /// Vertex and face attributes are copied, edge attributes are
+    /// reset to their default.
+    pub fn split_components(&self) -> Result<Vec<Submesh<VA, EA, FA>>>
+    where
+        VA: Clone,
+        EA: Default,
+        FA: Clone + Default,
+    {
+        let components = self.components();
+        let mut local = vec![INVALID; self.vertices.len()];
+        let mut vertex_maps: Vec<Vec<VertexId>> = vec![Vec::new(); components.count];
+        for v in self.vertex_ids() {
+            let map = &mut vertex_maps[components.vertex_labels[v.index()]];
+            local[v.index()] = map.len();
+            map.push(v);
+        }
+        let mut builders: Vec<MeshBuilder<VA>> = vertex_maps
+            .iter()
+            .map(|map| MeshBuilder::new(map.iter().map(|&v| self.vertices[v].attr.clone()).collect(), &[], &[]))
+            .collect();
+        let mut face_maps: Vec<Vec<FaceId>> = vec![Vec::new(); components.count];
+        for f in self.face_ids() {
+            let c = components.face_labels[f.index()];
+            let ring: Vec<VertexId> = self.face_vertices(f).map(|v| VertexId::new(local[v.index()])).collect();
+            builders[c].add_face(&ring);
+            face_maps[c].push(f);
+        }
+        builders
+            .into_iter()
+            .zip(vertex_maps)
+            .zip(face_maps)
+            .map(|((builder, vertex_map), face_map)| {
+                let mut mesh = builder.build::<EA, FA>()?;
+                for (face, &original) in mesh.faces.iter_mut().zip(&face_map) {
+                    face.attr = self.faces[original].attr.clone();
+                }
+                Ok((mesh, vertex_map))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `n` x `n` grid of quads with opposite sides identified
+    fn torus(n: usize) -> Mesh<(), (), ()> {
+        let id = |i: usize, j: usize| (i % n) * n + j % n;
+        let mut indices = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                indices.extend_from_slice(&[id(i, j), id(i + 1, j), id(i + 1, j + 1), id(i, j + 1)]);
+            }
+        }
+        Mesh::from_vertices_and_faces(vec![(); n * n], &vec![4; n * n], &indices).unwrap()
+    }
+
+    #[test]
+    fn test_invariants() {
+        let torus = torus(3);
+        assert_eq!(torus.euler_characteristic(), 0);
+        assert_eq!(torus.genus(), 1);
+        assert_eq!(torus.boundary_loop_count(), 0);
+        assert!(!torus.is_disk());
+
+        let tetrahedron = Mesh::<(), (), ()>::from_vertices_and_faces(
+            vec![(); 4], &[3; 4], &[0, 2, 1, 0, 1, 3, 1, 2, 3, 0, 3, 2],
+        ).unwrap();
+        assert_eq!(tetrahedron.euler_characteristic(), 2);
+        assert_eq!(tetrahedron.genus(), 0);
+
+        let square = Mesh::<(), (), ()>::from_vertices_and_faces(vec![(); 4], &[3, 3], &[0, 1, 2, 0, 2, 3]).unwrap();
+        assert!(square.is_disk());
+    }
+
+    #[test]
+    fn test_genus_ignores_isolated_vertices() {
+        // a torus with two unreferenced vertices
+        let torus = torus(4);
+        let mesh = Mesh::<(), (), ()>::from_vertices_and_faces(
+            vec![(); 18], &torus.face_vertex_counts(), &torus.face_vertex_indices(),
+        ).unwrap();
+        assert_eq!(mesh.components().count, 3);
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert_eq!(mesh.genus(), 1);
+    }
+
+    #[test]
+    fn test_split_components() {
+        // a quad, a separate triangle and an isolated vertex
+        let positions = vec![[0.0], [1.0], [2.0], [3.0], [4.0], [5.0], [6.0], [7.0]];
+        let mut mesh = Mesh::<[f64; 1], (), u8>::from_vertices_and_faces(
+            positions, &[3, 4], &[4, 6, 5, 0, 1, 2, 3],
+        ).unwrap();
+        mesh.faces[1].attr = 9;
+        let components = mesh.components();
+        assert_eq!(components.count, 3);
+        assert_eq!(components.vertex_labels, vec![0, 0, 0, 0, 1, 1, 1, 2]);
+        assert_eq!(components.face_labels, vec![1, 0]);
+        assert_eq!(mesh.genus(), 0);
+
+        let parts = mesh.split_components().unwrap();
+        assert_eq!(parts.len(), 3);
+        let (quad, map) = &parts[0];
+        assert_eq!(map, &[0, 1, 2, 3]);
+        assert_eq!(quad.face_vertex_counts(), vec![4]);
+        assert_eq!(quad.faces[0].attr, 9);
+        assert!(quad.is_disk());
+        let (triangle, map) = &parts[1];
+        assert_eq!(map, &[4, 5, 6]);
+        assert_eq!(triangle.face_vertex_indices(), vec![0, 2, 1]);
+        assert_eq!(triangle.vertices[1].attr, [5.0]);
+        assert_eq!(parts[2].0.vertices.len(), 1);
+    }
+}
@@ -0,0 +1,184 @@
+//! Tree–cotree decomposition and homology generators
+//!
+//! A spanning tree of the vertices and a spanning tree of the faces across
+//! the remaining edges ("cotree") leave exactly `2g` edges on a closed
+//! surface of genus `g`. Closing each of them through the tree gives a
+//! primal loop, through the cotree a dual loop; both sets generate the first
+//! homology group.
+
+use crate::error::{Error, Result};
+use crate::mesh::half_edge::{EdgeId, FaceId, HalfEdgeId, Mesh, VertexId};
+use std::collections::VecDeque;
+
+/// Tree–cotree decomposition of a closed connected mesh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeCotree {
+    /// Half-edge from the tree parent to every vertex, `INVALID` at the root
+    pub vertex_parents: Vec<HalfEdgeId>,
+    /// Half-edge crossed from every face to its cotree parent, `INVALID` at
+    /// the root
+    pub face_parents: Vec<HalfEdgeId>,
+    /// Edges in neither tree, one per generator
+    pub generator_edges: Vec<EdgeId>,
+    /// Primal homology generators, as closed head-to-tail half-edge loops
+    pub primal_generators: Vec<Vec<HalfEdgeId>>,
+    /// Dual homology generators, as closed loops of half-edges `h` crossed
+    /// from the face of `h` into the face of its twin
+    pub dual_generators: Vec<Vec<HalfEdgeId>>,
+}
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Tree–cotree decomposition, with breadth-first trees rooted at the
+    /// first vertex and face. Returns `Error::Topology` if the mesh has
+    /// boundary or more than one component.
+    pub fn tree_cotree(&self) -> Result<TreeCotree> {
+        if self.edge_ids().any(|e| self.is_boundary_edge(e)) {
+            return Err(Error::Topology("tree-cotree decomposition of a mesh with boundary".into()));
+        }
+        if self.components().count != 1 {
+            return Err(Error::Topology("tree-cotree decomposition of a disconnected mesh".into()));
+        }
+        let (Some(root_vertex), Some(root_face)) = (self.vertex_ids().next(), self.face_ids().next()) else {
+            return Err(Error::Topology("tree-cotree decomposition of an empty mesh".into()));
+        };
+
+        // primal spanning tree
+        let mut in_tree = vec![false; self.edges.len()];
+        let mut vertex_parents = vec![HalfEdgeId::INVALID; self.vertices.len()];
+        let mut visited = vec![false; self.vertices.len()];
+        visited[root_vertex.index()] = true;
+        let mut queue = VecDeque::from([root_vertex]);
+        while let Some(v) = queue.pop_front() {
+            for h in self.vertex_outgoing_halfedges(v) {
+                let w = self.halfedge_to(h);
+                if !visited[w.index()] {
+                    visited[w.index()] = true;
+                    vertex_parents[w.index()] = h;
+                    in_tree[self.halfedges[h].edge.index()] = true;
+                    queue.push_back(w);
+                }
+            }
+        }
+
+        // dual spanning tree across the edges not in the primal tree
+        let mut in_cotree = vec![false; self.edges.len()];
+        let mut face_parents = vec![HalfEdgeId::INVALID; self.faces.len()];
+        let mut visited = vec![false; self.faces.len()];
+        visited[root_face.index()] = true;
+        let mut queue = VecDeque::from([root_face]);
+        while let Some(f) = queue.pop_front() {
+            for h in self.face_halfedges(f) {
+                let e = self.halfedges[h].edge;
+                let twin = self.halfedges[h].twin;
+                let g = self.halfedges[twin].face;
+                if !in_tree[e.index()] && !visited[g.index()] {
+                    visited[g.index()] = true;
+                    face_parents[g.index()] = twin;
+                    in_cotree[e.index()] = true;
+                    queue.push_back(g);
+                }
+            }
+        }
+
+        let generator_edges: Vec<EdgeId> =
+            self.edge_ids().filter(|e| !in_tree[e.index()] && !in_cotree[e.index()]).collect();
+        let mut primal_generators = Vec::with_capacity(generator_edges.len());
+        let mut dual_generators = Vec::with_capacity(generator_edges.len());
+        for &e in &generator_edges {
+            let h = self.edges[e].he;
+            let twin = self.halfedges[h].twin;
+
+            // h from u to v, then back from v to u through the tree
+            let up_to = |v: VertexId| self.path_to_root(v, |v| vertex_parents[v.index()], |p| self.halfedges[p].from);
+            let (mut up_u, mut up_v) = (up_to(self.halfedges[h].from), up_to(self.halfedge_to(h)));
+            trim_common_tail(&mut up_u, &mut up_v);
+            let mut primal = vec![h];
+            primal.extend(up_v.iter().map(|&p| self.halfedges[p].twin));
+            primal.extend(up_u.iter().rev());
+            primal_generators.push(primal);
+
+            // across h from its face into its twin's, then back through the
+            // cotree
+            let up_to = |f: FaceId| {
+                self.path_to_root(f, |f| face_parents[f.index()], |p| self.halfedges[self.halfedges[p].twin].face)
+            };
+            let (mut up_f, mut up_g) = (up_to(self.halfedges[h].face), up_to(self.halfedges[twin].face));
+            trim_common_tail(&mut up_f, &mut up_g);
+            let mut dual = vec![h];
+            dual.extend(up_g.iter().copied());
+            dual.extend(up_f.iter().rev().map(|&p| self.halfedges[p].twin));
+            dual_generators.push(dual);
+        }
+
+        Ok(TreeCotree { vertex_parents, face_parents, generator_edges, primal_generators, dual_generators })
+    }
+
+    /// Parent links from `start` up to the root of a tree, where `parent`
+    /// gives the link of an element and `step` the element a link leads to
+    fn path_to_root<H: Copy>(
+        &self,
+        start: H,
+        parent: impl Fn(H) -> HalfEdgeId,
+        step: impl Fn(HalfEdgeId) -> H,
+    ) -> Vec<HalfEdgeId> {
+        let mut path = Vec::new();
+        let mut current = start;
+        loop {
+            let link = parent(current);
+            if !link.is_valid() {
+                return path;
+            }
+            path.push(link);
+            current = step(link);
+        }
+    }
+}
+
+/// Remove the links two root paths share
+fn trim_common_tail(a: &mut Vec<HalfEdgeId>, b: &mut Vec<HalfEdgeId>) {
+    while let (Some(x), Some(y)) = (a.last(), b.last()) {
+        if x != y {
+            break;
+        }
+        a.pop();
+        b.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::half_edge::Mesh;
+
+    /// `n` x `m` grid of quads with opposite sides identified
+    fn torus(n: usize, m: usize) -> Mesh<(), (), ()> {
+        let id = |i: usize, j: usize| (i % n) * m + j % m;
+        let mut indices = Vec::new();
+        for i in 0..n {
+            for j in 0..m {
+                indices.extend_from_slice(&[id(i, j), id(i + 1, j), id(i + 1, j + 1), id(i, j + 1)]);
+            }
+        }
+        Mesh::from_vertices_and_faces(vec![(); n * m], &vec![4; n * m], &indices).unwrap()
+    }
+
+    #[test]
+    fn test_torus_generators() {
+        let mesh = torus(6, 4);
+        let decomposition = mesh.tree_cotree().unwrap();
+        assert_eq!(mesh.genus(), 1);
+        assert_eq!(decomposition.generator_edges.len(), 2);
+        for generator in &decomposition.primal_generators {
+            for (k, &h) in generator.iter().enumerate() {
+                let next = generator[(k + 1) % generator.len()];
+                assert_eq!(mesh.halfedge_to(h), mesh.halfedges[next].from);
+            }
+        }
+        for generator in &decomposition.dual_generators {
+            for (k, &h) in generator.iter().enumerate() {
+                let next = generator[(k + 1) % generator.len()];
+                let twin = mesh.halfedges[h].twin;
+                assert_eq!(mesh.halfedges[twin].face, mesh.halfedges[next].face);
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Triangulation of polygonal faces
+//!
+//! Faces are split in place by inserting diagonals, so vertex ids and the
+//! ids of faces that already are triangles do not change. Each polygon keeps
+//! its id for its first triangle; the others are appended and copy its
+//! attribute.
+
+use crate::error::{Error, Result};
+use crate::geometry::predicate::orient2d;
+use crate::geometry::vector::Vector3;
+use crate::mesh::half_edge::{FaceId, HalfEdgeId, Mesh};
+
+/// How [`Mesh::triangulate`] splits a polygon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Triangulation {
+    /// Connect the first vertex of the face to all others. Only correct for
+    /// convex faces.
+    Fan,
+    /// Repeatedly cut off an ear of the polygon projected onto its best-fit
+    /// plane; handles non-convex (but simple) faces.
+    EarClipping,
+}
+
+impl<VA, EA, FA> Mesh<VA, EA, FA>
+where
+    VA: Copy + Into<Vector3>,
+    EA: Default,
+    FA: Clone,
+{
+    /// Split every face with more than three vertices into triangles.
+    /// Stops with the error of the first face [`Mesh::triangulate_face`]
+    /// cannot split; the faces before it stay triangulated.
+    pub fn triangulate(&mut self, method: Triangulation) -> Result<()> {
+        let faces: Vec<FaceId> = self.face_ids().collect();
+        for f in faces {
+            self.triangulate_face(f, method)?;
+        }
+        Ok(())
+    }
+
+    /// Split face `fid` into triangles; does nothing for triangles.
+    ///
+    /// No diagonal is inserted between vertices that another face already
+    /// joins by an edge: the fan moves on to the next corner, ear clipping
+    /// to the next candidate. Returns `Error::Topology` if every diagonal
+    /// cutting off a corner of the remaining polygon already exists.
+    pub fn triangulate_face(&mut self, fid: FaceId, method: Triangulation) -> Result<()> {
+        let apex = self.halfedges[self.faces[fid].he].from;
+        let mut face = fid;
+        while self.face_degree(face) > 3 {
+            let ring: Vec<HalfEdgeId> = self.face_halfedges(face).collect();
+            let n = ring.len();
+            let candidates = match method {
+                Triangulation::Fan => {
+                    let start = ring.iter().position(|&h| self.halfedges[h].from == apex).unwrap_or(0);
+                    (0..n).map(|i| (start + i) % n).collect()
+                }
+                Triangulation::EarClipping => self.ear_candidates(&ring),
+            };
+            let joined = |k: usize| {
+                let (a, b) = (self.halfedges[ring[k]].from, self.halfedges[ring[(k + 2) % n]].from);
+                self.vertex_vertices(a).any(|w| w == b)
+            };
+            let Some(k) = candidates.into_iter().find(|&k| !joined(k)) else {
+                self.sync_properties();
+                return Err(Error::Topology(format!(
+                    "every diagonal of face {} duplicates an existing edge",
+                    face
+                )));
+            };
+            // the triangle ring[k], ring[k + 1] keeps `face`, the rest is new
+            let attr = self.faces[face].attr.clone();
+            self.insert_diagonal(ring[k], ring[(k + 2) % n], attr);
+            face = FaceId::new(self.faces.len() - 1);
+        }
+        self.sync_properties();
+        Ok(())
+    }
+
+    /// Positions in `ring` of the half-edges entering each ear, i.e. a
+    /// convex corner whose triangle contains no other vertex of the polygon,
+    /// in ring order. If the projected polygon is not simple there may be no
+    /// ear; the remaining corners follow from the most to the least convex,
+    /// so the fallback clips the sharpest convex corner first.
+    fn ear_candidates(&self, ring: &[HalfEdgeId]) -> Vec<usize> {
+        let points: Vec<Vector3> = ring.iter().map(|&h| self.vertices[self.halfedges[h].from].attr.into()).collect();
+        let planar = project_to_plane(&points);
+        let n = planar.len();
+        let turn = |k: usize| orient2d(&planar[k], &planar[(k + 1) % n], &planar[(k + 2) % n]);
+        let is_ear = |k: usize| {
+            let (a, b, c) = (planar[k], planar[(k + 1) % n], planar[(k + 2) % n]);
+            turn(k) > 0.0
+                && (0..n)
+                    .filter(|&j| j != k && j != (k + 1) % n && j != (k + 2) % n)
+                    .all(|j| !in_triangle(&planar[j], &a, &b, &c))
+        };
+        let (mut ears, mut rest): (Vec<usize>, Vec<usize>) = (0..n).partition(|&k| is_ear(k));
+        rest.sort_by(|&a, &b| turn(b).total_cmp(&turn(a)));
+        ears.extend(rest);
+        ears
+    }
+}
+
+/// Coordinates of `points` in the plane orthogonal to their Newell normal,
+/// oriented so the polygon winds counter-clockwise.
+fn project_to_plane(points: &[Vector3]) -> Vec<Vector3> {
+    let n = points.len();
+    let normal = (0..n)
+        .fold(Vector3::zero(), |acc, i| acc + points[i].cross(&points[(i + 1) % n]))
+        .normalize();
+    let helper = if normal.x.abs() < 0.9 { Vector3::new(1.0, 0.0, 0.0) } else { Vector3::new(0.0, 1.0, 0.0) };
+    let u = normal.cross(&helper).normalize();
+    let v = normal.cross(&u);
+    points.iter().map(|p| Vector3::new(p.dot(&u), p.dot(&v), 0.0)).collect()
+}
+
+/// Whether `p` lies inside or on the boundary of the counter-clockwise
+/// triangle `abc`
+fn in_triangle(p: &Vector3, a: &Vector3, b: &Vector3, c: &Vector3) -> bool {
+    orient2d(a, b, p) >= 0.0 && orient2d(b, c, p) >= 0.0 && orient2d(c, a, p) >= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::Mesh3D;
+
+    #[test]
+    fn test_fan_triangulation_of_quads() {
+        // two unit quads sharing an edge
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0), Vector3::new(1.0, 1.0, 0.0), Vector3::new(2.0, 1.0, 0.0),
+        ];
+        let mut mesh = Mesh3D::<(), u8>::from_vertices_and_faces(positions, &[4, 4], &[0, 1, 4, 3, 1, 2, 5, 4]).unwrap();
+        mesh.faces[1].attr = 7;
+        mesh.triangulate(Triangulation::Fan).unwrap();
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert_eq!(mesh.face_vertex_counts(), vec![3; 4]);
+        assert_eq!(mesh.edges.len(), 9);
+        assert_eq!(mesh.face_vertices(FaceId::new(0)).collect::<Vec<_>>(), vec![0, 1, 4]);
+        // triangles of the second quad share its attribute
+        assert_eq!(mesh.faces.iter().filter(|f| f.attr == 7).count(), 2);
+        let total: f64 = mesh.vertex_areas().iter().sum();
+        assert!((total - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ear_clipping_non_convex() {
+        // L-shaped hexagon: a fan from its first vertex 2 would leave it
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0), Vector3::new(2.0, 0.0, 0.0), Vector3::new(2.0, 1.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0), Vector3::new(1.0, 2.0, 0.0), Vector3::new(0.0, 2.0, 0.0),
+        ];
+        let mut mesh = Mesh3D::<(), ()>::from_vertices_and_faces(positions, &[6], &[2, 3, 4, 5, 0, 1]).unwrap();
+        mesh.triangulate(Triangulation::EarClipping).unwrap();
+        assert!(mesh.is_valid(), "{:?}", mesh.validate());
+        assert_eq!(mesh.faces.len(), 4);
+        // every triangle is counter-clockwise, so none overlaps the notch
+        for f in mesh.face_ids() {
+            let p: Vec<Vector3> = mesh.face_vertices(f).map(|v| mesh.vertices[v].attr).collect();
+            assert!(orient2d(&p[0], &p[1], &p[2]) > 0.0);
+        }
+        let total: f64 = mesh.vertex_areas().iter().sum();
+        assert!((total - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_triangulation_keeps_existing_diagonals_unique() {
+        // a quad closed off by two triangles along its diagonal 0-2, so the
+        // fan from vertex 0 has to use the other diagonal
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.5), Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.5), Vector3::new(0.0, 1.0, 0.0),
+        ];
+        for method in [Triangulation::Fan, Triangulation::EarClipping] {
+            let mut mesh = Mesh3D::<(), ()>::from_vertices_and_faces(
+                positions.clone(), &[4, 3, 3], &[0, 1, 2, 3, 2, 1, 0, 0, 3, 2],
+            ).unwrap();
+            mesh.triangulate(method).unwrap();
+            assert!(mesh.is_valid(), "{:?}", mesh.validate());
+            let mut pairs: Vec<[usize; 2]> = mesh
+                .edge_ids()
+                .map(|e| {
+                    let [a, b] = mesh.edge_vertices(e).map(|v| v.index());
+                    [a.min(b), a.max(b)]
+                })
+                .collect();
+            pairs.sort();
+            pairs.dedup();
+            assert_eq!(pairs.len(), mesh.edges.len(), "{:?} duplicated an edge", method);
+            assert!(pairs.contains(&[1, 3]));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Half-edge connectivity validation
+
+use crate::error::Error;
+use crate::mesh::half_edge::{FaceId, HalfEdgeId, Mesh};
+
+impl<VA, EA, FA> Mesh<VA, EA, FA> {
+    /// Check the half-edge connectivity for consistency.
+    ///
+    /// Returns one `Error::Topology` per violated invariant, naming the
+    /// offending element ids; an empty list means the mesh is valid. Index
+    /// bounds are checked first and, if any pointer is out of range, only
+    /// those diagnostics are reported since the remaining checks would have
+    /// to follow it; the same holds for pointers to deleted elements.
+    /// Deleted elements themselves are skipped.
+    pub fn validate(&self) -> Vec<Error> {
+        let errors = self.validate_bounds();
+        if !errors.is_empty() {
+            return errors;
+        }
+        let errors = self.validate_deleted_references();
+        if !errors.is_empty() {
+            return errors;
+        }
+        let mut errors = Vec::new();
+        self.validate_halfedges(&mut errors);
+        self.validate_back_pointers(&mut errors);
+        self.validate_vertex_rings(&mut errors);
+        errors
+    }
+
+    /// Whether [`Mesh::validate`] reports no problems
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_empty()
+    }
+
+    fn validate_bounds(&self) -> Vec<Error> {
+        let (nv, nh, ne, nf) = (self.vertices.len(), self.halfedges.len(), self.edges.len(), self.faces.len());
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, what: String| {
+            if !ok {
+                errors.push(Error::Topology(what));
+            }
+        };
+        for v in self.vertex_ids() {
+            let he_out = self.vertices[v].he_out;
+            check(!he_out.is_valid() || he_out.index() < nh,
+                format!("vertex {}: outgoing half-edge {} out of range ({} half-edges)", v, he_out, nh));
+        }
+        for h in self.halfedge_ids() {
+            let he = &self.halfedges[h];
+            check(he.from.index() < nv, format!("half-edge {}: origin vertex {} out of range ({} vertices)", h, he.from, nv));
+            check(he.twin.index() < nh, format!("half-edge {}: twin {} out of range ({} half-edges)", h, he.twin, nh));
+            check(he.next.index() < nh, format!("half-edge {}: next {} out of range ({} half-edges)", h, he.next, nh));
+            check(he.edge.index() < ne, format!("half-edge {}: edge {} out of range ({} edges)", h, he.edge, ne));
+            check(!he.face.is_valid() || he.face.index() < nf,
+                format!("half-edge {}: face {} out of range ({} faces)", h, he.face, nf));
+        }
+        for e in self.edge_ids() {
+            let he = self.edges[e].he;
+            check(he.index() < nh, format!("edge {}: half-edge {} out of range ({} half-edges)", e, he, nh));
+        }
+        for f in self.face_ids() {
+            let he = self.faces[f].he;
+            check(he.index() < nh, format!("face {}: half-edge {} out of range ({} half-edges)", f, he, nh));
+        }
+        errors
+    }
+
+    fn validate_deleted_references(&self) -> Vec<Error> {
+        let mut errors = Vec::new();
+        let mut check = |deleted: bool, what: String| {
+            if deleted {
+                errors.push(Error::Topology(what));
+            }
+        };
+        for v in self.vertex_ids() {
+            let he_out = self.vertices[v].he_out;
+            check(he_out.is_valid() && self.is_halfedge_deleted(he_out),
+                format!("vertex {}: outgoing half-edge {} is deleted", v, he_out));
+        }
+        for h in self.halfedge_ids() {
+            let he = &self.halfedges[h];
+            check(self.is_vertex_deleted(he.from), format!("half-edge {}: origin vertex {} is deleted", h, he.from));
+            check(self.is_halfedge_deleted(he.twin), format!("half-edge {}: twin {} is deleted", h, he.twin));
+            check(self.is_halfedge_deleted(he.next), format!("half-edge {}: next {} is deleted", h, he.next));
+            check(self.is_edge_deleted(he.edge), format!("half-edge {}: edge {} is deleted", h, he.edge));
+            check(he.face.is_valid() && self.is_face_deleted(he.face),
+                format!("half-edge {}: face {} is deleted", h, he.face));
+        }
+        for e in self.edge_ids() {
+            let he = self.edges[e].he;
+            check(self.is_halfedge_deleted(he), format!("edge {}: half-edge {} is deleted", e, he));
+        }
+        for f in self.face_ids() {
+            let he = self.faces[f].he;
+            check(self.is_halfedge_deleted(he), format!("face {}: half-edge {} is deleted", f, he));
+        }
+        errors
+    }
+
+    /// Twin involution, `next` forming a permutation, and per-half-edge
+    /// consistency of origins, edges and faces along `twin` and `next`.
+    fn validate_halfedges(&self, errors: &mut Vec<Error>) {
+        let mut prev_count = vec![0usize; self.halfedges.len()];
+        let mut edge_count = vec![0usize; self.edges.len()];
+        for h in self.halfedge_ids() {
+            let he = &self.halfedges[h];
+            prev_count[he.next.index()] += 1;
+            edge_count[he.edge.index()] += 1;
+            let twin = &self.halfedges[he.twin];
+            if he.twin == h {
+                errors.push(Error::Topology(format!("half-edge {}: is its own twin", h)));
+            } else if twin.twin != h {
+                errors.push(Error::Topology(format!(
+                    "half-edge {}: twin {} has twin {}, expected {}", h, he.twin, twin.twin, h
+                )));
+            }
+            if twin.edge != he.edge {
+                errors.push(Error::Topology(format!(
+                    "half-edge {}: on edge {} but its twin {} is on edge {}", h, he.edge, he.twin, twin.edge
+                )));
+            }
+            let to = self.halfedges[he.next].from;
+            if he.twin != h && twin.from != to {
+                errors.push(Error::Topology(format!(
+                    "half-edge {}: ends at vertex {} but its twin {} starts at vertex {}", h, to, he.twin, twin.from
+                )));
+            }
+            let next_face = self.halfedges[he.next].face;
+            if next_face != he.face {
+                errors.push(Error::Topology(format!(
+                    "half-edge {}: in face {} but its next {} is in face {}",
+                    h, face_name(he.face), he.next, face_name(next_face)
+                )));
+            }
+        }
+        for h in self.halfedge_ids() {
+            let count = prev_count[h.index()];
+            if count != 1 {
+                errors.push(Error::Topology(format!(
+                    "half-edge {}: is the next of {} half-edges, expected 1", h, count
+                )));
+            }
+        }
+        for e in self.edge_ids() {
+            let count = edge_count[e.index()];
+            if count != 2 {
+                errors.push(Error::Topology(format!(
+                    "edge {}: referenced by {} half-edges, expected 2", e, count
+                )));
+            }
+        }
+    }
+
+    /// Vertex, edge and face pointers back into the half-edges, and closed
+    /// `next` loops around every face.
+    fn validate_back_pointers(&self, errors: &mut Vec<Error>) {
+        for v in self.vertex_ids() {
+            let he_out = self.vertices[v].he_out;
+            if he_out.is_valid() && self.halfedges[he_out].from != v {
+                errors.push(Error::Topology(format!(
+                    "vertex {}: outgoing half-edge {} starts at vertex {}",
+                    v, he_out, self.halfedges[he_out].from
+                )));
+            }
+        }
+        for e in self.edge_ids() {
+            let he = self.edges[e].he;
+            if self.halfedges[he].edge != e {
+                errors.push(Error::Topology(format!(
+                    "edge {}: half-edge {} belongs to edge {}", e, he, self.halfedges[he].edge
+                )));
+            }
+        }
+        for f in self.face_ids() {
+            let he = self.faces[f].he;
+            if self.halfedges[he].face != f {
+                errors.push(Error::Topology(format!(
+                    "face {}: half-edge {} belongs to face {}", f, he, face_name(self.halfedges[he].face)
+                )));
+                continue;
+            }
+            if self.walk(he, |he| self.halfedges[he].next).is_none() {
+                errors.push(Error::Topology(format!(
+                    "face {}: next pointers from half-edge {} do not return to it", f, he
+                )));
+            }
+        }
+    }
+
+    /// Manifoldness: every vertex is reached by a single circulation of its
+    /// outgoing half-edges, with at most one boundary gap.
+    fn validate_vertex_rings(&self, errors: &mut Vec<Error>) {
+        let mut valence = vec![0usize; self.vertices.len()];
+        for h in self.halfedge_ids() {
+            valence[self.halfedges[h].from.index()] += 1;
+        }
+        for v in self.vertex_ids() {
+            let he_out = self.vertices[v].he_out;
+            let valence = valence[v.index()];
+            if !he_out.is_valid() {
+                if valence > 0 {
+                    errors.push(Error::Topology(format!(
+                        "vertex {}: has no outgoing half-edge but {} half-edges start there", v, valence
+                    )));
+                }
+                continue;
+            }
+            let ring = match self.walk(he_out, |he| self.halfedges[self.halfedges[he].twin].next) {
+                Some(ring) => ring,
+                None => {
+                    errors.push(Error::Topology(format!(
+                        "vertex {}: one-ring circulation from half-edge {} does not return to it", v, he_out
+                    )));
+                    continue;
+                }
+            };
+            if ring.len() != valence {
+                errors.push(Error::Topology(format!(
+                    "vertex {}: non-manifold, circulation visits {} of its {} outgoing half-edges",
+                    v, ring.len(), valence
+                )));
+            }
+            let gaps = ring.iter().filter(|&&he| self.is_boundary_halfedge(he)).count();
+            if gaps > 1 {
+                errors.push(Error::Topology(format!(
+                    "vertex {}: non-manifold, {} boundary gaps in its one-ring", v, gaps
+                )));
+            }
+        }
+    }
+
+    /// Follow `step` from `start` and return the visited cycle, or `None` if
+    /// it does not come back to `start` within one pass over all half-edges.
+    fn walk(&self, start: HalfEdgeId, step: impl Fn(HalfEdgeId) -> HalfEdgeId) -> Option<Vec<HalfEdgeId>> {
+        let mut cycle = Vec::new();
+        let mut he = start;
+        loop {
+            cycle.push(he);
+            he = step(he);
+            if he == start {
+                return Some(cycle);
+            }
+            if cycle.len() > self.halfedges.len() {
+                return None;
+            }
+        }
+    }
+}
+
+fn face_name(f: FaceId) -> String {
+    if f.is_valid() { f.to_string() } else { "<boundary>".to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::{Mesh3D, Vertex, HalfEdge, Edge, Face};
+
+    fn messages(errors: &[Error]) -> Vec<String> {
+        errors.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_built_meshes() {
+        let square = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 4], &[3,3], &[0,1,2, 0,2,3],
+        ).unwrap();
+        assert!(square.validate().is_empty());
+        let tet = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 4], &[3,3,3,3], &[0,2,1, 0,1,3, 1,2,3, 2,0,3],
+        ).unwrap();
+        assert!(tet.is_valid());
+    }
+
+    #[test]
+    fn test_validate_reports_broken_pointers() {
+        let mut mesh = Mesh3D::<(),()>::from_vertices_and_faces(
+            vec![Vector3::zero(); 4], &[3,3], &[0,1,2, 0,2,3],
+        ).unwrap();
+        // Break the twin of half-edge 0 and the face back-pointer
+        let t = mesh.halfedges[0].twin;
+        mesh.halfedges[0].twin = 0.into();
+        mesh.faces[1].he = 0.into();
+        let errors = messages(&mesh.validate());
+        assert!(errors.iter().any(|m| m.contains("half-edge 0: is its own twin")));
+        assert!(errors.iter().any(|m| m.contains(&format!("half-edge {}: twin 0 has twin 0, expected {}", t, t))));
+        assert!(errors.iter().any(|m| m.contains("face 1: half-edge 0 belongs to face 0")));
+    }
+
+    #[test]
+    fn test_validate_out_of_range_and_hand_built() {
+        // Triangle in the style of the older hand-built tests: no twins
+        let mut mesh = Mesh3D::<(),()>::new();
+        mesh.vertices = vec![
+            Vertex { he_out: 0.into(), attr: Vector3::zero() },
+            Vertex { he_out: 1.into(), attr: Vector3::zero() },
+            Vertex { he_out: 2.into(), attr: Vector3::zero() },
+        ];
+        mesh.halfedges = vec![
+            HalfEdge { from:0.into(), twin:0.into(), next:1.into(), edge:0.into(), face:0.into() },
+            HalfEdge { from:1.into(), twin:1.into(), next:2.into(), edge:1.into(), face:0.into() },
+            HalfEdge { from:2.into(), twin:2.into(), next:0.into(), edge:2.into(), face:0.into() },
+        ];
+        mesh.edges = vec![ Edge { he:0.into(), attr:() }, Edge { he:1.into(), attr:() }, Edge { he:2.into(), attr:() } ];
+        mesh.faces = vec![ Face { he:0.into(), attr:() } ];
+        let errors = messages(&mesh.validate());
+        assert!(errors.iter().any(|m| m.contains("half-edge 2: is its own twin")));
+        assert!(errors.iter().any(|m| m.contains("edge 1: referenced by 1 half-edges, expected 2")));
+
+        mesh.halfedges[1].next = 7.into();
+        let errors = mesh.validate();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(&errors[0], Error::Topology(m) if m.contains("half-edge 1: next 7 out of range")));
+    }
+}
@@ -0,0 +1,174 @@
+//! Sparse matrix assembly
+//!
+//! Stiffness and mass matrices of piecewise linear functions on triangle
+//! meshes, as `sprs` CSR matrices indexed by vertex. Stiffness matrices are
+//! positive semidefinite (`K = -L` for the [`super::laplacian::Laplacian`]
+//! sign convention); weights come from any [`Geometry`], so the same
+//! matrices serve embedded and intrinsic meshes.
+
+use crate::error::{Error, Result};
+use crate::geometry::metric::Geometry;
+use crate::mesh::half_edge::Mesh;
+use sprs::{CsMat, TriMat};
+
+/// Cotangent stiffness matrix: `K_ij = -w_ij`, `K_ii = Σ_j w_ij`
+pub fn cotan_stiffness<G: Geometry + ?Sized, VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, geometry: &G) -> CsMat<f64> {
+    let n = mesh.vertices.len();
+    let mut triplets = TriMat::new((n, n));
+    for e in mesh.edge_ids() {
+        let [i, j] = mesh.edge_vertices(e).map(|v| v.index());
+        let w = geometry.cotan_weight(mesh, e);
+        triplets.add_triplet(i, j, -w);
+        triplets.add_triplet(j, i, -w);
+        triplets.add_triplet(i, i, w);
+        triplets.add_triplet(j, j, w);
+    }
+    triplets.to_csr()
+}
+
+/// Uniform (graph) Laplacian: `K_ij = -1` for neighbours, `K_ii` the
+/// vertex degree
+pub fn uniform_laplacian<VA, EA, FA>(mesh: &Mesh<VA, EA, FA>) -> CsMat<f64> {
+    let n = mesh.vertices.len();
+    let mut triplets = TriMat::new((n, n));
+    for e in mesh.edge_ids() {
+        let [i, j] = mesh.edge_vertices(e).map(|v| v.index());
+        triplets.add_triplet(i, j, -1.0);
+        triplets.add_triplet(j, i, -1.0);
+        triplets.add_triplet(i, i, 1.0);
+        triplets.add_triplet(j, j, 1.0);
+    }
+    triplets.to_csr()
+}
+
+/// Lumped barycentric mass matrix: a third of every incident triangle area
+pub fn barycentric_mass<G: Geometry + ?Sized, VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, geometry: &G) -> CsMat<f64> {
+    let mut areas = vec![0.0; mesh.vertices.len()];
+    for f in mesh.face_ids() {
+        let third = geometry.face_area(mesh, f) / 3.0;
+        for v in mesh.face_vertices(f) {
+            areas[v.index()] += third;
+        }
+    }
+    diagonal(&areas)
+}
+
+/// Lumped mixed Voronoi mass matrix, the areas [`Geometry::vertex_areas`]
+pub fn voronoi_mass<G: Geometry + ?Sized, VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, geometry: &G) -> CsMat<f64> {
+    diagonal(&geometry.vertex_areas(mesh))
+}
+
+/// Consistent Galerkin mass matrix `M_ij = ∫ φ_i φ_j`: `A / 6` on the
+/// diagonal and `A / 12` between the corners of every triangle
+pub fn galerkin_mass<G: Geometry + ?Sized, VA, EA, FA>(mesh: &Mesh<VA, EA, FA>, geometry: &G) -> CsMat<f64> {
+    let n = mesh.vertices.len();
+    let mut triplets = TriMat::new((n, n));
+    for f in mesh.face_ids() {
+        let area = geometry.face_area(mesh, f);
+        let vs: Vec<usize> = mesh.face_vertices(f).map(|v| v.index()).collect();
+        for &i in &vs {
+            for &j in &vs {
+                triplets.add_triplet(i, j, if i == j { area / 6.0 } else { area / 12.0 });
+            }
+        }
+    }
+    triplets.to_csr()
+}
+
+/// Diagonal matrix with `values` on its diagonal
+pub fn diagonal(values: &[f64]) -> CsMat<f64> {
+    let n = values.len();
+    CsMat::new((n, n), (0..=n).collect(), (0..n).collect(), values.to_vec())
+}
+
+/// Product of a CSR matrix with a dense vector
+pub fn mul_vec(matrix: &CsMat<f64>, x: &[f64]) -> Vec<f64> {
+    if !matrix.is_csr() {
+        return mul_vec(&matrix.to_csr(), x);
+    }
+    matrix.outer_iterator().map(|row| row.iter().map(|(j, &a)| a * x[j]).sum()).collect()
+}
+
+/// Solve the symmetric positive definite system `A x = b` by conjugate
+/// gradients, to a relative residual of `tolerance`. Returns
+/// `Error::Numeric` if it does not converge within `max_iterations`.
+pub fn conjugate_gradient(a: &CsMat<f64>, b: &[f64], tolerance: f64, max_iterations: usize) -> Result<Vec<f64>> {
+    let dot = |u: &[f64], v: &[f64]| u.iter().zip(v).map(|(x, y)| x * y).sum::<f64>();
+    let a = a.to_csr();
+    let mut x = vec![0.0; b.len()];
+    let mut r = b.to_vec();
+    let mut p = r.clone();
+    let mut rr = dot(&r, &r);
+    let target = tolerance * tolerance * rr.max(f64::MIN_POSITIVE);
+    for _ in 0..max_iterations {
+        if rr <= target {
+            return Ok(x);
+        }
+        let ap = mul_vec(&a, &p);
+        let alpha = rr / dot(&p, &ap);
+        for k in 0..x.len() {
+            x[k] += alpha * p[k];
+            r[k] -= alpha * ap[k];
+        }
+        let next = dot(&r, &r);
+        for k in 0..p.len() {
+            p[k] = r[k] + next / rr * p[k];
+        }
+        rr = next;
+    }
+    if rr <= target {
+        Ok(x)
+    } else {
+        Err(Error::Numeric(format!("conjugate gradient did not converge in {} iterations", max_iterations)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::vector::Vector3;
+    use crate::mesh::half_edge::Mesh3D;
+
+    /// Unit square split into four triangles around its centre
+    fn square() -> Mesh3D<(), ()> {
+        let positions = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.5, 0.5, 0.0),
+        ];
+        Mesh::from_vertices_and_faces(positions, &[3; 4], &[0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4]).unwrap()
+    }
+
+    #[test]
+    fn test_mass_and_stiffness() {
+        let mesh = square();
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        let geometry = positions.as_slice();
+        let ones = vec![1.0; 5];
+        for mass in [barycentric_mass(&mesh, geometry), voronoi_mass(&mesh, geometry), galerkin_mass(&mesh, geometry)] {
+            let total: f64 = mul_vec(&mass, &ones).iter().sum();
+            assert!((total - 1.0).abs() < 1e-12);
+        }
+        // constants are in the kernel, and the Dirichlet energy of x is the area
+        let stiffness = cotan_stiffness(&mesh, geometry);
+        assert!(mul_vec(&stiffness, &ones).iter().all(|v| v.abs() < 1e-12));
+        let x: Vec<f64> = positions.iter().map(|p| p.x).collect();
+        let energy: f64 = x.iter().zip(mul_vec(&stiffness, &x)).map(|(a, b)| a * b).sum();
+        assert!((energy - 1.0).abs() < 1e-12);
+        assert_eq!(uniform_laplacian(&mesh).get(4, 4), Some(&4.0));
+    }
+
+    #[test]
+    fn test_conjugate_gradient() {
+        let mesh = square();
+        let positions: Vec<Vector3> = mesh.vertices.iter().map(|v| v.attr).collect();
+        let a = &cotan_stiffness(&mesh, positions.as_slice()) + &galerkin_mass(&mesh, positions.as_slice());
+        let expected = vec![1.0, -2.0, 0.5, 3.0, 0.25];
+        let x = conjugate_gradient(&a, &mul_vec(&a, &expected), 1e-12, 100).unwrap();
+        for (x, e) in x.iter().zip(&expected) {
+            assert!((x - e).abs() < 1e-9);
+        }
+    }
+}
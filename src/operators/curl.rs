@@ -1,5 +1,6 @@
 use crate::operators::traits::Operator;
 use crate::mesh::half_edge::Mesh3D;
+use crate::geometry::real::Real;
 use crate::geometry::vector::Vector3;
 
 /// Curl operator: vector field on faces -> vector per vertex
@@ -7,31 +8,26 @@ pub struct Curl;
 
 impl Curl {
     /// Compute discrete curl per vertex: sum over incident faces of field × edge
-    pub fn compute(mesh: &Mesh3D<(), ()>, field: &[Vector3]) -> Vec<Vector3> {
+    pub fn compute<T: Real>(mesh: &Mesh3D<(), (), T>, field: &[Vector3<T>]) -> Vec<Vector3<T>> {
         let n = mesh.vertices.len();
         let mut curl = vec![Vector3::zero(); n];
         // mixed/Voronoi area per vertex
         let area = mesh.vertex_areas();
         // sum field × edge around each vertex
-        for i in 0..n {
-            let mut he = mesh.vertices[i].he_out;
-            loop {
-                let f_id = mesh.halfedges[he].face;
-                let F = field[f_id];
-                let nex = mesh.halfedges[he].next;
-                let j = mesh.halfedges[nex].from;
-                let pi = mesh.vertices[i].attr;
-                let pj = mesh.vertices[j].attr;
-                let e = pj - pi;
-                // cross product F × e
-                let c = F.cross(&e);
-                curl[i] = curl[i] + c;
-                let twin = mesh.halfedges[he].twin;
-                he = mesh.halfedges[twin].next;
-                if he == mesh.vertices[i].he_out { break; }
-            }
+        for v in mesh.vertex_ids() {
+            let i = v.index();
+            let pi = mesh.vertices[v].attr;
+            curl[i] = mesh
+                .vertex_outgoing_halfedges(v)
+                .filter(|&he| !mesh.is_boundary_halfedge(he))
+                .map(|he| {
+                    let e = mesh.vertices[mesh.halfedge_to(he)].attr - pi;
+                    // cross product F × e
+                    field[mesh.halfedges[he].face.index()].cross(&e)
+                })
+                .fold(Vector3::zero(), |acc, c| acc + c);
             // normalize by mixed/Voronoi area
-            if area[i] != 0.0 {
+            if area[i] != T::ZERO {
                 curl[i] = curl[i] / area[i];
             }
         }
@@ -39,8 +35,8 @@ impl Curl {
     }
 }
 
-impl Operator<Vec<Vector3>, Vec<Vector3>> for Curl {
-    fn apply(&self, mesh: &Mesh3D<(), ()>, field: &Vec<Vector3>) -> Vec<Vector3> {
+impl<T: Real> Operator<Vec<Vector3<T>>, Vec<Vector3<T>>, T> for Curl {
+    fn apply(&self, mesh: &Mesh3D<(), (), T>, field: &Vec<Vector3<T>>) -> Vec<Vector3<T>> {
         Curl::compute(mesh, field)
     }
 }
\ No newline at end of file
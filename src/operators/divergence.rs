@@ -1,5 +1,6 @@
 use crate::operators::traits::Operator;
 use crate::mesh::half_edge::{Mesh3D};
+use crate::geometry::real::Real;
 use crate::geometry::vector::Vector3;
 
 /// Divergence operator: vector field on faces -> scalar per vertex
@@ -7,26 +8,23 @@ pub struct Divergence;
 
 impl Divergence {
     /// Compute discrete divergence per vertex: sum of face vector · edge over incident faces
-    pub fn compute(mesh: &Mesh3D<(), ()>, field: &[Vector3]) -> Vec<f64> {
+    pub fn compute<T: Real>(mesh: &Mesh3D<(), (), T>, field: &[Vector3<T>]) -> Vec<T> {
         let n = mesh.vertices.len();
-        let mut div = vec![0.0; n];
+        let mut div = vec![T::ZERO; n];
         // mixed/Voronoi area per vertex
         let area = mesh.vertex_areas();
-        for i in 0..n {
-            let mut he = mesh.vertices[i].he_out;
-            loop {
-                let f_id = mesh.halfedges[he].face;
-                let f = field[f_id];
-                let nex = mesh.halfedges[he].next;
-                let j = mesh.halfedges[nex].from;
-                let pi = mesh.vertices[i].attr;
-                let pj = mesh.vertices[j].attr;
-                let e = pj - pi;
-                div[i] += f.dot(&e);
-                he = mesh.halfedges[mesh.halfedges[he].twin].next;
-                if he == mesh.vertices[i].he_out { break; }
-            }
-            if area[i] != 0.0 {
+        for v in mesh.vertex_ids() {
+            let i = v.index();
+            let pi = mesh.vertices[v].attr;
+            div[i] = mesh
+                .vertex_outgoing_halfedges(v)
+                .filter(|&he| !mesh.is_boundary_halfedge(he))
+                .map(|he| {
+                    let e = mesh.vertices[mesh.halfedge_to(he)].attr - pi;
+                    field[mesh.halfedges[he].face.index()].dot(&e)
+                })
+                .sum();
+            if area[i] != T::ZERO {
                 div[i] /= area[i];
             }
         }
@@ -34,8 +32,8 @@ impl Divergence {
     }
 }
 
-impl Operator<Vec<Vector3>, Vec<f64>> for Divergence {
-    fn apply(&self, mesh: &Mesh3D<(), ()>, field: &Vec<Vector3>) -> Vec<f64> {
+impl<T: Real> Operator<Vec<Vector3<T>>, Vec<T>, T> for Divergence {
+    fn apply(&self, mesh: &Mesh3D<(), (), T>, field: &Vec<Vector3<T>>) -> Vec<T> {
         Divergence::compute(mesh, field)
     }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use crate::operators::traits::Operator;
 use crate::mesh::half_edge::{Mesh3D};
+use crate::geometry::real::Real;
 use crate::geometry::vector::Vector3;
 
 /// Gradient operator: scalar field on vertices -> vector per face
@@ -7,7 +8,7 @@ pub struct Gradient;
 
 impl Gradient {
     /// Compute per-face gradient: ∇f = Σ f_i (N×e_i) / |N|
-    pub fn compute(mesh: &Mesh3D<(), ()>, field: &[f64]) -> Vec<Vector3> {
+    pub fn compute<T: Real>(mesh: &Mesh3D<(), (), T>, field: &[T]) -> Vec<Vector3<T>> {
         let mut result = Vec::with_capacity(mesh.faces.len());
         for face in &mesh.faces {
             let h0 = face.he;
@@ -21,12 +22,12 @@ impl Gradient {
             let v = p2 - p0;
             let n = u.cross(&v);
             let norm_n = n.norm();
-            if norm_n == 0.0 {
+            if norm_n == T::ZERO {
                 result.push(Vector3::zero());
                 continue;
             }
             let e = [p2 - p1, p0 - p2, p1 - p0];
-            let fvals = [field[vs[0]], field[vs[1]], field[vs[2]]];
+            let fvals = [field[vs[0].index()], field[vs[1].index()], field[vs[2].index()]];
             let mut grad = Vector3::zero();
             for i in 0..3 {
                 grad = grad + n.cross(&e[i]) * fvals[i];
@@ -37,8 +38,8 @@ impl Gradient {
     }
 }
 
-impl Operator<Vec<f64>, Vec<Vector3>> for Gradient {
-    fn apply(&self, mesh: &Mesh3D<(), ()>, field: &Vec<f64>) -> Vec<Vector3> {
+impl<T: Real> Operator<Vec<T>, Vec<Vector3<T>>, T> for Gradient {
+    fn apply(&self, mesh: &Mesh3D<(), (), T>, field: &Vec<T>) -> Vec<Vector3<T>> {
         Gradient::compute(mesh, field)
     }
 }
\ No newline at end of file
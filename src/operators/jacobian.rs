@@ -1,5 +1,6 @@
 use crate::operators::traits::Operator;
 use crate::mesh::half_edge::{Mesh3D};
+use crate::geometry::real::Real;
 use crate::geometry::vector::Vector3;
 use crate::geometry::matrix::Matrix3;
 
@@ -8,13 +9,13 @@ use crate::geometry::matrix::Matrix3;
 pub struct Jacobian;
 
 impl Jacobian {
-    pub fn compute(mesh: &Mesh3D<(), ()>, field: &[Vector3]) -> Vec<Matrix3> {
+    pub fn compute<T: Real>(mesh: &Mesh3D<(), (), T>, field: &[Vector3<T>]) -> Vec<Matrix3<T>> {
         let mut result = Vec::with_capacity(mesh.faces.len());
         for face in &mesh.faces {
             let h0 = face.he;
             let h1 = mesh.halfedges[h0].next;
             let h2 = mesh.halfedges[h1].next;
-            let vs = [mesh.halfedges[h0].from, mesh.halfedges[h1].from, mesh.halfedges[h2].from];
+            let vs = [mesh.halfedges[h0].from.index(), mesh.halfedges[h1].from.index(), mesh.halfedges[h2].from.index()];
             let p0 = mesh.vertices[vs[0]].attr;
             let p1 = mesh.vertices[vs[1]].attr;
             let p2 = mesh.vertices[vs[2]].attr;
@@ -22,7 +23,7 @@ impl Jacobian {
             let v = p2 - p0;
             let n = u.cross(&v);
             let norm_n = n.norm();
-            if norm_n == 0.0 {
+            if norm_n == T::ZERO {
                 result.push(Matrix3::identity());
                 continue;
             }
@@ -52,8 +53,8 @@ impl Jacobian {
     }
 }
 
-impl Operator<Vec<Vector3>, Vec<Matrix3>> for Jacobian {
-    fn apply(&self, mesh: &Mesh3D<(), ()>, field: &Vec<Vector3>) -> Vec<Matrix3> {
+impl<T: Real> Operator<Vec<Vector3<T>>, Vec<Matrix3<T>>, T> for Jacobian {
+    fn apply(&self, mesh: &Mesh3D<(), (), T>, field: &Vec<Vector3<T>>) -> Vec<Matrix3<T>> {
         Jacobian::compute(mesh, field)
     }
 }
@@ -1,59 +1,53 @@
 use crate::operators::traits::Operator;
+use crate::geometry::real::Real;
 use crate::geometry::vector::Vector3;
-use crate::mesh::half_edge::Mesh3D;
+use crate::geometry::metric::Geometry;
+use crate::mesh::half_edge::{Mesh, Mesh3D};
 
 /// Cotangent Laplace–Beltrami operator: scalar field on vertices -> scalar per vertex
 pub struct Laplacian;
 
 impl Laplacian {
     /// Compute cotangent Laplacian of a scalar field.
-    /// Positions stored in vertex attr as Vector3.
-    pub fn cotan_laplacian(
-        mesh: &Mesh3D<(), ()>,
-        field: &[f64],
-    ) -> Vec<f64> {
-        let n = mesh.vertices.len();
-        let mut L = vec![0.0; n];
+    /// Positions stored in vertex attr as Vector3; faces must be triangles
+    /// (see `Mesh::triangulate`).
+    pub fn cotan_laplacian<T: Real>(
+        mesh: &Mesh3D<(), (), T>,
+        field: &[T],
+    ) -> Vec<T> {
+        let positions: Vec<Vector3<T>> = mesh.vertices.iter().map(|v| v.attr).collect();
+        Self::cotan_laplacian_with(mesh, positions.as_slice(), field)
+    }
+
+    /// Compute cotangent Laplacian of a scalar field on any [`Geometry`],
+    /// e.g. edge lengths without an embedding.
+    pub fn cotan_laplacian_with<T: Real, G: Geometry<T> + ?Sized, VA, EA, FA>(
+        mesh: &Mesh<VA, EA, FA>,
+        geometry: &G,
+        field: &[T],
+    ) -> Vec<T> {
+        let mut lap = vec![T::ZERO; mesh.vertices.len()];
         // mixed/Voronoi per-vertex areas
-        let A = mesh.vertex_areas();
+        let areas = geometry.vertex_areas(mesh);
 
         // Sum cotangent weights along edges
-        for edge in &mesh.edges {
-            let he0 = edge.he;
-            let he1 = mesh.halfedges[he0].twin;
-            let i = mesh.halfedges[he0].from;
-            let j = mesh.halfedges[he1].from;
-            // opposite vertices
-            let k0 = mesh.halfedges[mesh.halfedges[he0].next].from;
-            let k1 = mesh.halfedges[mesh.halfedges[he1].next].from;
-            let pi = mesh.vertices[i].attr;
-            let pj = mesh.vertices[j].attr;
-            let pk0 = mesh.vertices[k0].attr;
-            let pk1 = mesh.vertices[k1].attr;
-            let cot0 = cotangent(pi, pj, pk0);
-            let cot1 = cotangent(pi, pj, pk1);
-            let w = 0.5 * (cot0 + cot1);
-            L[i] += w * (field[j] - field[i]);
-            L[j] += w * (field[i] - field[j]);
+        for e in mesh.edge_ids() {
+            let [i, j] = mesh.edge_vertices(e).map(|v| v.index());
+            let w = geometry.cotan_weight(mesh, e);
+            lap[i] += w * (field[j] - field[i]);
+            lap[j] += w * (field[i] - field[j]);
         }
 
         // Normalize by mixed areas
-        for i in 0..n {
-            L[i] /= A[i];
+        for (l, a) in lap.iter_mut().zip(areas) {
+            *l /= a;
         }
-        L
+        lap
     }
 }
 
-/// Compute cotangent of angle at vertex pk in triangle (pi, pj, pk)
-fn cotangent(pi: Vector3, pj: Vector3, pk: Vector3) -> f64 {
-    let u = pi - pk;
-    let v = pj - pk;
-    u.dot(&v) / u.cross(&v).norm()
-}
-
-impl Operator<Vec<f64>, Vec<f64>> for Laplacian {
-    fn apply(&self, mesh: &Mesh3D<(), ()>, field: &Vec<f64>) -> Vec<f64> {
+impl<T: Real> Operator<Vec<T>, Vec<T>, T> for Laplacian {
+    fn apply(&self, mesh: &Mesh3D<(), (), T>, field: &Vec<T>) -> Vec<T> {
         Self::cotan_laplacian(mesh, field)
     }
 }
\ No newline at end of file
@@ -5,4 +5,6 @@ pub mod curl;
 pub mod laplacian;
 pub mod curvature;
 pub mod jacobian;
-pub mod shape_operator;
\ No newline at end of file
+pub mod shape_operator;
+pub mod volumetric;
+pub mod assembly;
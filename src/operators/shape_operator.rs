@@ -1,5 +1,6 @@
 use crate::operators::traits::Operator;
-use crate::mesh::half_edge::Mesh3D;
+use crate::mesh::half_edge::{FaceId, Mesh3D};
+use crate::geometry::real::Real;
 use crate::geometry::vector::Vector3;
 use crate::geometry::matrix::Matrix3;
 
@@ -8,7 +9,7 @@ use crate::geometry::matrix::Matrix3;
 pub struct ShapeOperator;
 
 impl ShapeOperator {
-    pub fn compute(mesh: &Mesh3D<(), ()>, positions: &[Vector3]) -> Vec<Matrix3> {
+    pub fn compute<T: Real>(mesh: &Mesh3D<(), (), T>, positions: &[Vector3<T>]) -> Vec<Matrix3<T>> {
         let n = mesh.vertices.len();
         let mut result = vec![Matrix3::identity(); n];
         // compute face normals (unit)
@@ -17,7 +18,7 @@ impl ShapeOperator {
             let h0 = face.he;
             let h1 = mesh.halfedges[h0].next;
             let h2 = mesh.halfedges[h1].next;
-            let vs = [mesh.halfedges[h0].from, mesh.halfedges[h1].from, mesh.halfedges[h2].from];
+            let vs = [mesh.halfedges[h0].from.index(), mesh.halfedges[h1].from.index(), mesh.halfedges[h2].from.index()];
             let p0 = positions[vs[0]];
             let p1 = positions[vs[1]];
             let p2 = positions[vs[2]];
@@ -30,38 +31,38 @@ impl ShapeOperator {
         let mut vertex_normals = vec![Vector3::zero(); n];
         for (f_id, face) in mesh.faces.iter().enumerate() {
             let fnorm = face_normals[f_id];
-            for vid in mesh.face_vertices(f_id) {
-                vertex_normals[vid] = vertex_normals[vid] + fnorm;
+            for vid in mesh.face_vertices(FaceId::new(f_id)) {
+                vertex_normals[vid.index()] = vertex_normals[vid.index()] + fnorm;
             }
         }
         for vn in &mut vertex_normals {
             *vn = vn.normalize();
         }
         // precompute cot weights per edge half sum
-        let mut wmap = vec![0.0; mesh.edges.len()];
+        let mut wmap = vec![T::ZERO; mesh.edges.len()];
         for (e_id, edge) in mesh.edges.iter().enumerate() {
             let he0 = edge.he;
             let he1 = mesh.halfedges[he0].twin;
-            let i = mesh.halfedges[he0].from;
-            let j = mesh.halfedges[he1].from;
-            let k0 = mesh.halfedges[mesh.halfedges[he0].next].from;
-            let k1 = mesh.halfedges[mesh.halfedges[he1].next].from;
+            let i = mesh.halfedges[he0].from.index();
+            let j = mesh.halfedges[he1].from.index();
+            let k0 = mesh.halfedges[mesh.halfedges[he0].next].from.index();
+            let k1 = mesh.halfedges[mesh.halfedges[he1].next].from.index();
             let pi = positions[i];
             let pj = positions[j];
             let pk0 = positions[k0];
             let pk1 = positions[k1];
             let cot0 = (pi - pk0).dot(&(pj - pk0)) / (pi - pk0).cross(&(pj - pk0)).norm();
             let cot1 = (pj - pk1).dot(&(pi - pk1)) / (pj - pk1).cross(&(pi - pk1)).norm();
-            wmap[e_id] = 0.5 * (cot0 + cot1);
+            wmap[e_id] = T::from_f64(0.5) * (cot0 + cot1);
         }
         // Actually, build per-vertex M and C accumulators
-        let mut ms = vec![Matrix3::new([[0.0;3];3]); n];
-        let mut cs = vec![Matrix3::new([[0.0;3];3]); n];
+        let mut ms = vec![Matrix3::zero(); n];
+        let mut cs = vec![Matrix3::zero(); n];
         for (e_id, edge) in mesh.edges.iter().enumerate() {
             let he0 = edge.he;
             let he1 = mesh.halfedges[he0].twin;
-            let i = mesh.halfedges[he0].from;
-            let j = mesh.halfedges[he1].from;
+            let i = mesh.halfedges[he0].from.index();
+            let j = mesh.halfedges[he1].from.index();
             let pi = positions[i];
             let pj = positions[j];
             let e_vec = pj - pi;
@@ -111,8 +112,8 @@ impl ShapeOperator {
     }
 }
 
-impl Operator<Vec<Vector3>, Vec<Matrix3>> for ShapeOperator {
-    fn apply(&self, mesh: &Mesh3D<(), ()>, positions: &Vec<Vector3>) -> Vec<Matrix3> {
+impl<T: Real> Operator<Vec<Vector3<T>>, Vec<Matrix3<T>>, T> for ShapeOperator {
+    fn apply(&self, mesh: &Mesh3D<(), (), T>, positions: &Vec<Vector3<T>>) -> Vec<Matrix3<T>> {
         ShapeOperator::compute(mesh, positions)
     }
 }
@@ -1,7 +1,8 @@
+use crate::geometry::real::Real;
 use crate::mesh::half_edge::Mesh3D;
 
-/// A discrete operator on a mesh field.
-pub trait Operator<Input, Output> {
+/// A discrete operator on a mesh field, over scalars `T` (`f64` by default).
+pub trait Operator<Input, Output, T: Real = f64> {
     /// Apply the operator on the given field over the mesh.
-    fn apply(&self, mesh: &Mesh3D<(), ()>, field: &Input) -> Output;
+    fn apply(&self, mesh: &Mesh3D<(), (), T>, field: &Input) -> Output;
 }
\ No newline at end of file